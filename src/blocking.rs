@@ -0,0 +1,251 @@
+//! A client for devices without an async executor. Instead of running an
+//! event loop, the application calls [`Client::poll`] from its superloop,
+//! which drives the [`Node`] using an [`embedded_can::nb::Can`] controller
+//! and a monotonic [`Clock`].
+//!
+//! Controllers that only implement [`embedded_can::blocking::Can`] can be
+//! wrapped in [`Blocking`], but note that [`Client::poll`] will then block
+//! until a frame is received.
+
+use generic_array::GenericArray;
+
+use crate::{node::Node, time::Instant, well_known::DeviceName, Id, Message, Metadata, NmeaFrame};
+
+/// The number of sent frames that can be waiting to be returned from
+/// [`Client::poll`] when loopback is enabled. This is enough for the longest
+/// possible fast packet message.
+pub const LOOPBACK_LEN: usize = 32;
+
+/// A monotonic tick source, e.g. a hardware timer or the system tick.
+pub trait Clock {
+    fn now(&self) -> Instant;
+}
+
+impl<F> Clock for F
+where
+    F: Fn() -> Instant,
+{
+    fn now(&self) -> Instant {
+        self()
+    }
+}
+
+/// Adapts an [`embedded_can::blocking::Can`] to [`embedded_can::nb::Can`].
+/// Both transmitting and receiving block, so [`nb::Error::WouldBlock`] is
+/// never returned.
+#[derive(Debug)]
+pub struct Blocking<C>(pub C);
+
+impl<C> embedded_can::nb::Can for Blocking<C>
+where
+    C: embedded_can::blocking::Can,
+{
+    type Frame = C::Frame;
+
+    type Error = C::Error;
+
+    fn transmit(&mut self, frame: &Self::Frame) -> nb::Result<Option<Self::Frame>, Self::Error> {
+        self.0.transmit(frame)?;
+        Ok(None)
+    }
+
+    fn receive(&mut self) -> nb::Result<Self::Frame, Self::Error> {
+        Ok(self.0.receive()?)
+    }
+}
+
+pub struct Client<C: embedded_can::nb::Can, K> {
+    node: Node,
+    can: C,
+    clock: K,
+    timestamp: fn(&C::Frame) -> Option<Instant>,
+    /// A frame that the controller did not have room for yet, either
+    /// generated by the node or displaced from the controller by a frame
+    /// with a higher priority.
+    pending: Option<NmeaFrame>,
+    /// Sent frames waiting to be returned from [`Client::poll`].
+    looped: Option<heapless::Deque<NmeaFrame, LOOPBACK_LEN>>,
+    group_no: u8,
+}
+
+impl<C: embedded_can::nb::Can, K> core::fmt::Debug for Client<C, K> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Client")
+            .field("node", &self.node)
+            .field("pending", &self.pending)
+            .field("loopback", &self.looped.is_some())
+            .field("group_no", &self.group_no)
+            .finish()
+    }
+}
+
+impl<C, K> Client<C, K>
+where
+    C: embedded_can::nb::Can,
+    K: Clock,
+{
+    pub fn new(name: impl Into<DeviceName>, can: C, clock: K) -> Self {
+        Self {
+            node: Node::new(name),
+            can,
+            clock,
+            timestamp: |_| None,
+            pending: None,
+            looped: None,
+            group_no: 0,
+        }
+    }
+
+    /// Use hardware timestamps for received frames. The timestamps should be
+    /// on the same time base as the [`Clock`]. By default, frames are
+    /// timestamped by the clock when they are read from the controller.
+    #[must_use]
+    pub fn with_timestamps(mut self, timestamp: fn(&C::Frame) -> Option<Instant>) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    /// If enabled, frames sent with [`Client::send`] are also returned from
+    /// [`Client::poll`], with [`Metadata::local`] set. This gives local
+    /// consumers the same view of the bus as other devices have, since CAN
+    /// controllers do not receive their own frames. Disabled by default.
+    pub fn set_loopback(&mut self, enabled: bool) {
+        match (enabled, &self.looped) {
+            (true, None) => self.looped = Some(heapless::Deque::new()),
+            (false, Some(_)) => self.looped = None,
+            _ => {}
+        }
+    }
+
+    pub fn src(&self) -> u8 {
+        self.node.src()
+    }
+
+    /// The underlying protocol state machine.
+    pub fn node(&self) -> &Node {
+        &self.node
+    }
+
+    /// Send the frames queued by the node until the controller is full.
+    fn flush(&mut self) -> Result<(), C::Error> {
+        while let Some(frame) = self.pending.take().or_else(|| self.node.poll_transmit()) {
+            match self.can.transmit(&frame.to_can_frame()) {
+                Ok(None) => {}
+                Ok(Some(displaced)) => {
+                    // retry on the next flush, since the controller just
+                    // chose another frame over it
+                    self.pending = self.displaced(&displaced);
+                    break;
+                }
+                Err(nb::Error::WouldBlock) => {
+                    self.pending = Some(frame);
+                    break;
+                }
+                Err(nb::Error::Other(e)) => return Err(e),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn displaced(&self, frame: &C::Frame) -> Option<NmeaFrame> {
+        NmeaFrame::from_can_frame(frame, Metadata::local(self.clock.now()))
+    }
+
+    /// Advance the protocol and check for a received frame. This must be
+    /// called regularly, at least as often as frames arrive.
+    pub fn poll(&mut self) -> Result<Option<NmeaFrame>, C::Error> {
+        let now = self.clock.now();
+
+        if !self.node.is_started() {
+            self.node.start(now);
+        }
+
+        self.node.handle_timeout(now);
+        self.flush()?;
+
+        if let Some(frame) = self.looped.as_mut().and_then(|l| l.pop_front()) {
+            return Ok(Some(frame));
+        }
+
+        let frame = match self.can.receive() {
+            Ok(frame) => frame,
+            Err(nb::Error::WouldBlock) => return Ok(None),
+            Err(nb::Error::Other(e)) => return Err(e),
+        };
+
+        let timestamp = (self.timestamp)(&frame).unwrap_or_else(|| self.clock.now());
+
+        let Some(frame) = NmeaFrame::from_can_frame(&frame, Metadata::received(timestamp)) else {
+            return Ok(None);
+        };
+
+        self.node.handle_frame(&frame, self.clock.now());
+        self.flush()?;
+
+        Ok(Some(frame))
+    }
+
+    /// Try to send a frame. Returns [`nb::Error::WouldBlock`] if the address
+    /// claim has not finished yet or if the controller is busy.
+    ///
+    /// If the controller displaces a lower priority frame to make room, that
+    /// frame is sent again before any new frames.
+    pub fn send(&mut self, mut frame: NmeaFrame) -> nb::Result<(), C::Error> {
+        if !self.node.is_ready() {
+            return Err(nb::Error::WouldBlock);
+        }
+
+        self.flush()?;
+
+        if self.pending.is_some() {
+            return Err(nb::Error::WouldBlock);
+        }
+
+        self.node.prepare_transmit(&mut frame);
+
+        if let Some(displaced) = self.can.transmit(&frame.to_can_frame())? {
+            self.pending = self.displaced(&displaced);
+        }
+
+        if let Some(looped) = &mut self.looped {
+            if looped.is_full() {
+                looped.pop_front();
+            }
+
+            frame.meta = Metadata::local(self.clock.now());
+            // cannot fail since we just made room
+            let _ = looped.push_back(frame);
+        }
+
+        Ok(())
+    }
+
+    /// Send a fast packet message. See [`crate::fast_packet`] for more
+    /// information.
+    ///
+    /// Returns [`nb::Error::WouldBlock`] without sending anything if the
+    /// address claim has not finished yet. Otherwise, blocks until all
+    /// frames have been handed to the controller.
+    pub fn send_fast_packet<T>(&mut self, msg: T, prio: u8, dest: u8) -> nb::Result<(), C::Error>
+    where
+        T: Message,
+    {
+        if !self.node.is_ready() {
+            return Err(nb::Error::WouldBlock);
+        }
+
+        let id = Id::new(prio, T::PGN, 0, dest);
+
+        let mut buf: GenericArray<u8, T::EncodedLen> = GenericArray::default();
+
+        self.group_no = self.group_no.wrapping_add(1);
+
+        for fast_packet in msg.encode_to_fast_packets(buf.as_mut_slice(), self.group_no) {
+            let frame = NmeaFrame::new(id, fast_packet.0.as_ref().try_into().unwrap());
+            nb::block!(self.send(frame.clone())).map_err(nb::Error::Other)?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,4 +1,21 @@
+/// Round to the nearest integer, away from zero on ties. `f64::round` is not
+/// available in `core`.
+#[inline]
+pub(crate) fn round(value: f64) -> i64 {
+    if value < 0.0 {
+        (value - 0.5) as i64
+    } else {
+        (value + 0.5) as i64
+    }
+}
+
 pub trait Buf {
+    /// The number of bytes left to read.
+    fn remaining(&self) -> usize;
+
+    /// Skip `n` bytes, e.g. reserved fields.
+    fn advance(&mut self, n: usize);
+
     fn get_u8(&mut self) -> u8;
 
     fn get_i8(&mut self) -> i8;
@@ -33,9 +50,134 @@ pub trait Buf {
             value => Some(value as f32 * precision),
         }
     }
+
+    /// Get an unsigned byte, where `0xff` signals that the value is not
+    /// available.
+    #[inline]
+    fn get_opt_u8(&mut self) -> Option<u8> {
+        match self.get_u8() {
+            u8::MAX => None,
+            value => Some(value),
+        }
+    }
+
+    /// Get an unsigned 2-byte integer, where `0xffff` signals that the value
+    /// is not available.
+    #[inline]
+    fn get_opt_u16(&mut self) -> Option<u16> {
+        match self.get_u16() {
+            u16::MAX => None,
+            value => Some(value),
+        }
+    }
+
+    /// Get an unsigned 4-byte integer, where `0xffffffff` signals that the
+    /// value is not available.
+    #[inline]
+    fn get_opt_u32(&mut self) -> Option<u32> {
+        match self.get_u32() {
+            u32::MAX => None,
+            value => Some(value),
+        }
+    }
+
+    /// Get a floating point number from a fixed point 1-byte unsigned
+    /// representation.
+    #[inline]
+    fn get_fixed_u8(&mut self, precision: f32) -> Option<f32> {
+        self.get_opt_u8().map(|value| value as f32 * precision)
+    }
+
+    /// Get a floating point number from a fixed point 1-byte signed
+    /// representation.
+    #[inline]
+    fn get_fixed_i8(&mut self, precision: f32) -> Option<f32> {
+        match self.get_i8() {
+            i8::MAX => None,
+            value => Some(value as f32 * precision),
+        }
+    }
+
+    /// Same as [`Buf::get_fixed_f32`].
+    #[inline]
+    fn get_fixed_i16(&mut self, precision: f32) -> Option<f32> {
+        self.get_fixed_f32(precision)
+    }
+
+    /// Get a floating point number from a fixed point 2-byte unsigned
+    /// representation.
+    ///
+    /// ```
+    /// # use nmea2000::Buf;
+    /// let mut buf = &[0x10, 0x27, 0xff, 0xff][..];
+    /// assert_eq!(buf.get_fixed_u16(0.01), Some(100.0));
+    /// assert_eq!(buf.get_fixed_u16(0.01), None);
+    /// ```
+    #[inline]
+    fn get_fixed_u16(&mut self, precision: f32) -> Option<f32> {
+        self.get_opt_u16().map(|value| value as f32 * precision)
+    }
+
+    /// Get a floating point number from a fixed point 3-byte signed
+    /// representation.
+    #[inline]
+    fn get_fixed_i24(&mut self, precision: f32) -> Option<f32> {
+        // sign extend
+        match (self.get_i24() << 8) >> 8 {
+            0x7f_ffff => None,
+            value => Some(value as f32 * precision),
+        }
+    }
+
+    /// Get a floating point number from a fixed point 3-byte unsigned
+    /// representation.
+    #[inline]
+    fn get_fixed_u24(&mut self, precision: f32) -> Option<f32> {
+        match self.get_u24() {
+            0xff_ffff => None,
+            value => Some(value as f32 * precision),
+        }
+    }
+
+    /// Get a floating point number from a fixed point 4-byte signed
+    /// representation.
+    #[inline]
+    fn get_fixed_i32(&mut self, precision: f64) -> Option<f64> {
+        match self.get_i32() {
+            i32::MAX => None,
+            value => Some(value as f64 * precision),
+        }
+    }
+
+    /// Get a floating point number from a fixed point 4-byte unsigned
+    /// representation.
+    #[inline]
+    fn get_fixed_u32(&mut self, precision: f64) -> Option<f64> {
+        self.get_opt_u32().map(|value| value as f64 * precision)
+    }
+
+    /// Get a floating point number from a fixed point 8-byte signed
+    /// representation.
+    #[inline]
+    fn get_fixed_i64(&mut self, precision: f64) -> Option<f64> {
+        match self.get_i64() {
+            i64::MAX => None,
+            value => Some(value as f64 * precision),
+        }
+    }
 }
 
 impl Buf for &[u8] {
+    #[inline]
+    fn remaining(&self) -> usize {
+        self.len()
+    }
+
+    #[inline]
+    fn advance(&mut self, n: usize) {
+        self.split_off(..n).unwrap();
+    }
+
     #[inline]
     fn get_u8(&mut self) -> u8 {
         *self.split_off_first().unwrap()
@@ -117,6 +259,128 @@ pub trait BufMut {
         let value = (value / precision) as i16;
         self.put_i16(value);
     }
+
+    /// Fill the next `n` bytes with `0xff`, which is how reserved fields are
+    /// transmitted.
+    fn put_reserved(&mut self, n: usize) {
+        for _ in 0..n {
+            self.put_u8(0xff);
+        }
+    }
+
+    /// Put an unsigned byte, or `0xff` if the value is not available.
+    #[inline]
+    fn put_opt_u8(&mut self, value: Option<u8>) {
+        self.put_u8(value.unwrap_or(u8::MAX));
+    }
+
+    /// Put an unsigned 2-byte integer, or `0xffff` if the value is not
+    /// available.
+    #[inline]
+    fn put_opt_u16(&mut self, value: Option<u16>) {
+        self.put_u16(value.unwrap_or(u16::MAX));
+    }
+
+    /// Put an unsigned 4-byte integer, or `0xffffffff` if the value is not
+    /// available.
+    #[inline]
+    fn put_opt_u32(&mut self, value: Option<u32>) {
+        self.put_u32(value.unwrap_or(u32::MAX));
+    }
+
+    /// Put a floating point number into a fixed point 1-byte unsigned
+    /// representation, rounding to the nearest step.
+    #[inline]
+    fn put_fixed_u8(&mut self, value: Option<f32>, precision: f32) {
+        self.put_u8(match value {
+            Some(value) => round(value as f64 / precision as f64).clamp(0, 0xfd) as u8,
+            None => u8::MAX,
+        });
+    }
+
+    /// Put a floating point number into a fixed point 1-byte signed
+    /// representation, rounding to the nearest step.
+    #[inline]
+    fn put_fixed_i8(&mut self, value: Option<f32>, precision: f32) {
+        self.put_i8(match value {
+            Some(value) => round(value as f64 / precision as f64).clamp(-0x80, 0x7d) as i8,
+            None => i8::MAX,
+        });
+    }
+
+    /// Like [`BufMut::put_fixed_f32`], but rounds to the nearest step and
+    /// encodes [`None`] as not available.
+    #[inline]
+    fn put_fixed_i16(&mut self, value: Option<f32>, precision: f32) {
+        self.put_i16(match value {
+            Some(value) => round(value as f64 / precision as f64).clamp(-0x8000, 0x7ffd) as i16,
+            None => i16::MAX,
+        });
+    }
+
+    /// Put a floating point number into a fixed point 2-byte unsigned
+    /// representation, rounding to the nearest step.
+    #[inline]
+    fn put_fixed_u16(&mut self, value: Option<f32>, precision: f32) {
+        self.put_u16(match value {
+            Some(value) => round(value as f64 / precision as f64).clamp(0, 0xfffd) as u16,
+            None => u16::MAX,
+        });
+    }
+
+    /// Put a floating point number into a fixed point 3-byte signed
+    /// representation, rounding to the nearest step.
+    #[inline]
+    fn put_fixed_i24(&mut self, value: Option<f32>, precision: f32) {
+        self.put_i24(match value {
+            Some(value) => {
+                round(value as f64 / precision as f64).clamp(-0x80_0000, 0x7f_fffd) as i32
+            }
+            None => 0x7f_ffff,
+        });
+    }
+
+    /// Put a floating point number into a fixed point 3-byte unsigned
+    /// representation, rounding to the nearest step.
+    #[inline]
+    fn put_fixed_u24(&mut self, value: Option<f32>, precision: f32) {
+        self.put_u24(match value {
+            Some(value) => round(value as f64 / precision as f64).clamp(0, 0xff_fffd) as u32,
+            None => 0xff_ffff,
+        });
+    }
+
+    /// Put a floating point number into a fixed point 4-byte signed
+    /// representation, rounding to the nearest step.
+    #[inline]
+    fn put_fixed_i32(&mut self, value: Option<f64>, precision: f64) {
+        self.put_i32(match value {
+            Some(value) => {
+                round(value / precision).clamp(i32::MIN as i64, i32::MAX as i64 - 2) as i32
+            }
+            None => i32::MAX,
+        });
+    }
+
+    /// Put a floating point number into a fixed point 4-byte unsigned
+    /// representation, rounding to the nearest step.
+    #[inline]
+    fn put_fixed_u32(&mut self, value: Option<f64>, precision: f64) {
+        self.put_u32(match value {
+            Some(value) => round(value / precision).clamp(0, u32::MAX as i64 - 2) as u32,
+            None => u32::MAX,
+        });
+    }
+
+    /// Put a floating point number into a fixed point 8-byte signed
+    /// representation, rounding to the nearest step.
+    #[inline]
+    fn put_fixed_i64(&mut self, value: Option<f64>, precision: f64) {
+        self.put_i64(match value {
+            Some(value) => round(value / precision).min(i64::MAX - 2),
+            None => i64::MAX,
+        });
+    }
 }
 
 impl BufMut for &mut [u8] {
@@ -146,16 +410,16 @@ impl BufMut for &mut [u8] {
 
     #[inline]
     fn put_u24(&mut self, value: u32) {
-        let mut bytes = [0; 4];
-        bytes[..3].copy_from_slice(&value.to_le_bytes()[..3]);
-        self.split_off_mut(..3).unwrap().copy_from_slice(&bytes);
+        self.split_off_mut(..3)
+            .unwrap()
+            .copy_from_slice(&value.to_le_bytes()[..3]);
     }
 
     #[inline]
     fn put_i24(&mut self, value: i32) {
-        let mut bytes = [0; 4];
-        bytes[..3].copy_from_slice(&value.to_le_bytes()[..3]);
-        self.split_off_mut(..3).unwrap().copy_from_slice(&bytes);
+        self.split_off_mut(..3)
+            .unwrap()
+            .copy_from_slice(&value.to_le_bytes()[..3]);
     }
 
     #[inline]
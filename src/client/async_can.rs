@@ -1,3 +1,5 @@
+use embassy_time::Instant;
+
 /// Until the [`embedded-can`] crate supports async, we need to define our own
 /// trait.
 pub trait AsyncCan {
@@ -8,6 +10,20 @@ pub trait AsyncCan {
     async fn send(&mut self, frame: Self::Frame) -> Result<(), Self::Error>;
 
     async fn receive(&mut self) -> Result<Self::Frame, Self::Error>;
+
+    /// The time at which a received frame was timestamped by the controller,
+    /// if it supports hardware timestamps. Otherwise, the event loop
+    /// timestamps frames when they are received.
+    ///
+    /// The controller's clock has to be mapped to [`embassy_time`], e.g. by
+    /// recording both its counter and [`Instant::now`] at startup. The
+    /// instant is then converted to [`Metadata::timestamp`] like the
+    /// timestamps taken by the event loop.
+    ///
+    /// [`Metadata::timestamp`]: crate::Metadata::timestamp
+    fn timestamp(_frame: &Self::Frame) -> Option<Instant> {
+        None
+    }
 }
 
 impl<T> AsyncCan for &mut T
@@ -24,4 +40,8 @@ where
     async fn receive(&mut self) -> Result<Self::Frame, Self::Error> {
         (*self).receive().await
     }
+
+    fn timestamp(frame: &Self::Frame) -> Option<Instant> {
+        T::timestamp(frame)
+    }
 }
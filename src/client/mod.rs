@@ -1,78 +1,88 @@
-use core::{cmp::Ordering, fmt};
+use core::{cell::RefCell, fmt};
 
 #[cfg(feature = "defmt")]
-use defmt::{debug, warn};
-use embassy_futures::select::{select, Either};
+use defmt::debug;
+use embassy_futures::select::{select3, Either3};
 use embassy_sync::{
-    blocking_mutex::raw::CriticalSectionRawMutex,
+    blocking_mutex::{raw::CriticalSectionRawMutex, Mutex},
     zerocopy_channel::{Channel, Receiver, Sender},
 };
-use embassy_time::{Duration, Timer};
-use embedded_can::Frame;
+use embassy_time::{Duration, Instant, Timer};
 use generic_array::GenericArray;
 
 use crate::{
-    id::DESTINATION_BROADCAST,
-    well_known::{DeviceName, IsoAddressClaim},
-    Id, Message, NmeaFrame,
+    node::Node,
+    service::{pgn_list::PgnLists, Service, Transmit},
+    time,
+    well_known::{
+        pgn_list::{PgnListFunction, MAX_PGN_LIST_LEN},
+        DeviceName,
+    },
+    Id, Message, Metadata, NmeaFrame,
 };
 
 mod async_can;
 
+pub use crate::node::{MAX_SRC, MIN_SRC};
 pub use async_can::AsyncCan;
 
-pub const ADDRESS_CLAIM_TIMEOUT: Duration = Duration::from_millis(250);
-pub const MIN_SRC: u8 = 1;
-pub const MAX_SRC: u8 = 254;
-
-/// NMEA 2000 addresses are distributed to the devices on the bus
-/// by having a new device A sending an address claim message upon
-/// startup. If another device B has the same address, it will
-/// refute the address claim and A will have to choose a new address.
-/// The process is repeated until A has a uniqie address.
-struct AddressClaimState {
-    timer: Option<Timer>,
-}
+pub const ADDRESS_CLAIM_TIMEOUT: Duration =
+    Duration::from_micros(crate::node::ADDRESS_CLAIM_TIMEOUT.as_micros() as u64);
 
-impl AddressClaimState {
-    const fn new() -> Self {
-        Self { timer: None }
-    }
+/// The number of services an [`EventLoop`] can run.
+pub const MAX_SERVICES: usize = 8;
+
+/// A [`Service`] shared between the application and the [`EventLoop`]
+/// running it.
+pub type Shared<S> = Mutex<CriticalSectionRawMutex, RefCell<S>>;
+
+/// [`Service`] for a [`Shared`] one, so that services of different types
+/// can be stored together.
+trait SharedService {
+    fn handle_frame(&self, frame: &NmeaFrame, address: u8, now: time::Instant);
+
+    fn poll_transmit(&self, now: time::Instant) -> Option<Transmit>;
 
-    /// Returns true if the address claim timer is running or has expired,
-    /// both of which indicate that the address claim process has started
-    /// (and might have finished).
-    fn is_started(&self) -> bool {
-        self.timer.is_some()
+    fn poll_timeout(&self) -> Option<time::Instant>;
+}
+
+impl<S: Service> SharedService for Shared<S> {
+    fn handle_frame(&self, frame: &NmeaFrame, address: u8, now: time::Instant) {
+        self.lock(|s| s.borrow_mut().handle_frame(frame, address, now));
     }
 
-    fn restart_timer(&mut self) {
-        self.timer = Some(Timer::after(ADDRESS_CLAIM_TIMEOUT));
+    fn poll_transmit(&self, now: time::Instant) -> Option<Transmit> {
+        self.lock(|s| s.borrow_mut().poll_transmit(now))
     }
-}
 
-impl fmt::Debug for AddressClaimState {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("AddressClaimState")
-            .field("timer", &self.timer.is_some())
-            .finish()
+    fn poll_timeout(&self) -> Option<time::Instant> {
+        self.lock(|s| s.borrow().poll_timeout())
     }
 }
 
+/// Drives a [`Node`] using embassy: frames are exchanged with an
+/// [`AsyncCan`] and the node's timers are backed by [`embassy_time`].
+///
+/// The event loop answers requests for the PGN lists of the device itself.
+/// The PGNs of sent frames are added to the transmit list, while received
+/// message types are registered with [`EventLoop::pgn_lists_mut`].
 pub struct EventLoop<'ch, C: AsyncCan> {
-    name: DeviceName,
-    src: u8,
+    node: Node,
     can: C,
-    address_claim: AddressClaimState,
     rx: Receiver<'ch, CriticalSectionRawMutex, NmeaFrame>,
+    loopback: bool,
+    pgn_lists: PgnLists<MAX_PGN_LIST_LEN>,
+    services: heapless::Vec<&'ch dyn SharedService, MAX_SERVICES>,
+    group_no: u8,
 }
 
 impl<C: AsyncCan> fmt::Debug for EventLoop<'_, C> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("EventLoop")
-            .field("name", &self.name)
-            .field("src", &self.src)
-            .field("address_claim", &self.address_claim)
+            .field("node", &self.node)
+            .field("loopback", &self.loopback)
+            .field("pgn_lists", &self.pgn_lists)
+            .field("services", &self.services.len())
             .finish()
     }
 }
@@ -94,19 +104,12 @@ where
     C: AsyncCan,
 {
     let frame = can.receive().await?;
+    let timestamp = C::timestamp(&frame).unwrap_or_else(Instant::now).into();
 
-    let id = match frame.id() {
-        embedded_can::Id::Extended(extended_id) => Id::from_can_id(extended_id),
-        embedded_can::Id::Standard(_) => return Ok(None), // confused
-    };
-
-    Ok(Some(NmeaFrame::new(
-        id,
-        frame
-            .data()
-            .try_into()
-            .expect("frame data should not be larger than 8 bytes"),
-    )))
+    Ok(NmeaFrame::from_can_frame(
+        &frame,
+        Metadata::received(timestamp),
+    ))
 }
 
 pub fn new<'ch, C: AsyncCan>(
@@ -124,7 +127,12 @@ pub fn new<'ch, C: AsyncCan>(
 
 impl<'ch, C: AsyncCan> EventLoop<'ch, C> {
     pub fn src(&self) -> u8 {
-        self.src
+        self.node.src()
+    }
+
+    /// The underlying protocol state machine.
+    pub fn node(&self) -> &Node {
+        &self.node
     }
 
     pub fn from_receiver(
@@ -133,113 +141,177 @@ impl<'ch, C: AsyncCan> EventLoop<'ch, C> {
         rx: Receiver<'ch, CriticalSectionRawMutex, NmeaFrame>,
     ) -> Self {
         Self {
-            name: name.into(),
-            src: MIN_SRC,
+            node: Node::new(name),
             can,
-            address_claim: AddressClaimState::new(),
             rx,
+            loopback: false,
+            pgn_lists: PgnLists::new(),
+            services: heapless::Vec::new(),
+            group_no: 0,
         }
     }
 
-    fn incr_src(&mut self) {
-        if self.src > MAX_SRC {
-            self.src = MIN_SRC;
-        } else {
-            self.src += 1;
-        }
+    /// Run a service, such as a
+    /// [`SwitchBank`](crate::service::switch_bank::SwitchBank), alongside
+    /// the node. It is passed the received frames and the messages it
+    /// returns are sent once the address claim has finished. Fails if
+    /// [`MAX_SERVICES`] services are already running.
+    #[allow(clippy::result_unit_err)]
+    pub fn add_service<S: Service>(&mut self, service: &'ch Shared<S>) -> Result<(), ()> {
+        self.services.push(service).map_err(|_| ())
+    }
+
+    /// If enabled, frames sent by the [`Client`] are also returned from
+    /// [`EventLoop::poll`] once they have been sent, with
+    /// [`Metadata::local`] set. This gives local consumers the same view of
+    /// the bus as other devices have, since CAN controllers do not receive
+    /// their own frames. Disabled by default.
+    pub fn set_loopback(&mut self, enabled: bool) {
+        self.loopback = enabled;
+    }
+
+    /// The PGN lists sent in reply to requests. The message types that the
+    /// application receives should be registered here.
+    pub fn pgn_lists_mut(&mut self) -> &mut PgnLists<MAX_PGN_LIST_LEN> {
+        &mut self.pgn_lists
     }
 
-    async fn handle_system_message(&mut self, frame: &NmeaFrame) -> Result<(), Error<C>> {
-        if frame.id.pgn() == IsoAddressClaim::PGN {
-            self.handle_incoming_address_claim(
-                frame.id.source(),
-                IsoAddressClaim::decode(&frame.data).unwrap(),
-            )
-            .await
-            .map_err(Error::Can)?;
+    /// Send all frames queued by the node.
+    async fn flush(&mut self) -> Result<(), C::Error> {
+        while let Some(frame) = self.node.poll_transmit() {
+            self.can.send(frame.to_can_frame()).await?;
         }
 
         Ok(())
     }
 
-    async fn handle_incoming_address_claim(
-        &mut self,
-        src: u8,
-        claim: IsoAddressClaim,
-    ) -> Result<(), C::Error> {
-        #[cfg(feature = "defmt")]
-        debug!("Received ISO Address Claim from {}", src);
+    /// Send a message on behalf of the event loop itself.
+    async fn send_transmit(&mut self, transmit: &Transmit) -> Result<(), C::Error> {
+        self.group_no = self.group_no.wrapping_add(1);
+
+        for mut frame in transmit.frames(self.group_no) {
+            self.node.prepare_transmit(&mut frame);
+            self.can.send(frame.to_can_frame()).await?;
+        }
+
+        // a full list is not an error, the lists are best effort
+        let _ = self
+            .pgn_lists
+            .register(PgnListFunction::Transmit, transmit.id.pgn());
+
+        Ok(())
+    }
 
-        if src != self.src {
-            // ignore claims from other sources than our own
+    /// Send the messages of the services that are due.
+    async fn send_services(&mut self, now: time::Instant) -> Result<(), C::Error> {
+        if !self.node.is_ready() {
             return Ok(());
         }
 
-        match self.name.cmp(&claim.name) {
-            Ordering::Less => {
-                // re-claim address
-                #[cfg(feature = "defmt")]
-                debug!("Reclaiming address {}", src);
-                self.send_address_claim().await?;
-            }
-            Ordering::Equal => {
-                // that's us, do nothing. this should not happen
-                #[cfg(feature = "defmt")]
-                warn!(
-                    "received address claim from a device with the same name as ours: {}",
-                    claim.name
-                );
-            }
-            Ordering::Greater => {
-                // another device has an address with a greater priority, so
-                // we cede the address to them and keep looking for another
-                self.incr_src();
-                self.start_address_claim().await?;
+        for i in 0..self.services.len() {
+            while let Some(transmit) = self.services[i].poll_transmit(now) {
+                self.send_transmit(&transmit).await?;
             }
         }
 
         Ok(())
     }
 
-    async fn start_address_claim(&mut self) -> Result<(), C::Error> {
-        self.send_address_claim().await?;
-        self.address_claim.restart_timer();
+    /// Send the PGN lists that were requested.
+    async fn send_pgn_lists(&mut self, now: time::Instant) -> Result<(), C::Error> {
+        while let Some(transmit) = Service::poll_transmit(&mut self.pgn_lists, now) {
+            self.send_transmit(&transmit).await?;
+        }
+
         Ok(())
     }
 
+    /// The earliest instant at which the node or a service has to be
+    /// woken up. Services cannot send before the address claim has
+    /// finished.
+    fn poll_timeout(&self) -> Option<time::Instant> {
+        let services = self.node.is_ready().then_some(&self.services[..]);
+
+        services
+            .into_iter()
+            .flatten()
+            .filter_map(|s| s.poll_timeout())
+            .chain(self.node.poll_timeout())
+            .min()
+    }
+
     pub async fn send_address_claim(&mut self) -> Result<(), C::Error> {
-        let id = Id::new(6, IsoAddressClaim::PGN, self.src, DESTINATION_BROADCAST);
-        let frame = NmeaFrame::from_message(id, &IsoAddressClaim { name: self.name });
-        self.can.send(frame.to_can_frame()).await
+        self.node.send_address_claim();
+        self.flush().await
     }
 
     pub async fn poll(&mut self) -> Result<NmeaFrame, Error<C>> {
         loop {
-            if !self.address_claim.is_started() {
-                self.start_address_claim().await.map_err(Error::Can)?;
+            let now = Instant::now().into();
+
+            if !self.node.is_started() {
+                self.node.start(now);
             }
 
-            let send_fut = async {
-                // wait for the address claim timer to expire before sending
-                if let Some(timer) = &mut self.address_claim.timer {
-                    timer.await;
+            self.node.handle_timeout(now);
+            self.flush().await.map_err(Error::Can)?;
+            self.send_services(now).await.map_err(Error::Can)?;
+
+            let timeout = self.poll_timeout();
+            let timer_fut = async {
+                match timeout {
+                    Some(deadline) => Timer::at(deadline.into()).await,
+                    None => core::future::pending().await,
                 }
+            };
 
-                self.rx.receive().await
+            // wait for the address claim to finish before sending
+            let is_ready = self.node.is_ready();
+            let send_fut = async {
+                if is_ready {
+                    self.rx.receive().await
+                } else {
+                    core::future::pending().await
+                }
             };
 
-            match select(send_fut, receive_n2k(&mut self.can)).await {
-                Either::First(f) => {
+            match select3(timer_fut, send_fut, receive_n2k(&mut self.can)).await {
+                Either3::First(()) => {}
+                Either3::Second(f) => {
                     #[cfg(feature = "defmt")]
                     debug!("Sending frame");
 
-                    f.id.set_source(self.src);
+                    self.node.prepare_transmit(f);
                     self.can.send(f.to_can_frame()).await.map_err(Error::Can)?;
+                    let _ = self
+                        .pgn_lists
+                        .register(PgnListFunction::Transmit, f.id.pgn());
+
+                    let looped = self.loopback.then(|| NmeaFrame {
+                        meta: Metadata::local(Instant::now().into()),
+                        ..f.clone()
+                    });
                     self.rx.receive_done();
+
+                    if let Some(f) = looped {
+                        return Ok(f);
+                    }
                 }
-                Either::Second(res) => {
+                Either3::Third(res) => {
                     if let Some(f) = res.map_err(Error::Can)? {
-                        self.handle_system_message(&f).await?;
+                        let now = Instant::now().into();
+                        self.node.handle_frame(&f, now);
+                        self.flush().await.map_err(Error::Can)?;
+
+                        for service in &self.services {
+                            service.handle_frame(&f, self.node.src(), now);
+                        }
+                        self.send_services(now).await.map_err(Error::Can)?;
+
+                        if self.node.is_ready() && self.pgn_lists.handle_frame(&f, self.node.src())
+                        {
+                            self.send_pgn_lists(now).await.map_err(Error::Can)?;
+                        }
 
                         return Ok(f);
                     }
@@ -0,0 +1,78 @@
+use generic_array::typenum::Unsigned;
+
+use crate::{
+    fast_packet::{self, FastPacket},
+    Id, Message, Metadata, NmeaFrame,
+};
+
+/// A decoded message along with the identifier and metadata of the frame it
+/// was received in. For fast packets, these are taken from the last frame.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Received<T> {
+    pub id: Id,
+    pub meta: Metadata,
+    pub msg: T,
+}
+
+/// Decodes messages of type `T` from a stream of frames. Messages longer
+/// than 8 bytes are assumed to be sent as fast packets and are reassembled
+/// before being decoded. Up to `SOURCES` devices can be sending fast packets
+/// concurrently; if more do, the least recently started reassembly is
+/// dropped.
+pub struct Decoder<T: Message, const SOURCES: usize> {
+    /// Fast packet readers, keyed by source address.
+    readers: heapless::Vec<(u8, fast_packet::Reader<T>), SOURCES>,
+}
+
+impl<T: Message, const SOURCES: usize> Default for Decoder<T, SOURCES> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Message, const SOURCES: usize> Decoder<T, SOURCES> {
+    pub const fn new() -> Self {
+        Self {
+            readers: heapless::Vec::new(),
+        }
+    }
+
+    fn reader(&mut self, src: u8) -> &mut fast_packet::Reader<T> {
+        let i = match self.readers.iter().position(|(s, _)| *s == src) {
+            Some(i) => i,
+            None => {
+                if self.readers.is_full() {
+                    self.readers.remove(0);
+                }
+
+                // cannot fail since we just made room
+                let _ = self.readers.push((src, fast_packet::Reader::new()));
+                self.readers.len() - 1
+            }
+        };
+
+        &mut self.readers[i].1
+    }
+
+    /// Feed a frame to the decoder. Frames with another PGN than
+    /// [`Message::PGN`] are ignored.
+    pub fn decode(&mut self, frame: &NmeaFrame) -> Option<Result<Received<T>, T::DecodeError>> {
+        if frame.id.pgn() != T::PGN {
+            return None;
+        }
+
+        let res = if T::EncodedLen::USIZE <= 8 {
+            T::decode(&frame.data)
+        } else {
+            let packet = frame.data.as_slice().try_into().ok()?;
+            self.reader(frame.id.source()).read(FastPacket(packet))?
+        };
+
+        Some(res.map(|msg| Received {
+            id: frame.id,
+            meta: frame.meta,
+            msg,
+        }))
+    }
+}
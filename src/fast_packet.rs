@@ -2,11 +2,19 @@
 //! messages into multiple frames, so-called Fast Packets. Each frame contains
 //! some group number, frame number, and the actual data. The first frame also
 //! contains the length of the total message transmitted ([`FastPacket::total_len`]).
+//!
+//! The first byte of each frame holds the group number in its upper 3 bits and
+//! the frame number in its lower 5 bits, so a message spans at most 32 frames
+//! and is at most [`MAX_LEN`] bytes long.
 
 use generic_array::{typenum::Unsigned, GenericArray};
 
 use crate::Message;
 
+/// The maximum length of a message sent as a fast packet: 6 bytes in the
+/// first frame and 7 bytes in each of the 31 following frames.
+pub const MAX_LEN: usize = 223;
+
 /// See the [module-level documentation](self) for more information.
 pub struct FastPacket(pub [u8; 8]);
 
@@ -15,7 +23,7 @@ impl FastPacket {
     #[inline]
     #[must_use]
     pub const fn frame_no(&self) -> u8 {
-        self.0[0] & 0b1111
+        self.0[0] & 0b1_1111
     }
 
     /// The group number of the frames. All frames of the same group can be
@@ -23,7 +31,7 @@ impl FastPacket {
     #[inline]
     #[must_use]
     pub const fn group_no(&self) -> u8 {
-        self.0[0] >> 4
+        self.0[0] >> 5
     }
 
     #[inline]
@@ -71,6 +79,8 @@ pub struct Reader<T: Message> {
     buf: GenericArray<u8, T::EncodedLen>,
     group_no: u8,
     buf_pos: usize,
+    /// The total length of the message being read, at most `T::EncodedLen`.
+    len: usize,
     _marker: core::marker::PhantomData<T>,
 }
 
@@ -90,9 +100,10 @@ where
     pub fn new() -> Self {
         Self {
             buf: Default::default(),
-            // since the group number is 4 bits, this will always be different from the first group's number
+            // since the group number is 3 bits, this will always be different from the first group's number
             group_no: 0xff,
             buf_pos: 0,
+            len: 0,
             _marker: core::marker::PhantomData,
         }
     }
@@ -102,23 +113,25 @@ where
     }
 
     const fn bytes_remaining(&self) -> usize {
-        T::EncodedLen::USIZE - self.buf_pos
+        self.len - self.buf_pos
     }
 
     /// Reads a fast packet and tries to decode the message if all frames have been received.
     ///
     /// Packets that belong to a different group than the previous packet are ignored unless
-    /// they are the first packet of a new group.
+    /// they are the first packet of a new group. Messages longer than `T::EncodedLen` are
+    /// ignored as well.
     pub fn read(&mut self, packet: FastPacket) -> Option<Result<T, T::DecodeError>> {
         if packet.group_no() != self.group_no {
-            if packet.is_first() {
-                if packet.total_len() != Some(T::EncodedLen::U8) {
+            if let Some(total_len) = packet.total_len() {
+                if usize::from(total_len) > T::EncodedLen::USIZE {
                     // should we return an error here?
                     return None;
                 }
 
                 self.group_no = packet.group_no();
                 self.buf_pos = 0;
+                self.len = total_len.into();
             } else {
                 return None;
             }
@@ -134,8 +147,10 @@ where
         self.buf[self.buf_pos..self.buf_pos + data.len()].copy_from_slice(data);
         self.buf_pos += data.len();
 
-        if self.buf_pos == T::EncodedLen::USIZE {
-            Some(T::decode(&self.buf))
+        if self.buf_pos == self.len {
+            // the next group must start with a first frame
+            self.group_no = 0xff;
+            Some(T::decode(&self.buf[..self.len]))
         } else {
             None
         }
@@ -150,18 +165,19 @@ pub struct Iter<'a> {
 }
 
 impl<'a> Iter<'a> {
-    /// Creates a new iterator over the given data.
+    /// Creates a new iterator over the given data. Only the lower 3 bits of
+    /// `group_no` are used, so a counter that is incremented for each
+    /// message can be passed as it is.
     ///
     /// # Panics
     ///
-    /// Panics if the data is longer than 255 bytes.
+    /// Panics if the data is longer than [`MAX_LEN`] bytes.
     pub fn new(buf: &'a [u8], group_no: u8) -> Self {
-        debug_assert!(buf.len() <= 255, "data too big");
-        debug_assert!(group_no < 16, "group_no too big");
+        debug_assert!(buf.len() <= MAX_LEN, "data too big");
 
         Self {
             buf,
-            group_no,
+            group_no: group_no & 0b111,
             frame_no: 0,
         }
     }
@@ -171,18 +187,18 @@ impl<'a> Iterator for Iter<'a> {
     type Item = FastPacket;
 
     fn next(&mut self) -> Option<Self::Item> {
+        if self.buf.is_empty() && self.frame_no > 0 {
+            // EOF
+            return None;
+        }
+
         let mut packet = FastPacket([0; 8]);
-        packet.0[0] = (self.group_no << 4) | self.frame_no;
+        packet.0[0] = (self.group_no << 5) | self.frame_no;
 
         if packet.is_first() {
             packet.0[1] = self.buf.len() as u8;
         }
 
-        if self.buf.is_empty() && !packet.is_first() {
-            // EOF
-            return None;
-        }
-
         let dest = packet.data_mut();
         let len = self.buf.len().min(dest.len());
 
@@ -198,9 +214,9 @@ impl<'a> Iterator for Iter<'a> {
 mod tests {
     use generic_array::typenum;
 
-    use crate::Message;
+    use crate::{decoder::Decoder, Id, Message, NmeaFrame};
 
-    use super::FastPacket;
+    use super::{FastPacket, MAX_LEN};
 
     #[test]
     fn read_fast_packets() {
@@ -238,4 +254,52 @@ mod tests {
         assert_eq!(reader.read(p1), None);
         assert_eq!(reader.read(p2), Some(Ok(TestMessage)));
     }
+
+    #[test]
+    fn max_len() {
+        #[derive(Debug, PartialEq)]
+        struct LongMessage([u8; MAX_LEN]);
+
+        impl Message for LongMessage {
+            const PGN: u32 = 126996;
+
+            type EncodedLen = typenum::U223;
+
+            type DecodeError = ();
+
+            fn encode(&self, buf: &mut [u8]) {
+                buf[..MAX_LEN].copy_from_slice(&self.0);
+            }
+
+            fn decode(data: &[u8]) -> Result<Self, Self::DecodeError>
+            where
+                Self: Sized,
+            {
+                Ok(Self(data.try_into().map_err(|_| ())?))
+            }
+        }
+
+        let msg = LongMessage(core::array::from_fn(|i| i as u8));
+        let mut buf = [0; MAX_LEN];
+        // only the lower 3 bits of the group number are used
+        let packets: heapless::Vec<_, 33> = msg.encode_to_fast_packets(&mut buf, 255).collect();
+
+        assert_eq!(packets.len(), 32);
+        assert_eq!(packets[0].0[..2], [0b1110_0000, 223]);
+        assert_eq!(packets[31].0[0], 0b1111_1111);
+        assert!(packets.iter().all(|p| p.group_no() == 7));
+
+        let mut decoder = Decoder::<LongMessage, 1>::new();
+        let id = Id::new(3, LongMessage::PGN, 30, 255);
+        let mut received = None;
+        for FastPacket(data) in packets {
+            assert!(received.is_none());
+            received = decoder.decode(&NmeaFrame::new(
+                id,
+                heapless::Vec::from_slice(&data).unwrap(),
+            ));
+        }
+
+        assert_eq!(received.unwrap().unwrap().msg, msg);
+    }
 }
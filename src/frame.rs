@@ -3,23 +3,87 @@ use generic_array::{
     GenericArray,
 };
 
-use crate::{Id, Message};
+use crate::{time::Instant, Id, Message};
+
+/// Information about when and where a frame was received.
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd, Eq, Ord)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Metadata {
+    /// When the frame was received, preferably as timestamped by the CAN
+    /// controller. [`None`] for frames that have not been received.
+    pub timestamp: Option<Instant>,
+    /// True if the frame was sent by this device rather than received from
+    /// the bus.
+    pub local: bool,
+}
+
+impl Metadata {
+    pub const DEFAULT: Self = Self {
+        timestamp: None,
+        local: false,
+    };
+
+    /// Metadata for a frame received from the bus at `timestamp`.
+    #[inline]
+    #[must_use]
+    pub const fn received(timestamp: Instant) -> Self {
+        Self {
+            timestamp: Some(timestamp),
+            local: false,
+        }
+    }
+
+    /// Metadata for a frame sent by this device at `timestamp` and looped
+    /// back to local consumers.
+    #[inline]
+    #[must_use]
+    pub const fn local(timestamp: Instant) -> Self {
+        Self {
+            timestamp: Some(timestamp),
+            local: true,
+        }
+    }
+}
 
 #[derive(Debug, Clone, PartialEq, PartialOrd, Eq, Ord)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct NmeaFrame {
     pub id: Id,
     pub data: heapless::Vec<u8, 8>,
+    pub meta: Metadata,
 }
 
 impl NmeaFrame {
     pub const DEFAULT: Self = Self {
         id: Id::new(0, 0, 0, 0),
         data: heapless::Vec::new(),
+        meta: Metadata::DEFAULT,
     };
 
     pub fn new(id: Id, data: heapless::Vec<u8, 8>) -> Self {
-        Self { id, data }
+        Self {
+            id,
+            data,
+            meta: Metadata::DEFAULT,
+        }
+    }
+
+    /// Convert a received CAN frame. Returns [`None`] for frames with a
+    /// standard (11-bit) identifier, since those are not NMEA 2000 frames.
+    pub fn from_can_frame<T: embedded_can::Frame>(frame: &T, meta: Metadata) -> Option<Self> {
+        let id = match frame.id() {
+            embedded_can::Id::Extended(extended_id) => Id::from_can_id(extended_id),
+            embedded_can::Id::Standard(_) => return None,
+        };
+
+        Some(Self {
+            id,
+            data: frame
+                .data()
+                .try_into()
+                .expect("frame data should not be larger than 8 bytes"),
+            meta,
+        })
     }
 
     pub fn to_can_frame<T: embedded_can::Frame>(&self) -> T {
@@ -39,6 +103,7 @@ impl NmeaFrame {
         Self {
             id,
             data: unsafe { heapless::Vec::from_slice(buf.as_slice()).unwrap_unchecked() },
+            meta: Metadata::DEFAULT,
         }
     }
 }
@@ -13,6 +13,14 @@ pub enum Format {
 
 pub const DESTINATION_BROADCAST: u8 = 0xff;
 
+/// Returns true if `pgn` is in one of the ranges reserved for proprietary
+/// messages, which start with the manufacturer and industry codes.
+#[inline]
+#[must_use]
+pub const fn is_proprietary(pgn: u32) -> bool {
+    matches!(pgn, 0xef00 | 0xff00..=0xffff | 0x1ef00 | 0x1ff00..=0x1ffff)
+}
+
 impl Id {
     #[inline]
     #[must_use]
@@ -20,7 +28,7 @@ impl Id {
         debug_assert!(priority <= 7, "Priority must be in the range 0-7");
         debug_assert!(pgn <= 0x3ffff, "PGN must be less than 0x3ffff (18 bits");
 
-        let id_pf = pgn >> 8;
+        let id_pf = (pgn >> 8) as u8;
 
         let id = if id_pf < 240 {
             // PDU1
@@ -130,4 +138,14 @@ mod tests {
         assert_eq!(id.source(), 25);
         assert_eq!(id.destination(), 0xff);
     }
+
+    #[test]
+    fn pdu1_data_page() {
+        // PGN 126208 is addressed even though it is above 0xf000
+        let id = Id::new(3, 126_208, 10, 20);
+
+        assert_eq!(id.pgn(), 126_208);
+        assert_eq!(id.source(), 10);
+        assert_eq!(id.destination(), 20);
+    }
 }
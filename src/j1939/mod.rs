@@ -0,0 +1,669 @@
+//! SAE J1939, the protocol spoken by many marine diesel engines. NMEA 2000
+//! is built on J1939, so the identifiers and frames are the same, but the
+//! parameters are defined differently.
+//!
+//! J1939 parameters are identified by their suspect parameter number
+//! ([`Spn`]), which also defines how they are scaled. Values are converted
+//! to the units used in [`crate::well_known`], i.e. kelvin and pascals. A
+//! parameter above the valid range (`0xfa`, `0xfaff` or `0xfaffffff`,
+//! depending on its length) is an error indicator or not available, and
+//! is decoded as [`None`].
+//!
+//! Messages longer than 8 bytes, such as [`ActiveDiagnosticTroubleCodes`]
+//! with more than one code, are sent with the [`transport`] protocol rather
+//! than as fast packets.
+
+use core::time::Duration;
+
+use generic_array::typenum::{self, Unsigned};
+
+use crate::{
+    buf::round,
+    well_known::{get_opt_bool, put_opt_bool},
+    Buf, BufMut, Message,
+};
+
+pub mod transport;
+
+/// The largest valid raw value of a 1-byte parameter.
+const MAX_U8: u8 = 0xfa;
+
+/// The largest valid raw value of a 2-byte parameter.
+const MAX_U16: u16 = 0xfaff;
+
+/// The largest valid raw value of a 4-byte parameter.
+const MAX_U32: u32 = 0xfaff_ffff;
+
+/// A suspect parameter number and its scaling.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Spn {
+    pub number: u32,
+    /// The value of one bit.
+    pub resolution: f64,
+    /// The value of a raw zero.
+    pub offset: f64,
+}
+
+/// Coolant temperature in kelvin.
+pub const ENGINE_COOLANT_TEMPERATURE: Spn = Spn::new(110, 1.0, 233.15);
+/// Fuel temperature in kelvin.
+pub const ENGINE_FUEL_TEMPERATURE: Spn = Spn::new(174, 1.0, 233.15);
+/// Oil temperature in kelvin.
+pub const ENGINE_OIL_TEMPERATURE: Spn = Spn::new(175, 0.031_25, 0.15);
+/// Turbocharger oil temperature in kelvin.
+pub const TURBO_OIL_TEMPERATURE: Spn = Spn::new(176, 0.031_25, 0.15);
+/// Intercooler temperature in kelvin.
+pub const ENGINE_INTERCOOLER_TEMPERATURE: Spn = Spn::new(52, 1.0, 233.15);
+/// Intercooler thermostat opening in percent.
+pub const ENGINE_INTERCOOLER_THERMOSTAT_OPENING: Spn = Spn::new(1134, 0.4, 0.0);
+/// Fuel delivery pressure in pascals.
+pub const ENGINE_FUEL_DELIVERY_PRESSURE: Spn = Spn::new(94, 4000.0, 0.0);
+/// Extended crankcase blow-by pressure in pascals.
+pub const ENGINE_EXTENDED_CRANKCASE_BLOW_BY_PRESSURE: Spn = Spn::new(22, 50.0, 0.0);
+/// Oil level in percent.
+pub const ENGINE_OIL_LEVEL: Spn = Spn::new(98, 0.4, 0.0);
+/// Oil pressure in pascals.
+pub const ENGINE_OIL_PRESSURE: Spn = Spn::new(100, 4000.0, 0.0);
+/// Crankcase pressure in pascals.
+pub const ENGINE_CRANKCASE_PRESSURE: Spn = Spn::new(101, 7.8125, -250_000.0);
+/// Coolant pressure in pascals.
+pub const ENGINE_COOLANT_PRESSURE: Spn = Spn::new(109, 2000.0, 0.0);
+/// Coolant level in percent.
+pub const ENGINE_COOLANT_LEVEL: Spn = Spn::new(111, 0.4, 0.0);
+/// Fuel rate in liters per hour.
+pub const ENGINE_FUEL_RATE: Spn = Spn::new(183, 0.05, 0.0);
+/// Instantaneous fuel economy in kilometers per liter.
+pub const ENGINE_INSTANTANEOUS_FUEL_ECONOMY: Spn = Spn::new(184, 1.0 / 512.0, 0.0);
+/// Average fuel economy in kilometers per liter.
+pub const ENGINE_AVERAGE_FUEL_ECONOMY: Spn = Spn::new(185, 1.0 / 512.0, 0.0);
+/// Throttle position in percent.
+pub const ENGINE_THROTTLE_POSITION: Spn = Spn::new(51, 0.4, 0.0);
+/// Second throttle position in percent.
+pub const ENGINE_THROTTLE_2_POSITION: Spn = Spn::new(3673, 0.4, 0.0);
+/// Total engine hours in seconds.
+pub const ENGINE_TOTAL_HOURS: Spn = Spn::new(247, 180.0, 0.0);
+/// Total engine revolutions.
+pub const ENGINE_TOTAL_REVOLUTIONS: Spn = Spn::new(249, 1000.0, 0.0);
+/// Engine speed in rpm.
+pub const ENGINE_SPEED: Spn = Spn::new(190, 0.125, 0.0);
+/// Driver's demand torque in percent of the reference torque.
+pub const DRIVERS_DEMAND_ENGINE_PERCENT_TORQUE: Spn = Spn::new(512, 1.0, -125.0);
+/// Actual torque in percent of the reference torque.
+pub const ACTUAL_ENGINE_PERCENT_TORQUE: Spn = Spn::new(513, 1.0, -125.0);
+/// Demanded torque in percent of the reference torque.
+pub const ENGINE_DEMAND_PERCENT_TORQUE: Spn = Spn::new(2432, 1.0, -125.0);
+
+impl Spn {
+    #[must_use]
+    pub const fn new(number: u32, resolution: f64, offset: f64) -> Self {
+        Self {
+            number,
+            resolution,
+            offset,
+        }
+    }
+
+    fn scale(&self, raw: u32) -> f64 {
+        f64::from(raw) * self.resolution + self.offset
+    }
+
+    fn unscale(&self, value: f64, max: u32) -> u32 {
+        round((value - self.offset) / self.resolution).clamp(0, max.into()) as u32
+    }
+
+    /// Read a 1-byte parameter.
+    pub fn get_u8(&self, buf: &mut &[u8]) -> Option<f32> {
+        match buf.get_u8() {
+            raw @ 0..=MAX_U8 => Some(self.scale(raw.into()) as f32),
+            _ => None,
+        }
+    }
+
+    /// Read a 2-byte parameter.
+    pub fn get_u16(&self, buf: &mut &[u8]) -> Option<f32> {
+        match buf.get_u16() {
+            raw @ 0..=MAX_U16 => Some(self.scale(raw.into()) as f32),
+            _ => None,
+        }
+    }
+
+    /// Read a 4-byte parameter.
+    pub fn get_u32(&self, buf: &mut &[u8]) -> Option<f64> {
+        match buf.get_u32() {
+            raw @ 0..=MAX_U32 => Some(self.scale(raw)),
+            _ => None,
+        }
+    }
+
+    /// Write a 1-byte parameter, clamped to the valid range.
+    pub fn put_u8(&self, buf: &mut &mut [u8], value: Option<f32>) {
+        buf.put_u8(match value {
+            Some(value) => self.unscale(value.into(), MAX_U8.into()) as u8,
+            None => u8::MAX,
+        });
+    }
+
+    /// Write a 2-byte parameter, clamped to the valid range.
+    pub fn put_u16(&self, buf: &mut &mut [u8], value: Option<f32>) {
+        buf.put_u16(match value {
+            Some(value) => self.unscale(value.into(), MAX_U16.into()) as u16,
+            None => u16::MAX,
+        });
+    }
+
+    /// Write a 4-byte parameter, clamped to the valid range.
+    pub fn put_u32(&self, buf: &mut &mut [u8], value: Option<f64>) {
+        buf.put_u32(match value {
+            Some(value) => self.unscale(value, MAX_U32),
+            None => u32::MAX,
+        });
+    }
+}
+
+lookup! {
+    /// What is currently limiting the engine torque.
+    pub enum EngineTorqueMode: u8 {
+        LowIdleGovernor = 0,
+        AcceleratorPedal = 1,
+        CruiseControl = 2,
+        PtoGovernor = 3,
+        RoadSpeedGovernor = 4,
+        AsrControl = 5,
+        TransmissionControl = 6,
+        AbsControl = 7,
+        TorqueLimiting = 8,
+        HighSpeedGovernor = 9,
+        BrakingSystem = 10,
+        RemoteAccelerator = 11,
+    }
+}
+
+/// PGN 61444, EEC1: engine speed and torque.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ElectronicEngineController1 {
+    pub torque_mode: EngineTorqueMode,
+    /// Torque requested by the operator, in percent.
+    pub drivers_demand_torque: Option<f32>,
+    /// Torque in percent.
+    pub actual_torque: Option<f32>,
+    /// Engine speed in rpm.
+    pub speed: Option<f32>,
+    /// The address of the device controlling the engine torque.
+    pub controlling_device: Option<u8>,
+    /// Torque requested by the engine itself, in percent.
+    pub demand_torque: Option<f32>,
+}
+
+impl Message for ElectronicEngineController1 {
+    const PGN: u32 = 61444;
+
+    type EncodedLen = typenum::U8;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_u8(0xf0 | (u8::from(self.torque_mode) & 0x0f));
+        DRIVERS_DEMAND_ENGINE_PERCENT_TORQUE.put_u8(&mut buf, self.drivers_demand_torque);
+        ACTUAL_ENGINE_PERCENT_TORQUE.put_u8(&mut buf, self.actual_torque);
+        ENGINE_SPEED.put_u16(&mut buf, self.speed);
+        buf.put_opt_u8(self.controlling_device);
+        buf.put_reserved(1);
+        ENGINE_DEMAND_PERCENT_TORQUE.put_u8(&mut buf, self.demand_torque);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        let torque_mode = (data.get_u8() & 0x0f).into();
+        let drivers_demand_torque = DRIVERS_DEMAND_ENGINE_PERCENT_TORQUE.get_u8(&mut data);
+        let actual_torque = ACTUAL_ENGINE_PERCENT_TORQUE.get_u8(&mut data);
+        let speed = ENGINE_SPEED.get_u16(&mut data);
+        let controlling_device = data.get_opt_u8();
+        data.advance(1);
+
+        Ok(Self {
+            torque_mode,
+            drivers_demand_torque,
+            actual_torque,
+            speed,
+            controlling_device,
+            demand_torque: ENGINE_DEMAND_PERCENT_TORQUE.get_u8(&mut data),
+        })
+    }
+}
+
+/// PGN 65262, ET1: engine temperatures.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct EngineTemperature1 {
+    /// Coolant temperature in kelvin.
+    pub coolant_temperature: Option<f32>,
+    /// Fuel temperature in kelvin.
+    pub fuel_temperature: Option<f32>,
+    /// Oil temperature in kelvin.
+    pub oil_temperature: Option<f32>,
+    /// Turbocharger oil temperature in kelvin.
+    pub turbo_oil_temperature: Option<f32>,
+    /// Intercooler temperature in kelvin.
+    pub intercooler_temperature: Option<f32>,
+    /// Intercooler thermostat opening in percent.
+    pub intercooler_thermostat_opening: Option<f32>,
+}
+
+impl Message for EngineTemperature1 {
+    const PGN: u32 = 65262;
+
+    type EncodedLen = typenum::U8;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        ENGINE_COOLANT_TEMPERATURE.put_u8(&mut buf, self.coolant_temperature);
+        ENGINE_FUEL_TEMPERATURE.put_u8(&mut buf, self.fuel_temperature);
+        ENGINE_OIL_TEMPERATURE.put_u16(&mut buf, self.oil_temperature);
+        TURBO_OIL_TEMPERATURE.put_u16(&mut buf, self.turbo_oil_temperature);
+        ENGINE_INTERCOOLER_TEMPERATURE.put_u8(&mut buf, self.intercooler_temperature);
+        ENGINE_INTERCOOLER_THERMOSTAT_OPENING.put_u8(&mut buf, self.intercooler_thermostat_opening);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        Ok(Self {
+            coolant_temperature: ENGINE_COOLANT_TEMPERATURE.get_u8(&mut data),
+            fuel_temperature: ENGINE_FUEL_TEMPERATURE.get_u8(&mut data),
+            oil_temperature: ENGINE_OIL_TEMPERATURE.get_u16(&mut data),
+            turbo_oil_temperature: TURBO_OIL_TEMPERATURE.get_u16(&mut data),
+            intercooler_temperature: ENGINE_INTERCOOLER_TEMPERATURE.get_u8(&mut data),
+            intercooler_thermostat_opening: ENGINE_INTERCOOLER_THERMOSTAT_OPENING.get_u8(&mut data),
+        })
+    }
+}
+
+/// PGN 65263, EFL/P1: engine fluid levels and pressures.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct EngineFluidLevelPressure1 {
+    /// Fuel delivery pressure in pascals.
+    pub fuel_delivery_pressure: Option<f32>,
+    /// Extended crankcase blow-by pressure in pascals.
+    pub extended_crankcase_blow_by_pressure: Option<f32>,
+    /// Oil level in percent.
+    pub oil_level: Option<f32>,
+    /// Oil pressure in pascals.
+    pub oil_pressure: Option<f32>,
+    /// Crankcase pressure in pascals.
+    pub crankcase_pressure: Option<f32>,
+    /// Coolant pressure in pascals.
+    pub coolant_pressure: Option<f32>,
+    /// Coolant level in percent.
+    pub coolant_level: Option<f32>,
+}
+
+impl Message for EngineFluidLevelPressure1 {
+    const PGN: u32 = 65263;
+
+    type EncodedLen = typenum::U8;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        ENGINE_FUEL_DELIVERY_PRESSURE.put_u8(&mut buf, self.fuel_delivery_pressure);
+        ENGINE_EXTENDED_CRANKCASE_BLOW_BY_PRESSURE
+            .put_u8(&mut buf, self.extended_crankcase_blow_by_pressure);
+        ENGINE_OIL_LEVEL.put_u8(&mut buf, self.oil_level);
+        ENGINE_OIL_PRESSURE.put_u8(&mut buf, self.oil_pressure);
+        ENGINE_CRANKCASE_PRESSURE.put_u16(&mut buf, self.crankcase_pressure);
+        ENGINE_COOLANT_PRESSURE.put_u8(&mut buf, self.coolant_pressure);
+        ENGINE_COOLANT_LEVEL.put_u8(&mut buf, self.coolant_level);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        Ok(Self {
+            fuel_delivery_pressure: ENGINE_FUEL_DELIVERY_PRESSURE.get_u8(&mut data),
+            extended_crankcase_blow_by_pressure: ENGINE_EXTENDED_CRANKCASE_BLOW_BY_PRESSURE
+                .get_u8(&mut data),
+            oil_level: ENGINE_OIL_LEVEL.get_u8(&mut data),
+            oil_pressure: ENGINE_OIL_PRESSURE.get_u8(&mut data),
+            crankcase_pressure: ENGINE_CRANKCASE_PRESSURE.get_u16(&mut data),
+            coolant_pressure: ENGINE_COOLANT_PRESSURE.get_u8(&mut data),
+            coolant_level: ENGINE_COOLANT_LEVEL.get_u8(&mut data),
+        })
+    }
+}
+
+/// PGN 65266, LFE: fuel rate, fuel economy and throttle position.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FuelEconomy {
+    /// Fuel rate in liters per hour.
+    pub fuel_rate: Option<f32>,
+    /// Instantaneous fuel economy in kilometers per liter.
+    pub instantaneous_fuel_economy: Option<f32>,
+    /// Average fuel economy in kilometers per liter.
+    pub average_fuel_economy: Option<f32>,
+    /// Throttle position in percent.
+    pub throttle_position: Option<f32>,
+    /// Second throttle position in percent.
+    pub throttle_2_position: Option<f32>,
+}
+
+impl Message for FuelEconomy {
+    const PGN: u32 = 65266;
+
+    type EncodedLen = typenum::U8;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        ENGINE_FUEL_RATE.put_u16(&mut buf, self.fuel_rate);
+        ENGINE_INSTANTANEOUS_FUEL_ECONOMY.put_u16(&mut buf, self.instantaneous_fuel_economy);
+        ENGINE_AVERAGE_FUEL_ECONOMY.put_u16(&mut buf, self.average_fuel_economy);
+        ENGINE_THROTTLE_POSITION.put_u8(&mut buf, self.throttle_position);
+        ENGINE_THROTTLE_2_POSITION.put_u8(&mut buf, self.throttle_2_position);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        Ok(Self {
+            fuel_rate: ENGINE_FUEL_RATE.get_u16(&mut data),
+            instantaneous_fuel_economy: ENGINE_INSTANTANEOUS_FUEL_ECONOMY.get_u16(&mut data),
+            average_fuel_economy: ENGINE_AVERAGE_FUEL_ECONOMY.get_u16(&mut data),
+            throttle_position: ENGINE_THROTTLE_POSITION.get_u8(&mut data),
+            throttle_2_position: ENGINE_THROTTLE_2_POSITION.get_u8(&mut data),
+        })
+    }
+}
+
+/// PGN 65253, HOURS: engine hours and revolutions.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct EngineHours {
+    /// Total engine hours, with a resolution of 3 minutes.
+    pub total_hours: Option<Duration>,
+    /// Total engine revolutions, with a resolution of 1000.
+    pub total_revolutions: Option<f64>,
+}
+
+impl Message for EngineHours {
+    const PGN: u32 = 65253;
+
+    type EncodedLen = typenum::U8;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        ENGINE_TOTAL_HOURS.put_u32(&mut buf, self.total_hours.map(|h| h.as_secs_f64()));
+        ENGINE_TOTAL_REVOLUTIONS.put_u32(&mut buf, self.total_revolutions);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        Ok(Self {
+            total_hours: ENGINE_TOTAL_HOURS
+                .get_u32(&mut data)
+                .map(Duration::from_secs_f64),
+            total_revolutions: ENGINE_TOTAL_REVOLUTIONS.get_u32(&mut data),
+        })
+    }
+}
+
+/// The state of the warning lamps of an engine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Lamps {
+    /// An emissions related problem.
+    pub malfunction_indicator: Option<bool>,
+    /// A problem severe enough to stop the engine.
+    pub red_stop: Option<bool>,
+    /// A problem that does not require stopping the engine.
+    pub amber_warning: Option<bool>,
+    /// A problem that is not electronic, such as a low coolant level.
+    pub protect: Option<bool>,
+}
+
+/// A diagnostic trouble code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Dtc {
+    /// The faulty parameter.
+    pub spn: u32,
+    /// The failure mode identifier, describing what is wrong.
+    pub fmi: u8,
+    /// How many times the fault has occurred.
+    pub occurrence_count: Option<u8>,
+}
+
+impl Dtc {
+    const LEN: usize = 4;
+
+    fn get(buf: &mut &[u8]) -> Self {
+        let raw = buf.get_u32();
+        let occurrence_count = (raw >> 24) as u8 & 0x7f;
+
+        Self {
+            spn: (raw & 0xffff) | ((raw >> 21) & 0x07) << 16,
+            fmi: (raw >> 16) as u8 & 0x1f,
+            occurrence_count: (occurrence_count != 0x7f).then_some(occurrence_count),
+        }
+    }
+
+    fn put(&self, buf: &mut &mut [u8]) {
+        buf.put_u32(
+            (self.spn & 0xffff)
+                | ((self.spn >> 16) & 0x07) << 21
+                | u32::from(self.fmi & 0x1f) << 16
+                | u32::from(self.occurrence_count.unwrap_or(0x7f) & 0x7f) << 24,
+        );
+    }
+}
+
+/// The maximum number of codes in [`ActiveDiagnosticTroubleCodes`].
+pub const MAX_DTCS: usize = 32;
+
+/// PGN 65226, DM1: the active diagnostic trouble codes and the state of
+/// the warning lamps. Sent with the [`transport`] protocol if more than one
+/// code is active, so it must not be received with a
+/// [`Decoder`](crate::Decoder).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ActiveDiagnosticTroubleCodes {
+    pub lamps: Lamps,
+    pub dtcs: heapless::Vec<Dtc, MAX_DTCS>,
+}
+
+impl Message for ActiveDiagnosticTroubleCodes {
+    const PGN: u32 = 65226;
+
+    type EncodedLen = typenum::U130;
+
+    type DecodeError = ();
+
+    fn encoded_len(&self) -> usize {
+        // a single frame is padded, and has an empty code if there are none
+        (2 + Dtc::LEN * self.dtcs.len()).max(8)
+    }
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        let lamps = [
+            self.lamps.malfunction_indicator,
+            self.lamps.red_stop,
+            self.lamps.amber_warning,
+            self.lamps.protect,
+        ];
+
+        buf.put_u8(
+            lamps
+                .iter()
+                .fold(0, |bits, &lamp| bits << 2 | put_opt_bool(lamp)),
+        );
+        // flashing is not supported
+        buf.put_reserved(1);
+
+        if self.dtcs.is_empty() {
+            buf.put_u32(0);
+        }
+
+        for dtc in &self.dtcs {
+            dtc.put(&mut buf);
+        }
+
+        if self.dtcs.len() <= 1 {
+            buf.put_reserved(2);
+        }
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < 2 + Dtc::LEN {
+            return Err(());
+        }
+
+        let lamps = data.get_u8();
+        data.advance(1);
+
+        let mut dtcs = heapless::Vec::new();
+
+        while data.remaining() >= Dtc::LEN {
+            let dtc = Dtc::get(&mut data);
+
+            // an empty code means that there are none, and all ones is
+            // padding
+            if dtc.spn == 0 && dtc.fmi == 0 || dtc.spn == 0x7_ffff {
+                continue;
+            }
+
+            dtcs.push(dtc).map_err(|_| ())?;
+        }
+
+        Ok(Self {
+            lamps: Lamps {
+                malfunction_indicator: get_opt_bool(lamps >> 6),
+                red_stop: get_opt_bool(lamps >> 4),
+                amber_warning: get_opt_bool(lamps >> 2),
+                protect: get_opt_bool(lamps),
+            },
+            dtcs,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::well_known::round_trip;
+
+    #[test]
+    fn electronic_engine_controller_1() {
+        let msg: ElectronicEngineController1 =
+            round_trip(&[0xf1, 0xaf, 0x9b, 0xe0, 0x2e, 0x00, 0xff, 0xff]);
+
+        assert_eq!(msg.torque_mode, EngineTorqueMode::AcceleratorPedal);
+        assert_eq!(msg.drivers_demand_torque, Some(50.0));
+        assert_eq!(msg.actual_torque, Some(30.0));
+        assert_eq!(msg.speed, Some(1500.0));
+        assert_eq!(msg.controlling_device, Some(0));
+        assert_eq!(msg.demand_torque, None);
+    }
+
+    #[test]
+    fn engine_temperature_1() {
+        let msg: EngineTemperature1 = round_trip(&[0x7d, 0xff, 0x00, 0x2e, 0xff, 0xff, 0xff, 0xff]);
+
+        assert!((msg.coolant_temperature.unwrap() - 358.15).abs() < 1e-3);
+        assert_eq!(msg.fuel_temperature, None);
+        assert!((msg.oil_temperature.unwrap() - 368.15).abs() < 1e-3);
+        assert_eq!(msg.turbo_oil_temperature, None);
+    }
+
+    #[test]
+    fn engine_fluid_level_pressure_1() {
+        let msg: EngineFluidLevelPressure1 =
+            round_trip(&[0xff, 0xff, 0xff, 0x64, 0xff, 0xff, 0x32, 0xfa]);
+
+        assert_eq!(msg.fuel_delivery_pressure, None);
+        assert_eq!(msg.oil_pressure, Some(400_000.0));
+        assert_eq!(msg.crankcase_pressure, None);
+        assert_eq!(msg.coolant_pressure, Some(100_000.0));
+        assert_eq!(msg.coolant_level, Some(100.0));
+    }
+
+    #[test]
+    fn fuel_economy_and_hours() {
+        let msg: FuelEconomy = round_trip(&[0xfa, 0x00, 0xff, 0xff, 0xff, 0xff, 0x64, 0xff]);
+
+        assert!((msg.fuel_rate.unwrap() - 12.5).abs() < 1e-3);
+        assert_eq!(msg.instantaneous_fuel_economy, None);
+        assert_eq!(msg.throttle_position, Some(40.0));
+
+        let msg: EngineHours = round_trip(&[0x72, 0x60, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff]);
+
+        assert_eq!(msg.total_hours, Some(Duration::from_secs(4_444_200)));
+        assert_eq!(msg.total_revolutions, None);
+    }
+
+    #[test]
+    fn out_of_range() {
+        // error indicators and reserved values are not available
+        assert_eq!(ENGINE_SPEED.get_u16(&mut &[0x00, 0xfe][..]), None);
+        assert_eq!(ENGINE_COOLANT_LEVEL.get_u8(&mut &[0xfb][..]), None);
+
+        // and values that cannot be represented are clamped
+        let mut buf = [0; 1];
+        ENGINE_COOLANT_TEMPERATURE.put_u8(&mut &mut buf[..], Some(200.0));
+        assert_eq!(buf, [0x00]);
+        ENGINE_COOLANT_TEMPERATURE.put_u8(&mut &mut buf[..], Some(1000.0));
+        assert_eq!(buf, [0xfa]);
+    }
+
+    #[test]
+    fn active_diagnostic_trouble_codes() {
+        let msg: ActiveDiagnosticTroubleCodes =
+            round_trip(&[0x00, 0xff, 0x00, 0x00, 0x00, 0x00, 0xff, 0xff]);
+
+        assert_eq!(msg.lamps.amber_warning, Some(false));
+        assert!(msg.dtcs.is_empty());
+
+        let msg: ActiveDiagnosticTroubleCodes =
+            round_trip(&[0x04, 0xff, 0x64, 0x00, 0x01, 0x03, 0xff, 0xff]);
+
+        assert_eq!(msg.lamps.malfunction_indicator, Some(false));
+        assert_eq!(msg.lamps.amber_warning, Some(true));
+        assert_eq!(
+            msg.dtcs,
+            [Dtc {
+                spn: 100,
+                fmi: 1,
+                occurrence_count: Some(3),
+            }]
+        );
+
+        // SPN 520192, which needs all 19 bits
+        let msg: ActiveDiagnosticTroubleCodes =
+            round_trip(&[0x44, 0xff, 0x64, 0x00, 0x01, 0x03, 0x00, 0xf0, 0xef, 0x7f]);
+
+        assert_eq!(msg.lamps.malfunction_indicator, Some(true));
+        assert_eq!(
+            msg.dtcs[1],
+            Dtc {
+                spn: 520_192,
+                fmi: 15,
+                occurrence_count: None,
+            }
+        );
+    }
+}
@@ -0,0 +1,284 @@
+//! The J1939 transport protocol (J1939-21), used to send messages longer
+//! than 8 bytes. A connection management frame (TP.CM, PGN 60416)
+//! announces the PGN and length of the message, which is then sent 7 bytes
+//! at a time in data transfer frames (TP.DT, PGN 60160).
+//!
+//! Only broadcast sessions (BAM) are supported. Connection mode sessions
+//! (RTS/CTS) require flow control from the receiver and are ignored.
+
+use crate::{id::DESTINATION_BROADCAST, Buf, BufMut, Id, NmeaFrame};
+
+/// PGN of TP.CM.
+pub const CONNECTION_MANAGEMENT_PGN: u32 = 60416;
+
+/// PGN of TP.DT.
+pub const DATA_TRANSFER_PGN: u32 = 60160;
+
+/// The longest message that can be sent with the transport protocol.
+pub const MAX_LEN: usize = 1785;
+
+/// The control byte of a Broadcast Announce Message.
+const BAM: u8 = 32;
+
+/// The number of data bytes in each TP.DT frame.
+const PACKET_LEN: usize = 7;
+
+/// The priority of transport protocol frames.
+const PRIORITY: u8 = 7;
+
+/// A message received with the transport protocol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Reassembled<'a> {
+    pub pgn: u32,
+    pub source: u8,
+    pub data: &'a [u8],
+}
+
+#[derive(Debug)]
+struct Session<const LEN: usize> {
+    source: u8,
+    pgn: u32,
+    len: usize,
+    /// The sequence number of the next TP.DT frame, starting at 1.
+    next: u8,
+    data: heapless::Vec<u8, LEN>,
+}
+
+/// Reassembles broadcast messages of up to `LEN` bytes from up to
+/// `SOURCES` devices at a time. Sessions for longer messages are ignored.
+#[derive(Debug)]
+pub struct Reassembler<const LEN: usize, const SOURCES: usize> {
+    sessions: heapless::Vec<Session<LEN>, SOURCES>,
+    /// The last completed session, borrowed by the returned [`Reassembled`].
+    complete: Option<Session<LEN>>,
+}
+
+impl<const LEN: usize, const SOURCES: usize> Default for Reassembler<LEN, SOURCES> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const LEN: usize, const SOURCES: usize> Reassembler<LEN, SOURCES> {
+    pub const fn new() -> Self {
+        Self {
+            sessions: heapless::Vec::new(),
+            complete: None,
+        }
+    }
+
+    /// Handle a received frame, returning the message once its last frame
+    /// has been received. Frames other than TP.CM and TP.DT are ignored.
+    pub fn handle_frame(&mut self, frame: &NmeaFrame) -> Option<Reassembled<'_>> {
+        let source = frame.id.source();
+
+        match frame.id.pgn() {
+            CONNECTION_MANAGEMENT_PGN => {
+                self.announce(source, frame.id.destination(), &frame.data);
+                None
+            }
+            DATA_TRANSFER_PGN => self.transfer(source, &frame.data),
+            _ => None,
+        }
+    }
+
+    fn announce(&mut self, source: u8, destination: u8, mut data: &[u8]) {
+        // a new announcement aborts any session from the same source
+        self.sessions.retain(|s| s.source != source);
+
+        if destination != DESTINATION_BROADCAST || data.len() < 8 || data.get_u8() != BAM {
+            return;
+        }
+
+        let len = usize::from(data.get_u16());
+        let packets = usize::from(data.get_u8());
+        data.advance(1);
+        let pgn = data.get_u24();
+
+        if len <= 8 || len > LEN || packets != len.div_ceil(PACKET_LEN) {
+            return;
+        }
+
+        if self.sessions.is_full() {
+            // drop the oldest session
+            self.sessions.remove(0);
+        }
+
+        let _ = self.sessions.push(Session {
+            source,
+            pgn,
+            len,
+            next: 1,
+            data: heapless::Vec::new(),
+        });
+    }
+
+    fn transfer(&mut self, source: u8, data: &[u8]) -> Option<Reassembled<'_>> {
+        let index = self.sessions.iter().position(|s| s.source == source)?;
+        let session = &mut self.sessions[index];
+
+        let (&sequence, data) = data.split_first()?;
+
+        if sequence != session.next {
+            // a frame was lost
+            self.sessions.remove(index);
+            return None;
+        }
+
+        let n = (session.len - session.data.len())
+            .min(PACKET_LEN)
+            .min(data.len());
+        // `len` never exceeds `LEN`
+        session.data.extend_from_slice(&data[..n]).unwrap();
+        session.next = session.next.wrapping_add(1);
+
+        if session.data.len() < session.len {
+            return None;
+        }
+
+        let session = self.complete.insert(self.sessions.remove(index));
+
+        Some(Reassembled {
+            pgn: session.pgn,
+            source: session.source,
+            data: &session.data,
+        })
+    }
+}
+
+/// The frames of a broadcast message, returned by [`bam`].
+#[derive(Debug, Clone)]
+pub struct BamFrames<'a> {
+    pgn: u32,
+    source: u8,
+    data: &'a [u8],
+    /// The sequence number of the next frame, where 0 is the TP.CM frame.
+    /// This goes one past the last TP.DT frame, whose number may be 255.
+    sequence: u16,
+}
+
+/// Split a message of 9 to [`MAX_LEN`] bytes into a TP.CM frame followed
+/// by TP.DT frames, to be sent at least 50 ms apart. Returns [`None`] if
+/// the message is too short or too long.
+#[must_use]
+pub fn bam(pgn: u32, source: u8, data: &[u8]) -> Option<BamFrames<'_>> {
+    if data.len() <= 8 || data.len() > MAX_LEN {
+        return None;
+    }
+
+    Some(BamFrames {
+        pgn,
+        source,
+        data,
+        sequence: 0,
+    })
+}
+
+impl Iterator for BamFrames<'_> {
+    type Item = NmeaFrame;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let packets = self.data.len().div_ceil(PACKET_LEN);
+
+        if usize::from(self.sequence) > packets {
+            return None;
+        }
+
+        let mut buf = [0xff; 8];
+        let mut b = &mut buf[..];
+
+        let pgn = if self.sequence == 0 {
+            b.put_u8(BAM);
+            b.put_u16(self.data.len() as u16);
+            b.put_u8(packets as u8);
+            b.put_reserved(1);
+            b.put_u24(self.pgn);
+            CONNECTION_MANAGEMENT_PGN
+        } else {
+            let start = (usize::from(self.sequence) - 1) * PACKET_LEN;
+            let chunk = &self.data[start..self.data.len().min(start + PACKET_LEN)];
+            b.put_u8(self.sequence as u8);
+            b[..chunk.len()].copy_from_slice(chunk);
+            DATA_TRANSFER_PGN
+        };
+
+        self.sequence += 1;
+
+        Some(NmeaFrame::new(
+            Id::new(PRIORITY, pgn, self.source, DESTINATION_BROADCAST),
+            heapless::Vec::from_slice(&buf).unwrap(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bam_round_trip() {
+        let data: [u8; 20] = core::array::from_fn(|i| i as u8);
+        let mut frames = bam(65226, 0x00, &data).unwrap();
+
+        let cm = frames.next().unwrap();
+        assert_eq!(cm.id.pgn(), CONNECTION_MANAGEMENT_PGN);
+        assert_eq!(
+            cm.data.as_slice(),
+            [0x20, 0x14, 0x00, 0x03, 0xff, 0xca, 0xfe, 0x00]
+        );
+
+        let mut reassembler = Reassembler::<32, 2>::new();
+        assert_eq!(reassembler.handle_frame(&cm), None);
+
+        let dt: heapless::Vec<NmeaFrame, 3> = frames.collect();
+        assert_eq!(dt.len(), 3);
+        assert_eq!(
+            dt[2].data.as_slice(),
+            [0x03, 0x0e, 0x0f, 0x10, 0x11, 0x12, 0x13, 0xff]
+        );
+
+        assert_eq!(reassembler.handle_frame(&dt[0]), None);
+        assert_eq!(reassembler.handle_frame(&dt[1]), None);
+        assert_eq!(
+            reassembler.handle_frame(&dt[2]),
+            Some(Reassembled {
+                pgn: 65226,
+                source: 0x00,
+                data: &data,
+            })
+        );
+    }
+
+    #[test]
+    fn lost_frame() {
+        let data = [0x55; 16];
+        let frames: heapless::Vec<NmeaFrame, 4> = bam(65226, 0x00, &data).unwrap().collect();
+
+        let mut reassembler = Reassembler::<32, 2>::new();
+        assert_eq!(reassembler.handle_frame(&frames[0]), None);
+        assert_eq!(reassembler.handle_frame(&frames[2]), None);
+        assert_eq!(reassembler.handle_frame(&frames[3]), None);
+
+        // too long for the reassembler
+        let data = [0x55; 40];
+        let frames: heapless::Vec<NmeaFrame, 7> = bam(65226, 0x00, &data).unwrap().collect();
+        assert!(frames.iter().all(|f| reassembler.handle_frame(f).is_none()));
+    }
+
+    #[test]
+    fn max_len() {
+        let data: [u8; MAX_LEN] = core::array::from_fn(|i| i as u8);
+        let frames = bam(65226, 0x00, &data).unwrap();
+        assert_eq!(frames.clone().count(), 256);
+        assert_eq!(frames.clone().last().unwrap().data[0], 255);
+
+        let mut reassembler = Reassembler::<MAX_LEN, 1>::new();
+        let mut reassembled = None;
+        for frame in frames {
+            assert_eq!(reassembled, None);
+            reassembled = reassembler.handle_frame(&frame).map(|r| r.data == data);
+        }
+
+        assert_eq!(reassembled, Some(true));
+    }
+}
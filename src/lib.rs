@@ -11,19 +11,35 @@
 #![no_std]
 #![allow(async_fn_in_trait)]
 
+#[cfg(feature = "tokio")]
+extern crate std;
+
+#[macro_use]
+mod macros;
+
+pub mod blocking;
 mod buf;
 #[cfg(feature = "client")]
 pub mod client;
+mod decoder;
 pub mod fast_packet;
 mod frame;
 pub mod id;
+pub mod j1939;
+pub mod node;
+pub mod proprietary;
+pub mod service;
+pub mod time;
+#[cfg(feature = "tokio")]
+pub mod tokio;
 pub mod well_known;
 
 use generic_array::{typenum::Unsigned, ArrayLength};
 
 pub use buf::{Buf, BufMut};
+pub use decoder::{Decoder, Received};
 pub use fast_packet::FastPacket;
-pub use frame::NmeaFrame;
+pub use frame::{Metadata, NmeaFrame};
 pub use generic_array::{typenum, GenericArray};
 pub use id::Id;
 
@@ -32,17 +48,24 @@ pub use id::Id;
 pub trait Message {
     const PGN: u32;
 
-    /// Total length of the encoded message in bytes.
+    /// Total length of the encoded message in bytes. For messages with a
+    /// variable length, this is the maximum length.
     type EncodedLen: ArrayLength;
 
     /// The error type returned when a message fails to decode.
     type DecodeError;
 
+    /// The length of this particular message when encoded. Only messages
+    /// with a variable length need to override this.
+    fn encoded_len(&self) -> usize {
+        Self::EncodedLen::USIZE
+    }
+
     fn encode(&self, buf: &mut [u8]);
 
     fn encode_to_fast_packets<'a>(&self, buf: &'a mut [u8], group_no: u8) -> fast_packet::Iter<'a> {
         self.encode(buf);
-        fast_packet::Iter::new(&buf[..Self::EncodedLen::USIZE], group_no)
+        fast_packet::Iter::new(&buf[..self.encoded_len()], group_no)
     }
 
     /// Decode a message from its encoded form.
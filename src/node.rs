@@ -0,0 +1,309 @@
+//! A sans-IO implementation of the NMEA 2000 network management logic.
+//!
+//! [`Node`] is a synchronous state machine that never touches a CAN
+//! controller or a clock. The driver feeds it received frames and the
+//! current time, and in return asks it for frames to transmit
+//! ([`Node::poll_transmit`]) and for the next point in time at which it
+//! wants to be woken up ([`Node::poll_timeout`]). This makes it possible to
+//! run the same logic on top of embassy ([`crate::client`]), any other async
+//! runtime, or a plain superloop, and to test it deterministically.
+
+use core::{cmp::Ordering, fmt, time::Duration};
+
+#[cfg(feature = "defmt")]
+use defmt::{debug, warn};
+use heapless::Deque;
+
+use crate::{
+    id::DESTINATION_BROADCAST,
+    time::Instant,
+    well_known::{DeviceName, IsoAddressClaim},
+    Id, Message, NmeaFrame,
+};
+
+/// How long a device has to wait after claiming an address before it may
+/// start using it.
+pub const ADDRESS_CLAIM_TIMEOUT: Duration = Duration::from_millis(250);
+pub const MIN_SRC: u8 = 1;
+pub const MAX_SRC: u8 = 254;
+
+/// The maximum number of frames generated by the node itself that can be
+/// waiting for [`Node::poll_transmit`]. If the driver falls behind, the
+/// oldest frames are dropped.
+pub const TRANSMIT_QUEUE_LEN: usize = 8;
+
+/// NMEA 2000 addresses are distributed to the devices on the bus
+/// by having a new device A sending an address claim message upon
+/// startup. If another device B has the same address, it will
+/// refute the address claim and A will have to choose a new address.
+/// The process is repeated until A has a uniqie address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+enum AddressClaimState {
+    /// No address claim has been sent yet.
+    Idle,
+    /// An address claim has been sent, and we are waiting for other devices
+    /// to refute it.
+    Pending { deadline: Instant },
+    /// Nobody refuted our claim before the deadline.
+    Claimed,
+}
+
+/// See the [module-level documentation](self) for more information.
+pub struct Node {
+    name: DeviceName,
+    src: u8,
+    address_claim: AddressClaimState,
+    transmit: Deque<NmeaFrame, TRANSMIT_QUEUE_LEN>,
+}
+
+impl fmt::Debug for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Node")
+            .field("name", &self.name)
+            .field("src", &self.src)
+            .field("address_claim", &self.address_claim)
+            .field("transmit", &self.transmit.len())
+            .finish()
+    }
+}
+
+impl Node {
+    pub fn new(name: impl Into<DeviceName>) -> Self {
+        Self {
+            name: name.into(),
+            src: MIN_SRC,
+            address_claim: AddressClaimState::Idle,
+            transmit: Deque::new(),
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn name(&self) -> DeviceName {
+        self.name
+    }
+
+    /// The source address currently used (or being claimed) by this node.
+    #[inline]
+    #[must_use]
+    pub fn src(&self) -> u8 {
+        self.src
+    }
+
+    /// Returns true if the address claim process has started (and might
+    /// have finished).
+    #[inline]
+    #[must_use]
+    pub fn is_started(&self) -> bool {
+        self.address_claim != AddressClaimState::Idle
+    }
+
+    /// Returns true if the address claim timeout has elapsed without anyone
+    /// refuting our claim, meaning that application frames may be sent.
+    #[inline]
+    #[must_use]
+    pub fn is_ready(&self) -> bool {
+        self.address_claim == AddressClaimState::Claimed
+    }
+
+    /// Start the address claim process.
+    pub fn start(&mut self, now: Instant) {
+        self.send_address_claim();
+        self.address_claim = AddressClaimState::Pending {
+            deadline: now + ADDRESS_CLAIM_TIMEOUT,
+        };
+    }
+
+    /// Queue an ISO Address Claim for our current source address.
+    pub fn send_address_claim(&mut self) {
+        let id = Id::new(6, IsoAddressClaim::PGN, self.src, DESTINATION_BROADCAST);
+        let frame = NmeaFrame::from_message(id, &IsoAddressClaim { name: self.name });
+        self.queue(frame);
+    }
+
+    fn queue(&mut self, frame: NmeaFrame) {
+        if self.transmit.is_full() {
+            self.transmit.pop_front();
+        }
+
+        // cannot fail since we just made room
+        let _ = self.transmit.push_back(frame);
+    }
+
+    fn incr_src(&mut self) {
+        if self.src >= MAX_SRC {
+            self.src = MIN_SRC;
+        } else {
+            self.src += 1;
+        }
+    }
+
+    /// Process a frame received from the bus.
+    pub fn handle_frame(&mut self, frame: &NmeaFrame, now: Instant) {
+        if frame.id.pgn() == IsoAddressClaim::PGN {
+            if let Ok(claim) = IsoAddressClaim::decode(&frame.data) {
+                self.handle_address_claim(frame.id.source(), claim, now);
+            }
+        }
+    }
+
+    fn handle_address_claim(&mut self, src: u8, claim: IsoAddressClaim, now: Instant) {
+        #[cfg(feature = "defmt")]
+        debug!("Received ISO Address Claim from {}", src);
+
+        if src != self.src {
+            // ignore claims from other sources than our own
+            return;
+        }
+
+        match self.name.cmp(&claim.name) {
+            Ordering::Less => {
+                // re-claim address
+                #[cfg(feature = "defmt")]
+                debug!("Reclaiming address {}", src);
+                self.send_address_claim();
+            }
+            Ordering::Equal => {
+                // that's us, do nothing. this should not happen
+                #[cfg(feature = "defmt")]
+                warn!(
+                    "received address claim from a device with the same name as ours: {}",
+                    claim.name
+                );
+            }
+            Ordering::Greater => {
+                // another device has an address with a greater priority, so
+                // we cede the address to them and keep looking for another
+                self.incr_src();
+                self.start(now);
+            }
+        }
+    }
+
+    /// Advance the internal timers. Should be called when the instant
+    /// returned by [`Node::poll_timeout`] has been reached, but calling it
+    /// more often is harmless.
+    pub fn handle_timeout(&mut self, now: Instant) {
+        if let AddressClaimState::Pending { deadline } = self.address_claim {
+            if now >= deadline {
+                self.address_claim = AddressClaimState::Claimed;
+            }
+        }
+    }
+
+    /// The next instant at which [`Node::handle_timeout`] should be called.
+    #[must_use]
+    pub fn poll_timeout(&self) -> Option<Instant> {
+        match self.address_claim {
+            AddressClaimState::Pending { deadline } => Some(deadline),
+            AddressClaimState::Idle | AddressClaimState::Claimed => None,
+        }
+    }
+
+    /// Returns the next frame generated by the node itself that should be
+    /// sent on the bus.
+    pub fn poll_transmit(&mut self) -> Option<NmeaFrame> {
+        self.transmit.pop_front()
+    }
+
+    /// Prepare a frame from the application for transmission by filling in
+    /// our source address. The frame should only be sent if
+    /// [`Node::is_ready`] returns true.
+    pub fn prepare_transmit(&self, frame: &mut NmeaFrame) {
+        frame.id.set_source(self.src);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Node, ADDRESS_CLAIM_TIMEOUT, MIN_SRC};
+    use crate::{
+        id::DESTINATION_BROADCAST,
+        time::Instant,
+        well_known::{DeviceName, IsoAddressClaim},
+        Id, Message, NmeaFrame,
+    };
+
+    fn claim(src: u8, name: u64) -> NmeaFrame {
+        NmeaFrame::from_message(
+            Id::new(6, IsoAddressClaim::PGN, src, DESTINATION_BROADCAST),
+            &IsoAddressClaim {
+                name: DeviceName(name),
+            },
+        )
+    }
+
+    #[test]
+    fn claim_address() {
+        let mut node = Node::new(0x1234);
+        let t0 = Instant::from_millis(1000);
+
+        assert!(!node.is_started());
+        node.start(t0);
+        assert_eq!(node.poll_transmit(), Some(claim(MIN_SRC, 0x1234)));
+        assert_eq!(node.poll_transmit(), None);
+        assert_eq!(node.poll_timeout(), Some(t0 + ADDRESS_CLAIM_TIMEOUT));
+
+        node.handle_timeout(Instant::from_millis(1100));
+        assert!(!node.is_ready());
+
+        node.handle_timeout(t0 + ADDRESS_CLAIM_TIMEOUT);
+        assert!(node.is_ready());
+        assert_eq!(node.poll_timeout(), None);
+    }
+
+    #[test]
+    fn cede_address() {
+        let mut node = Node::new(0x1234);
+        node.start(Instant::ZERO);
+        node.poll_transmit();
+
+        let t1 = Instant::from_millis(100);
+        node.handle_frame(&claim(MIN_SRC, 0x1000), t1);
+
+        assert_eq!(node.src(), MIN_SRC + 1);
+        assert_eq!(node.poll_transmit(), Some(claim(MIN_SRC + 1, 0x1234)));
+        assert_eq!(node.poll_timeout(), Some(t1 + ADDRESS_CLAIM_TIMEOUT));
+    }
+
+    #[test]
+    fn defend_address() {
+        let mut node = Node::new(0x1000);
+        node.start(Instant::ZERO);
+        node.handle_timeout(Instant::from_millis(300));
+        node.poll_transmit();
+
+        node.handle_frame(&claim(MIN_SRC, 0x1234), Instant::from_millis(400));
+
+        assert_eq!(node.src(), MIN_SRC);
+        assert!(node.is_ready());
+        assert_eq!(node.poll_transmit(), Some(claim(MIN_SRC, 0x1000)));
+    }
+
+    #[test]
+    fn short_claim() {
+        let mut node = Node::new(0x1234);
+        node.start(Instant::ZERO);
+        node.poll_transmit();
+
+        let mut frame = claim(MIN_SRC, 0x1000);
+        frame.data.truncate(4);
+        node.handle_frame(&frame, Instant::from_millis(100));
+
+        assert_eq!(node.src(), MIN_SRC);
+        assert_eq!(node.poll_transmit(), None);
+    }
+
+    #[test]
+    fn prepare_transmit() {
+        let mut node = Node::new(0x1234);
+        node.start(Instant::ZERO);
+        node.handle_frame(&claim(MIN_SRC, 0x1000), Instant::ZERO);
+
+        let mut frame = NmeaFrame::new(Id::new(2, 130_816, 0, 0), heapless::Vec::new());
+        node.prepare_transmit(&mut frame);
+
+        assert_eq!(frame.id.source(), MIN_SRC + 1);
+    }
+}
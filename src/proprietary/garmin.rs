@@ -0,0 +1,203 @@
+//! Garmin messages (manufacturer code 229). Garmin chartplotters share
+//! their day, night and colour modes over PGN 126720 so that all displays
+//! on the boat switch together.
+
+use generic_array::typenum;
+
+use super::{check_header, Dispatcher, ProprietaryMessage};
+use crate::{
+    well_known::{IndustryCode, Manufacturer},
+    Buf, BufMut, Message,
+};
+
+pub const MANUFACTURER: Manufacturer = Manufacturer {
+    code: 229,
+    industry: IndustryCode::Marine,
+};
+
+lookup! {
+    /// The palette selected on a display.
+    pub enum ColorMode: u8 {
+        Day = 0,
+        Night = 1,
+        Color = 13,
+    }
+}
+
+lookup! {
+    /// The colour used when the mode is [`ColorMode::Color`].
+    pub enum Color: u8 {
+        DayFullColor = 0,
+        DayHighContrast = 1,
+        NightFullColor = 2,
+        NightRedBlack = 3,
+        NightGreenBlack = 4,
+    }
+}
+
+/// Identifies display settings in PGN 126720.
+const DISPLAY_ID: [u8; 6] = [0x84, 0x05, 0x04, 0x05, 0x05, 0x02];
+
+/// PGN 126720 sent by Garmin devices. This is a fast packet.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Garmin {
+    /// Day mode with the given backlight level, in percent.
+    DayMode {
+        backlight: u8,
+    },
+    /// Night mode with the given backlight level, in percent.
+    NightMode {
+        backlight: u8,
+    },
+    ColorMode {
+        color: Color,
+    },
+}
+
+impl Message for Garmin {
+    const PGN: u32 = 126720;
+
+    type EncodedLen = typenum::U11;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        let (mode, value) = match self {
+            Self::DayMode { backlight } => (ColorMode::Day, *backlight),
+            Self::NightMode { backlight } => (ColorMode::Night, *backlight),
+            Self::ColorMode { color } => (ColorMode::Color, u8::from(*color)),
+        };
+
+        MANUFACTURER.put(&mut buf);
+        buf.split_off_mut(..DISPLAY_ID.len())
+            .unwrap()
+            .copy_from_slice(&DISPLAY_ID);
+        buf.put_u8(mode.into());
+        buf.put_reserved(1);
+        buf.put_u8(value);
+    }
+
+    fn decode(data: &[u8]) -> Result<Self, Self::DecodeError> {
+        let mut data = check_header::<Self>(data)?;
+
+        if data.remaining() < DISPLAY_ID.len() + 3
+            || data.split_off(..DISPLAY_ID.len()) != Some(&DISPLAY_ID[..])
+        {
+            return Err(());
+        }
+
+        let mode = ColorMode::from(data.get_u8());
+        data.advance(1);
+        let value = data.get_u8();
+
+        match mode {
+            ColorMode::Day => Ok(Self::DayMode { backlight: value }),
+            ColorMode::Night => Ok(Self::NightMode { backlight: value }),
+            ColorMode::Color => Ok(Self::ColorMode {
+                color: value.into(),
+            }),
+            ColorMode::Unknown(_) => Err(()),
+        }
+    }
+}
+
+impl ProprietaryMessage for Garmin {
+    const MANUFACTURER_CODE: u16 = MANUFACTURER.code;
+}
+
+/// Register all Garmin messages with `dispatcher`.
+#[allow(clippy::result_unit_err)]
+pub fn register<T, const N: usize>(dispatcher: &mut Dispatcher<T, N>) -> Result<(), ()>
+where
+    T: From<Garmin>,
+{
+    dispatcher.register_message::<Garmin>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        proprietary::{raymarine::Seatalk, victron},
+        well_known::round_trip,
+    };
+
+    #[test]
+    fn display_mode() {
+        let msg: Garmin = round_trip(&[
+            0xe5, 0x98, 0x84, 0x05, 0x04, 0x05, 0x05, 0x02, 0x01, 0xff, 0x20,
+        ]);
+        assert_eq!(msg, Garmin::NightMode { backlight: 32 });
+
+        let msg: Garmin = round_trip(&[
+            0xe5, 0x98, 0x84, 0x05, 0x04, 0x05, 0x05, 0x02, 0x0d, 0xff, 0x03,
+        ]);
+        assert_eq!(
+            msg,
+            Garmin::ColorMode {
+                color: Color::NightRedBlack
+            }
+        );
+    }
+
+    /// Garmin, Victron and Raymarine all send PGN 126720, so the
+    /// manufacturer code decides how it is decoded.
+    #[test]
+    fn dispatch() {
+        #[derive(Debug, PartialEq)]
+        enum Boat {
+            Garmin(Garmin),
+            Victron,
+            Raymarine,
+        }
+
+        impl From<Garmin> for Boat {
+            fn from(msg: Garmin) -> Self {
+                Self::Garmin(msg)
+            }
+        }
+
+        impl From<victron::Register> for Boat {
+            fn from(_: victron::Register) -> Self {
+                Self::Victron
+            }
+        }
+
+        impl From<victron::LongRegister> for Boat {
+            fn from(_: victron::LongRegister) -> Self {
+                Self::Victron
+            }
+        }
+
+        impl From<Seatalk> for Boat {
+            fn from(_: Seatalk) -> Self {
+                Self::Raymarine
+            }
+        }
+
+        let mut dispatcher = Dispatcher::<Boat, 4>::new();
+        register(&mut dispatcher).unwrap();
+        victron::register(&mut dispatcher).unwrap();
+        dispatcher.register_message::<Seatalk>().unwrap();
+
+        assert_eq!(
+            dispatcher.decode(
+                126720,
+                &[0xe5, 0x98, 0x84, 0x05, 0x04, 0x05, 0x05, 0x02, 0x00, 0xff, 0x64]
+            ),
+            Some(Ok(Boat::Garmin(Garmin::DayMode { backlight: 100 })))
+        );
+        assert_eq!(
+            dispatcher.decode(126720, &[0x66, 0x99, 0x0a, 0x01, b'H', b'Q']),
+            Some(Ok(Boat::Victron))
+        );
+        assert_eq!(
+            dispatcher.decode(
+                126720,
+                &[0x3b, 0x9f, 0x8c, 0x0c, 0x01, 0x01, 0x00, 0x64, 0x00]
+            ),
+            Some(Ok(Boat::Raymarine))
+        );
+    }
+}
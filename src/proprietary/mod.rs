@@ -0,0 +1,355 @@
+//! Proprietary messages, defined by manufacturers rather than by NMEA.
+//!
+//! Four PGN ranges are reserved for proprietary messages: 61184 (addressed,
+//! single frame), 65280–65535 (broadcast, single frame), 126720 (addressed,
+//! fast packet) and 130816–131071 (broadcast, fast packet). Every
+//! proprietary message starts with a [`Manufacturer`] header, so the same
+//! PGN means different things depending on who sent it.
+//!
+//! [`Proprietary`] is a raw proprietary message that can be received with
+//! a [`Decoder`](crate::Decoder) and used to send messages with our own
+//! manufacturer code. Typed messages implement [`ProprietaryMessage`], and
+//! a [`Dispatcher`] picks the decoder registered for the PGN and
+//! manufacturer code of a received message.
+
+use core::marker::PhantomData;
+
+use generic_array::{typenum, ArrayLength};
+
+use crate::{id::is_proprietary, well_known::Manufacturer, Message};
+
+pub mod garmin;
+pub mod raymarine;
+pub mod victron;
+
+/// The length of the [`Manufacturer`] header.
+pub const HEADER_LEN: usize = 2;
+
+/// The maximum length of a proprietary message, including the header.
+pub const MAX_LEN: usize = 223;
+
+/// A proprietary message with a known manufacturer code. The header is
+/// part of the encoded message, and [`Message::decode`] fails if it does
+/// not match [`ProprietaryMessage::MANUFACTURER_CODE`].
+pub trait ProprietaryMessage: Message<DecodeError = ()> {
+    const MANUFACTURER_CODE: u16;
+}
+
+/// Read the manufacturer of a proprietary message, or [`None`] if the
+/// message is too short.
+#[must_use]
+pub fn manufacturer(mut data: &[u8]) -> Option<Manufacturer> {
+    if data.len() < HEADER_LEN {
+        return None;
+    }
+
+    Some(Manufacturer::get(&mut data))
+}
+
+/// Check that a proprietary message was sent by the manufacturer of `T`,
+/// returning the data after the header. Useful when implementing
+/// [`Message::decode`] for a [`ProprietaryMessage`].
+#[allow(clippy::result_unit_err)]
+pub fn check_header<T: ProprietaryMessage>(data: &[u8]) -> Result<&[u8], ()> {
+    match manufacturer(data) {
+        Some(m) if m.code == T::MANUFACTURER_CODE => Ok(&data[HEADER_LEN..]),
+        _ => Err(()),
+    }
+}
+
+/// A raw proprietary message with PGN `PGN` and an encoded length of at
+/// most `L`. Use the aliases for the four proprietary ranges.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Proprietary<const PGN: u32, L> {
+    /// The whole message, including the header.
+    data: heapless::Vec<u8, MAX_LEN>,
+    _len: PhantomData<L>,
+}
+
+#[cfg(feature = "defmt")]
+impl<const PGN: u32, L> defmt::Format for Proprietary<PGN, L> {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "Proprietary {{ pgn: {}, data: {} }}",
+            PGN,
+            self.data.as_slice()
+        );
+    }
+}
+
+/// PGN 61184, addressed single frame.
+pub type AddressedSingleFrame = Proprietary<61184, typenum::U8>;
+
+/// PGNs 65280–65535, broadcast single frame.
+pub type SingleFrame<const PGN: u32> = Proprietary<PGN, typenum::U8>;
+
+/// PGN 126720, addressed fast packet.
+pub type AddressedFastPacket = Proprietary<126720, typenum::U223>;
+
+/// PGNs 130816–131071, broadcast fast packet.
+pub type FastPacket<const PGN: u32> = Proprietary<PGN, typenum::U223>;
+
+impl<const PGN: u32, L: ArrayLength> Proprietary<PGN, L> {
+    const VALID: () = assert!(is_proprietary(PGN), "not a proprietary PGN");
+
+    /// Create a message with our own manufacturer code. Fails if the
+    /// payload does not fit in `L` bytes together with the header.
+    #[allow(clippy::result_unit_err)]
+    pub fn new(manufacturer: Manufacturer, payload: &[u8]) -> Result<Self, ()> {
+        let () = Self::VALID;
+
+        if HEADER_LEN + payload.len() > L::USIZE.min(MAX_LEN) {
+            return Err(());
+        }
+
+        let mut data = heapless::Vec::new();
+        data.resize(HEADER_LEN, 0).unwrap();
+        Manufacturer::put(&manufacturer, &mut data.as_mut_slice());
+        data.extend_from_slice(payload).unwrap();
+
+        Ok(Self {
+            data,
+            _len: PhantomData,
+        })
+    }
+
+    #[must_use]
+    pub fn manufacturer(&self) -> Manufacturer {
+        // the header is always present
+        manufacturer(&self.data).unwrap()
+    }
+
+    /// The data after the header.
+    #[must_use]
+    pub fn payload(&self) -> &[u8] {
+        &self.data[HEADER_LEN..]
+    }
+
+    /// The whole message, including the header.
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+impl<const PGN: u32, L: ArrayLength> Message for Proprietary<PGN, L> {
+    const PGN: u32 = PGN;
+
+    type EncodedLen = L;
+
+    type DecodeError = ();
+
+    fn encoded_len(&self) -> usize {
+        self.data.len()
+    }
+
+    fn encode(&self, buf: &mut [u8]) {
+        buf[..self.data.len()].copy_from_slice(&self.data);
+    }
+
+    fn decode(data: &[u8]) -> Result<Self, Self::DecodeError> {
+        let () = Self::VALID;
+
+        if data.len() < HEADER_LEN {
+            return Err(());
+        }
+
+        Ok(Self {
+            data: heapless::Vec::from_slice(&data[..data.len().min(L::USIZE)]).map_err(|_| ())?,
+            _len: PhantomData,
+        })
+    }
+}
+
+/// Decodes a proprietary message, including its header.
+pub type DecodeFn<T> = fn(&[u8]) -> Result<T, ()>;
+
+struct Entry<T> {
+    pgn: u32,
+    manufacturer_code: u16,
+    decode: DecodeFn<T>,
+}
+
+fn decode_into<M, T>(data: &[u8]) -> Result<T, ()>
+where
+    M: ProprietaryMessage,
+    T: From<M>,
+{
+    M::decode(data).map(T::from)
+}
+
+/// Decodes proprietary messages into `T` with up to `N` registered
+/// decoders, keyed by PGN and manufacturer code.
+pub struct Dispatcher<T, const N: usize> {
+    entries: heapless::Vec<Entry<T>, N>,
+}
+
+impl<T, const N: usize> Default for Dispatcher<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> core::fmt::Debug for Dispatcher<T, N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list()
+            .entries(self.entries.iter().map(|e| (e.pgn, e.manufacturer_code)))
+            .finish()
+    }
+}
+
+impl<T, const N: usize> Dispatcher<T, N> {
+    pub const fn new() -> Self {
+        Self {
+            entries: heapless::Vec::new(),
+        }
+    }
+
+    /// Register a decoder for `pgn` sent by `manufacturer_code`, replacing
+    /// any previous one. Fails if `N` decoders have already been
+    /// registered.
+    #[allow(clippy::result_unit_err)]
+    pub fn register(
+        &mut self,
+        pgn: u32,
+        manufacturer_code: u16,
+        decode: DecodeFn<T>,
+    ) -> Result<(), ()> {
+        let entry = Entry {
+            pgn,
+            manufacturer_code,
+            decode,
+        };
+
+        match self
+            .entries
+            .iter_mut()
+            .find(|e| e.pgn == pgn && e.manufacturer_code == manufacturer_code)
+        {
+            Some(existing) => *existing = entry,
+            None => self.entries.push(entry).map_err(|_| ())?,
+        }
+
+        Ok(())
+    }
+
+    /// Register a typed message.
+    #[allow(clippy::result_unit_err)]
+    pub fn register_message<M>(&mut self) -> Result<(), ()>
+    where
+        M: ProprietaryMessage,
+        T: From<M>,
+    {
+        self.register(M::PGN, M::MANUFACTURER_CODE, decode_into::<M, T>)
+    }
+
+    /// Decode a proprietary message, including its header. Returns
+    /// [`None`] if no decoder is registered for the PGN and manufacturer.
+    pub fn decode(&self, pgn: u32, data: &[u8]) -> Option<Result<T, ()>> {
+        let code = manufacturer(data)?.code;
+        let entry = self
+            .entries
+            .iter()
+            .find(|e| e.pgn == pgn && e.manufacturer_code == code)?;
+
+        Some((entry.decode)(data))
+    }
+
+    /// Decode a raw proprietary message, for example one returned by a
+    /// [`Decoder`](crate::Decoder).
+    pub fn decode_raw<const PGN: u32, L>(&self, msg: &Proprietary<PGN, L>) -> Option<Result<T, ()>>
+    where
+        L: ArrayLength,
+    {
+        self.decode(PGN, msg.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{well_known::IndustryCode, BufMut};
+
+    const OURS: Manufacturer = Manufacturer {
+        code: 2000,
+        industry: IndustryCode::Marine,
+    };
+
+    /// A made-up message for PGN 65280.
+    #[derive(Debug, PartialEq)]
+    struct Dimmer {
+        level: u8,
+    }
+
+    impl Message for Dimmer {
+        const PGN: u32 = 65280;
+
+        type EncodedLen = typenum::U3;
+
+        type DecodeError = ();
+
+        fn encode(&self, mut buf: &mut [u8]) {
+            OURS.put(&mut buf);
+            buf.put_u8(self.level);
+        }
+
+        fn decode(data: &[u8]) -> Result<Self, Self::DecodeError> {
+            match check_header::<Self>(data)? {
+                &[level, ..] => Ok(Self { level }),
+                _ => Err(()),
+            }
+        }
+    }
+
+    impl ProprietaryMessage for Dimmer {
+        const MANUFACTURER_CODE: u16 = OURS.code;
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum Ours {
+        Dimmer(Dimmer),
+    }
+
+    impl From<Dimmer> for Ours {
+        fn from(msg: Dimmer) -> Self {
+            Self::Dimmer(msg)
+        }
+    }
+
+    #[test]
+    fn raw() {
+        let msg = SingleFrame::<65280>::new(OURS, &[0x2a]).unwrap();
+        let mut buf = [0; 8];
+        msg.encode(&mut buf);
+
+        // 2000 | 0x1800 | 4 << 13
+        assert_eq!(&buf[..msg.encoded_len()], [0xd0, 0x9f, 0x2a]);
+        assert_eq!(msg.manufacturer(), OURS);
+        assert_eq!(msg.payload(), [0x2a]);
+        assert_eq!(SingleFrame::<65280>::decode(&buf[..3]), Ok(msg));
+
+        assert_eq!(AddressedSingleFrame::new(OURS, &[0; 7]), Err(()));
+        assert!(AddressedFastPacket::new(OURS, &[0; 7]).is_ok());
+    }
+
+    #[test]
+    fn dispatch() {
+        let mut dispatcher = Dispatcher::<Ours, 2>::new();
+        dispatcher.register_message::<Dimmer>().unwrap();
+
+        let msg = SingleFrame::<65280>::new(OURS, &[0x2a]).unwrap();
+        assert_eq!(
+            dispatcher.decode_raw(&msg),
+            Some(Ok(Ours::Dimmer(Dimmer { level: 42 })))
+        );
+        assert_eq!(dispatcher.decode(65280, &[0xd0, 0x9f]), Some(Err(())));
+
+        // other manufacturers and PGNs are not dispatched
+        let other = Manufacturer { code: 1851, ..OURS };
+        let msg = SingleFrame::<65280>::new(other, &[0x2a]).unwrap();
+        assert_eq!(dispatcher.decode_raw(&msg), None);
+        let msg = SingleFrame::<65281>::new(OURS, &[0x2a]).unwrap();
+        assert_eq!(dispatcher.decode_raw(&msg), None);
+    }
+}
@@ -0,0 +1,477 @@
+//! Raymarine SeaTalkNG messages (manufacturer code 1851): autopilot
+//! keystrokes and modes, the locked heading and display settings.
+//!
+//! The autopilot mode is changed by sending a Group Function Command for
+//! [`PilotMode`], built with [`PilotMode::command`], while the keys of a
+//! pilot controller are sent as [`Keystroke`]s.
+
+use generic_array::typenum::{self, Unsigned};
+
+use super::{check_header, Dispatcher, ProprietaryMessage, HEADER_LEN};
+use crate::{
+    well_known::{
+        group_function::{Command, GroupFunction, Parameters},
+        IndustryCode, Manufacturer,
+    },
+    Buf, BufMut, Message,
+};
+
+pub const MANUFACTURER: Manufacturer = Manufacturer {
+    code: 1851,
+    industry: IndustryCode::Marine,
+};
+
+lookup! {
+    /// A key on a pilot controller.
+    pub enum Key: u8 {
+        Auto = 1,
+        Standby = 2,
+        Wind = 3,
+        Minus1 = 5,
+        Minus10 = 6,
+        Plus1 = 7,
+        Plus10 = 8,
+        Minus1And10 = 33,
+        Plus1And10 = 34,
+        Track = 35,
+    }
+}
+
+lookup! {
+    /// The steering mode of the autopilot.
+    pub enum AutopilotMode: u16 {
+        Standby = 0x0000,
+        Auto = 0x0040,
+        Wind = 0x0100,
+        Track = 0x0180,
+        NoDrift = 0x0181,
+    }
+}
+
+lookup! {
+    /// A group of displays that share their settings.
+    pub enum NetworkGroup: u8 {
+        None = 0,
+        Helm1 = 1,
+        Helm2 = 2,
+        Cockpit = 3,
+        Flybridge = 4,
+        Mast = 5,
+        Group1 = 6,
+        Group2 = 7,
+        Group3 = 8,
+        Group4 = 9,
+        Group5 = 10,
+    }
+}
+
+lookup! {
+    /// The colour palette of a display.
+    pub enum DisplayColor: u8 {
+        Day1 = 0,
+        Day2 = 2,
+        RedBlack = 3,
+        Inverse = 4,
+    }
+}
+
+/// Identifies a SeaTalk1 datagram wrapped in PGN 126720.
+const SEATALK1_ID: u16 = 0x81f0;
+
+/// The SeaTalk1 keystroke datagram.
+const KEYSTROKE_COMMAND: u8 = 0x86;
+
+/// Sent after a keystroke by Raymarine pilot controllers. Its meaning is
+/// unknown, but autopilots ignore keystrokes without it.
+const KEYSTROKE_TRAILER: [u8; 14] = [
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xc1, 0xc2, 0xcd, 0x66, 0x80, 0xd3, 0x42, 0xb1, 0xc8,
+];
+
+/// Identifies display settings in PGN 126720.
+const DISPLAY_ID: u16 = 0x0c8c;
+
+const BRIGHTNESS_COMMAND: u8 = 0;
+const COLOR_COMMAND: u8 = 1;
+
+/// A key pressed on a pilot controller.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Keystroke {
+    /// The SeaTalk1 ID of the sending device.
+    pub device: u8,
+    pub key: Key,
+}
+
+/// PGN 126720 sent by Raymarine devices. This is a fast packet.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Seatalk {
+    Keystroke(Keystroke),
+    /// The brightness of a group of displays, in percent.
+    DisplayBrightness {
+        group: NetworkGroup,
+        brightness: u8,
+    },
+    DisplayColor {
+        group: NetworkGroup,
+        color: DisplayColor,
+    },
+}
+
+impl Keystroke {
+    /// The SeaTalk1 device ID used by pilot controllers.
+    pub const PILOT_CONTROLLER: u8 = 0x21;
+
+    /// A keystroke from a pilot controller.
+    #[must_use]
+    pub const fn new(key: Key) -> Self {
+        Self {
+            device: Self::PILOT_CONTROLLER,
+            key,
+        }
+    }
+}
+
+impl Seatalk {
+    /// The length of a keystroke. Display settings are shorter.
+    const KEYSTROKE_LEN: usize = HEADER_LEN + 6 + KEYSTROKE_TRAILER.len();
+
+    const DISPLAY_LEN: usize = HEADER_LEN + 7;
+}
+
+impl Message for Seatalk {
+    const PGN: u32 = 126720;
+
+    type EncodedLen = typenum::U22;
+
+    type DecodeError = ();
+
+    fn encoded_len(&self) -> usize {
+        match self {
+            Self::Keystroke(_) => Self::KEYSTROKE_LEN,
+            Self::DisplayBrightness { .. } | Self::DisplayColor { .. } => Self::DISPLAY_LEN,
+        }
+    }
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        MANUFACTURER.put(&mut buf);
+
+        match self {
+            Self::Keystroke(keystroke) => {
+                let key = u8::from(keystroke.key);
+
+                buf.put_u16(SEATALK1_ID);
+                buf.put_u8(KEYSTROKE_COMMAND);
+                buf.put_u8(keystroke.device);
+                buf.put_u8(key);
+                buf.put_u8(!key);
+                buf.split_off_mut(..KEYSTROKE_TRAILER.len())
+                    .unwrap()
+                    .copy_from_slice(&KEYSTROKE_TRAILER);
+            }
+            Self::DisplayBrightness { group, brightness } => {
+                buf.put_u16(DISPLAY_ID);
+                buf.put_u8((*group).into());
+                buf.put_u8(0x01);
+                buf.put_u8(BRIGHTNESS_COMMAND);
+                buf.put_u8(*brightness);
+                buf.put_u8(0x00);
+            }
+            Self::DisplayColor { group, color } => {
+                buf.put_u16(DISPLAY_ID);
+                buf.put_u8((*group).into());
+                buf.put_u8(0x01);
+                buf.put_u8(COLOR_COMMAND);
+                buf.put_u8((*color).into());
+                buf.put_u8(0x00);
+            }
+        }
+    }
+
+    fn decode(data: &[u8]) -> Result<Self, Self::DecodeError> {
+        let mut data = check_header::<Self>(data)?;
+
+        if data.remaining() < 2 {
+            return Err(());
+        }
+
+        match data.get_u16() {
+            SEATALK1_ID => {
+                if data.remaining() < 4 || data.get_u8() != KEYSTROKE_COMMAND {
+                    return Err(());
+                }
+
+                Ok(Self::Keystroke(Keystroke {
+                    device: data.get_u8(),
+                    key: data.get_u8().into(),
+                }))
+            }
+            DISPLAY_ID => {
+                if data.remaining() < 4 {
+                    return Err(());
+                }
+
+                let group = data.get_u8().into();
+                data.advance(1);
+
+                match data.get_u8() {
+                    BRIGHTNESS_COMMAND => Ok(Self::DisplayBrightness {
+                        group,
+                        brightness: data.get_u8(),
+                    }),
+                    COLOR_COMMAND => Ok(Self::DisplayColor {
+                        group,
+                        color: data.get_u8().into(),
+                    }),
+                    _ => Err(()),
+                }
+            }
+            _ => Err(()),
+        }
+    }
+}
+
+impl ProprietaryMessage for Seatalk {
+    const MANUFACTURER_CODE: u16 = MANUFACTURER.code;
+}
+
+/// PGN 65379, the mode of the autopilot.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PilotMode {
+    pub mode: AutopilotMode,
+    pub sub_mode: u16,
+    pub data: u8,
+}
+
+impl PilotMode {
+    /// The numbers of the fields of PGN 65379, used in group functions.
+    const MANUFACTURER_FIELD: u8 = 1;
+    const INDUSTRY_FIELD: u8 = 3;
+    const MODE_FIELD: u8 = 4;
+    const SUB_MODE_FIELD: u8 = 5;
+
+    /// A Group Function Command that changes the mode of the autopilot, to
+    /// be sent to the autopilot computer.
+    #[must_use]
+    pub fn command(mode: AutopilotMode) -> GroupFunction {
+        let mut parameters = Parameters::new();
+
+        // cannot fail since there is plenty of room
+        let _ = parameters.push(Self::MANUFACTURER_FIELD, &MANUFACTURER.code.to_le_bytes());
+        let _ = parameters.push(Self::INDUSTRY_FIELD, &[MANUFACTURER.industry.into()]);
+        let _ = parameters.push(Self::MODE_FIELD, &u16::from(mode).to_le_bytes());
+        let _ = parameters.push(Self::SUB_MODE_FIELD, &[0xff, 0xff]);
+
+        GroupFunction::Command(Command {
+            pgn: Self::PGN,
+            priority: None,
+            parameters,
+        })
+    }
+}
+
+impl Message for PilotMode {
+    const PGN: u32 = 65379;
+
+    type EncodedLen = typenum::U8;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        MANUFACTURER.put(&mut buf);
+        buf.put_u16(self.mode.into());
+        buf.put_u16(self.sub_mode);
+        buf.put_u8(self.data);
+        buf.put_reserved(1);
+    }
+
+    fn decode(data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        let mut data = check_header::<Self>(data)?;
+
+        Ok(Self {
+            mode: data.get_u16().into(),
+            sub_mode: data.get_u16(),
+            data: data.get_u8(),
+        })
+    }
+}
+
+impl ProprietaryMessage for PilotMode {
+    const MANUFACTURER_CODE: u16 = MANUFACTURER.code;
+}
+
+/// PGN 65360, the heading the autopilot steers towards.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PilotLockedHeading {
+    /// Sequence ID, used to tie together related messages.
+    pub sid: Option<u8>,
+    /// True heading in radians.
+    pub target_true: Option<f32>,
+    /// Magnetic heading in radians.
+    pub target_magnetic: Option<f32>,
+}
+
+impl Message for PilotLockedHeading {
+    const PGN: u32 = 65360;
+
+    type EncodedLen = typenum::U8;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        MANUFACTURER.put(&mut buf);
+        buf.put_opt_u8(self.sid);
+        buf.put_fixed_u16(self.target_true, 1e-4);
+        buf.put_fixed_u16(self.target_magnetic, 1e-4);
+        buf.put_reserved(1);
+    }
+
+    fn decode(data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        let mut data = check_header::<Self>(data)?;
+
+        Ok(Self {
+            sid: data.get_opt_u8(),
+            target_true: data.get_fixed_u16(1e-4),
+            target_magnetic: data.get_fixed_u16(1e-4),
+        })
+    }
+}
+
+impl ProprietaryMessage for PilotLockedHeading {
+    const MANUFACTURER_CODE: u16 = MANUFACTURER.code;
+}
+
+/// Register all Raymarine messages with `dispatcher`.
+#[allow(clippy::result_unit_err)]
+pub fn register<T, const N: usize>(dispatcher: &mut Dispatcher<T, N>) -> Result<(), ()>
+where
+    T: From<Seatalk> + From<PilotMode> + From<PilotLockedHeading>,
+{
+    dispatcher.register_message::<Seatalk>()?;
+    dispatcher.register_message::<PilotMode>()?;
+    dispatcher.register_message::<PilotLockedHeading>()
+}
+
+#[cfg(test)]
+mod tests {
+    use core::f32::consts::FRAC_PI_2;
+
+    use super::*;
+    use crate::well_known::round_trip;
+
+    #[test]
+    fn keystroke() {
+        // +1 from a pilot controller
+        let msg: Seatalk = round_trip(&[
+            0x3b, 0x9f, 0xf0, 0x81, 0x86, 0x21, 0x07, 0xf8, 0xff, 0xff, 0xff, 0xff, 0xff, 0xc1,
+            0xc2, 0xcd, 0x66, 0x80, 0xd3, 0x42, 0xb1, 0xc8,
+        ]);
+
+        assert_eq!(msg, Seatalk::Keystroke(Keystroke::new(Key::Plus1)));
+    }
+
+    #[test]
+    fn display() {
+        let msg: Seatalk = round_trip(&[0x3b, 0x9f, 0x8c, 0x0c, 0x01, 0x01, 0x00, 0x64, 0x00]);
+        assert_eq!(
+            msg,
+            Seatalk::DisplayBrightness {
+                group: NetworkGroup::Helm1,
+                brightness: 100,
+            }
+        );
+
+        let msg: Seatalk = round_trip(&[0x3b, 0x9f, 0x8c, 0x0c, 0x03, 0x01, 0x01, 0x03, 0x00]);
+        assert_eq!(
+            msg,
+            Seatalk::DisplayColor {
+                group: NetworkGroup::Cockpit,
+                color: DisplayColor::RedBlack,
+            }
+        );
+    }
+
+    #[test]
+    fn pilot_mode() {
+        let msg: PilotMode = round_trip(&[0x3b, 0x9f, 0x80, 0x01, 0xff, 0xff, 0xff, 0xff]);
+        assert_eq!(msg.mode, AutopilotMode::Track);
+
+        let command = PilotMode::command(AutopilotMode::Auto);
+        let mut buf = [0; 17];
+        assert_eq!(command.encoded_len(), buf.len());
+        command.encode(&mut buf);
+
+        assert_eq!(
+            buf,
+            [
+                0x01, 0x63, 0xff, 0x00, 0xf8, 0x04, 0x01, 0x3b, 0x07, 0x03, 0x04, 0x04, 0x40, 0x00,
+                0x05, 0xff, 0xff,
+            ]
+        );
+    }
+
+    #[test]
+    fn pilot_locked_heading() {
+        let msg: PilotLockedHeading = round_trip(&[0x3b, 0x9f, 0x00, 0x5c, 0x3d, 0xff, 0xff, 0xff]);
+
+        assert_eq!(msg.sid, Some(0));
+        assert!((msg.target_true.unwrap() - FRAC_PI_2).abs() < 1e-4);
+        assert_eq!(msg.target_magnetic, None);
+
+        // not from Raymarine
+        assert_eq!(
+            PilotLockedHeading::decode(&[0x87, 0x98, 0x00, 0x5c, 0x3d, 0xff, 0xff, 0xff]),
+            Err(())
+        );
+    }
+
+    #[test]
+    fn dispatch() {
+        #[derive(Debug, PartialEq)]
+        enum Raymarine {
+            Seatalk(Seatalk),
+            PilotMode(PilotMode),
+            PilotLockedHeading(PilotLockedHeading),
+        }
+
+        impl From<Seatalk> for Raymarine {
+            fn from(msg: Seatalk) -> Self {
+                Self::Seatalk(msg)
+            }
+        }
+
+        impl From<PilotMode> for Raymarine {
+            fn from(msg: PilotMode) -> Self {
+                Self::PilotMode(msg)
+            }
+        }
+
+        impl From<PilotLockedHeading> for Raymarine {
+            fn from(msg: PilotLockedHeading) -> Self {
+                Self::PilotLockedHeading(msg)
+            }
+        }
+
+        let mut dispatcher = Dispatcher::<Raymarine, 3>::new();
+        register(&mut dispatcher).unwrap();
+
+        assert_eq!(
+            dispatcher.decode(65379, &[0x3b, 0x9f, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff]),
+            Some(Ok(Raymarine::PilotMode(PilotMode {
+                mode: AutopilotMode::Standby,
+                sub_mode: 0xffff,
+                data: 0xff,
+            })))
+        );
+    }
+}
@@ -0,0 +1,180 @@
+//! Victron Energy messages (manufacturer code 358). Victron devices expose
+//! their settings and measurements as VREG registers, which are read and
+//! written with [`Register`] (PGN 61184) or, for values longer than four
+//! bytes such as serial numbers, [`LongRegister`] (PGN 126720).
+
+use generic_array::typenum::{self, Unsigned};
+
+use super::{check_header, Dispatcher, ProprietaryMessage, HEADER_LEN, MAX_LEN};
+use crate::{
+    well_known::{IndustryCode, Manufacturer},
+    Buf, BufMut, Message,
+};
+
+pub const MANUFACTURER: Manufacturer = Manufacturer {
+    code: 358,
+    industry: IndustryCode::Marine,
+};
+
+lookup! {
+    /// A VREG register. The scaling of the value depends on the register.
+    pub enum RegisterId: u16 {
+        ProductId = 0x0100,
+        AppVersion = 0x0102,
+        SerialNumber = 0x010a,
+        ModelName = 0x010b,
+        DeviceMode = 0x0200,
+        DeviceState = 0x0201,
+        /// Time to go in minutes.
+        TimeToGo = 0x0ffe,
+        /// State of charge in 0.01 %.
+        StateOfCharge = 0x0fff,
+        /// Battery temperature in 0.01 K.
+        BatteryTemperature = 0xedec,
+        /// Battery voltage in 0.01 V.
+        MainVoltage = 0xed8d,
+        /// Battery current in 0.1 A, signed.
+        Current = 0xed8f,
+        /// Consumed amp hours in 0.1 Ah, signed.
+        ConsumedAmpHours = 0xeeff,
+    }
+}
+
+/// PGN 61184, the value of a register. Sent by a device when the register
+/// changes or is requested, and to a device to change it.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Register {
+    pub id: RegisterId,
+    pub value: u32,
+}
+
+impl Message for Register {
+    const PGN: u32 = 61184;
+
+    type EncodedLen = typenum::U8;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        MANUFACTURER.put(&mut buf);
+        buf.put_u16(self.id.into());
+        buf.put_u32(self.value);
+    }
+
+    fn decode(data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        let mut data = check_header::<Self>(data)?;
+
+        Ok(Self {
+            id: data.get_u16().into(),
+            value: data.get_u32(),
+        })
+    }
+}
+
+impl ProprietaryMessage for Register {
+    const MANUFACTURER_CODE: u16 = MANUFACTURER.code;
+}
+
+/// The maximum length of the value of a [`LongRegister`].
+pub const MAX_REGISTER_LEN: usize = MAX_LEN - HEADER_LEN - 2;
+
+/// PGN 126720, the value of a register that does not fit in a
+/// [`Register`]. This is a fast packet.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LongRegister {
+    pub id: RegisterId,
+    pub value: heapless::Vec<u8, MAX_REGISTER_LEN>,
+}
+
+impl LongRegister {
+    /// The value as a string, for registers such as
+    /// [`RegisterId::SerialNumber`]. Trailing NULs are stripped.
+    #[must_use]
+    pub fn as_str(&self) -> Option<&str> {
+        let end = self
+            .value
+            .iter()
+            .rposition(|&b| b != 0)
+            .map_or(0, |i| i + 1);
+
+        core::str::from_utf8(&self.value[..end]).ok()
+    }
+}
+
+impl Message for LongRegister {
+    const PGN: u32 = 126720;
+
+    type EncodedLen = typenum::U223;
+
+    type DecodeError = ();
+
+    fn encoded_len(&self) -> usize {
+        HEADER_LEN + 2 + self.value.len()
+    }
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        MANUFACTURER.put(&mut buf);
+        buf.put_u16(self.id.into());
+        buf.split_off_mut(..self.value.len())
+            .unwrap()
+            .copy_from_slice(&self.value);
+    }
+
+    fn decode(data: &[u8]) -> Result<Self, Self::DecodeError> {
+        let mut data = check_header::<Self>(data)?;
+
+        if data.remaining() < 2 {
+            return Err(());
+        }
+
+        Ok(Self {
+            id: data.get_u16().into(),
+            value: heapless::Vec::from_slice(data).map_err(|_| ())?,
+        })
+    }
+}
+
+impl ProprietaryMessage for LongRegister {
+    const MANUFACTURER_CODE: u16 = MANUFACTURER.code;
+}
+
+/// Register all Victron messages with `dispatcher`.
+#[allow(clippy::result_unit_err)]
+pub fn register<T, const N: usize>(dispatcher: &mut Dispatcher<T, N>) -> Result<(), ()>
+where
+    T: From<Register> + From<LongRegister>,
+{
+    dispatcher.register_message::<Register>()?;
+    dispatcher.register_message::<LongRegister>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::well_known::round_trip;
+
+    #[test]
+    fn register() {
+        // 13.05 V
+        let msg: Register = round_trip(&[0x66, 0x99, 0x8d, 0xed, 0x19, 0x05, 0x00, 0x00]);
+
+        assert_eq!(msg.id, RegisterId::MainVoltage);
+        assert_eq!(msg.value, 1305);
+    }
+
+    #[test]
+    fn long_register() {
+        let msg: LongRegister = round_trip(&[
+            0x66, 0x99, 0x0a, 0x01, b'H', b'Q', b'2', b'2', b'3', b'4', b'5', 0x00,
+        ]);
+
+        assert_eq!(msg.id, RegisterId::SerialNumber);
+        assert_eq!(msg.as_str(), Some("HQ22345"));
+    }
+}
@@ -0,0 +1,443 @@
+//! An alert manager for devices that raise alerts, such as bilge or engine
+//! monitors.
+//!
+//! The application raises and rectifies alerts with [`AlertManager`], which
+//! announces them as [`Alert`]s and follows the acknowledge and silence
+//! commands that displays send as [`AlertResponse`]s. The manager
+//! implements [`Service`], so the embassy and tokio event loops can run it.
+//! With [`blocking::Client`](crate::blocking::Client), received frames are
+//! passed to [`AlertManager::handle_frame`] and the alerts returned by
+//! [`AlertManager::poll_transmit`] are sent with
+//! [`Client::send_fast_packet`](crate::blocking::Client::send_fast_packet).
+//!
+//! An alert starts out active. A display may temporarily silence it, after
+//! which it becomes active again once [`SILENCE_DURATION`] has passed, or
+//! acknowledge it. When the condition is rectified, an acknowledged alert
+//! returns to normal. Alarms that have not been acknowledged are kept
+//! awaiting acknowledgement until they are, while warnings and cautions
+//! return to normal directly.
+
+use core::time::Duration;
+
+use super::{Service, Transmit};
+use crate::{
+    id::DESTINATION_BROADCAST,
+    time::Instant,
+    well_known::{
+        alert::{
+            Alert, AlertKey, AlertResponse, AlertResponseCommand, AlertState, AlertStatus,
+            AlertThresholdStatus, AlertTriggerCondition, AlertType,
+        },
+        DeviceName,
+    },
+    Decoder, Id, Message, NmeaFrame,
+};
+
+/// How often alerts that are not normal are announced.
+pub const ANNOUNCE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long a temporary silence lasts.
+pub const SILENCE_DURATION: Duration = Duration::from_secs(30);
+
+/// The priority of announced alerts.
+const PRIORITY: u8 = 2;
+
+/// The number of displays that can send responses concurrently.
+const RESPONSE_SOURCES: usize = 4;
+
+#[derive(Debug, Clone)]
+struct Entry {
+    key: AlertKey,
+    priority: u8,
+    state: AlertState,
+    acknowledged_by: Option<DeviceName>,
+    silenced_until: Option<Instant>,
+    next_announce: Instant,
+}
+
+impl Entry {
+    fn set_state(&mut self, state: AlertState) {
+        self.state = state;
+        // announce the change right away
+        self.next_announce = Instant::ZERO;
+    }
+
+    fn alert(&self) -> Alert {
+        Alert {
+            key: self.key.clone(),
+            status: AlertStatus {
+                temporary_silence: self.state == AlertState::Silenced,
+                acknowledged: self.acknowledged_by.is_some(),
+                escalated: false,
+                temporary_silence_support: true,
+                acknowledge_support: true,
+                escalation_support: false,
+            },
+            acknowledge_source_name: self.acknowledged_by,
+            trigger_condition: AlertTriggerCondition::Auto,
+            threshold_status: match self.state {
+                AlertState::Active | AlertState::Silenced => {
+                    AlertThresholdStatus::ThresholdExceeded
+                }
+                AlertState::Acknowledged => AlertThresholdStatus::Acknowledged,
+                AlertState::AwaitingAcknowledge => AlertThresholdStatus::AwaitingAcknowledge,
+                _ => AlertThresholdStatus::Normal,
+            },
+            priority: self.priority,
+            state: self.state,
+        }
+    }
+}
+
+/// Tracks up to `N` alerts. See the [module-level documentation](self) for
+/// more information.
+pub struct AlertManager<const N: usize> {
+    entries: heapless::Vec<Entry, N>,
+    responses: Decoder<AlertResponse, RESPONSE_SOURCES>,
+}
+
+impl<const N: usize> Default for AlertManager<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> core::fmt::Debug for AlertManager<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("AlertManager")
+            .field("entries", &self.entries)
+            .finish()
+    }
+}
+
+impl<const N: usize> AlertManager<N> {
+    pub const fn new() -> Self {
+        Self {
+            entries: heapless::Vec::new(),
+            responses: Decoder::new(),
+        }
+    }
+
+    fn entry(&mut self, key: &AlertKey) -> Option<&mut Entry> {
+        self.entries.iter_mut().find(|e| e.key == *key)
+    }
+
+    /// The state of an alert, or [`None`] if it is not tracked (anymore).
+    #[must_use]
+    pub fn state(&self, key: &AlertKey) -> Option<AlertState> {
+        self.entries.iter().find(|e| e.key == *key).map(|e| e.state)
+    }
+
+    /// Raise an alert because its condition has occurred. Raising an alert
+    /// that is already active has no effect, while an alert that has
+    /// returned to normal but was not announced yet becomes active again.
+    /// Fails if `N` alerts are already being tracked.
+    #[allow(clippy::result_unit_err)]
+    pub fn raise(&mut self, key: AlertKey, priority: u8) -> Result<(), ()> {
+        if let Some(entry) = self.entry(&key) {
+            match entry.state {
+                AlertState::AwaitingAcknowledge => entry.set_state(AlertState::Active),
+                AlertState::Normal | AlertState::Disabled => {
+                    // a new occurrence has to be acknowledged again
+                    entry.acknowledged_by = None;
+                    entry.priority = priority;
+                    entry.set_state(AlertState::Active);
+                }
+                _ => {}
+            }
+
+            return Ok(());
+        }
+
+        self.entries
+            .push(Entry {
+                key,
+                priority,
+                state: AlertState::Active,
+                acknowledged_by: None,
+                silenced_until: None,
+                next_announce: Instant::ZERO,
+            })
+            .map_err(|_| ())
+    }
+
+    /// Mark the condition of an alert as rectified.
+    pub fn rectify(&mut self, key: &AlertKey) {
+        let Some(entry) = self.entry(key) else {
+            return;
+        };
+
+        match entry.state {
+            AlertState::Active | AlertState::Silenced
+                if matches!(key.alert_type, AlertType::EmergencyAlarm | AlertType::Alarm) =>
+            {
+                entry.silenced_until = None;
+                entry.set_state(AlertState::AwaitingAcknowledge);
+            }
+            AlertState::Active | AlertState::Silenced | AlertState::Acknowledged => {
+                entry.silenced_until = None;
+                entry.set_state(AlertState::Normal);
+            }
+            _ => {}
+        }
+    }
+
+    /// Apply an acknowledge or silence command from a display. Returns true
+    /// if the state of the alert changed.
+    pub fn handle_response(&mut self, response: &AlertResponse, now: Instant) -> bool {
+        let Some(entry) = self.entry(&response.key) else {
+            return false;
+        };
+
+        match (response.command, entry.state) {
+            (
+                AlertResponseCommand::Acknowledge,
+                AlertState::Active | AlertState::Silenced | AlertState::AwaitingAcknowledge,
+            ) => {
+                entry.acknowledged_by = Some(response.acknowledge_source_name);
+                entry.silenced_until = None;
+
+                if entry.state == AlertState::AwaitingAcknowledge {
+                    entry.set_state(AlertState::Normal);
+                } else {
+                    entry.set_state(AlertState::Acknowledged);
+                }
+
+                true
+            }
+            (AlertResponseCommand::TemporarySilence, AlertState::Active) => {
+                entry.silenced_until = Some(now + SILENCE_DURATION);
+                entry.set_state(AlertState::Silenced);
+
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Process a frame received from the bus. Returns true if the state of
+    /// an alert changed.
+    pub fn handle_frame(&mut self, frame: &NmeaFrame, now: Instant) -> bool {
+        match self.responses.decode(frame) {
+            Some(Ok(received)) => self.handle_response(&received.msg, now),
+            _ => false,
+        }
+    }
+
+    /// Returns the next alert that should be announced. Alerts are
+    /// announced when they change and every [`ANNOUNCE_INTERVAL`] until they
+    /// return to normal, after which they are announced once more and then
+    /// forgotten.
+    pub fn poll_transmit(&mut self, now: Instant) -> Option<Alert> {
+        for entry in &mut self.entries {
+            if entry.silenced_until.is_some_and(|t| now >= t) {
+                entry.silenced_until = None;
+                entry.set_state(AlertState::Active);
+            }
+        }
+
+        let i = self.entries.iter().position(|e| now >= e.next_announce)?;
+        let entry = &mut self.entries[i];
+        let alert = entry.alert();
+
+        if entry.state == AlertState::Normal {
+            self.entries.swap_remove(i);
+        } else {
+            entry.next_announce = now + ANNOUNCE_INTERVAL;
+        }
+
+        Some(alert)
+    }
+
+    /// The next instant at which [`AlertManager::poll_transmit`] should be
+    /// called.
+    #[must_use]
+    pub fn poll_timeout(&self) -> Option<Instant> {
+        self.entries
+            .iter()
+            .flat_map(|e| [Some(e.next_announce), e.silenced_until])
+            .flatten()
+            .min()
+    }
+}
+
+impl<const N: usize> Service for AlertManager<N> {
+    fn handle_frame(&mut self, frame: &NmeaFrame, _address: u8, now: Instant) {
+        AlertManager::handle_frame(self, frame, now);
+    }
+
+    fn poll_transmit(&mut self, now: Instant) -> Option<Transmit> {
+        let alert = AlertManager::poll_transmit(self, now)?;
+        let id = Id::new(PRIORITY, Alert::PGN, 0, DESTINATION_BROADCAST);
+
+        Some(Transmit::new(id, &alert))
+    }
+
+    fn poll_timeout(&self) -> Option<Instant> {
+        AlertManager::poll_timeout(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use generic_array::GenericArray;
+
+    use super::*;
+    use crate::well_known::alert::{AlertCategory, AlertResponseCommand};
+
+    const DISPLAY: DeviceName = DeviceName(0x1000_0000_0000_0042);
+
+    fn key(alert_type: AlertType) -> AlertKey {
+        AlertKey {
+            alert_type,
+            category: AlertCategory::Technical,
+            system: 5,
+            sub_system: 1,
+            id: 300,
+            source_name: DeviceName(0x2000_0001_0203_0405),
+            source_instance: 0,
+            source_index: 0,
+            occurrence: 1,
+        }
+    }
+
+    fn response(key: AlertKey, command: AlertResponseCommand) -> AlertResponse {
+        AlertResponse {
+            key,
+            acknowledge_source_name: DISPLAY,
+            command,
+        }
+    }
+
+    #[test]
+    fn announce() {
+        let mut alerts = AlertManager::<2>::new();
+        let t0 = Instant::from_millis(1000);
+        alerts.raise(key(AlertType::Alarm), 3).unwrap();
+
+        let alert = alerts.poll_transmit(t0).unwrap();
+        assert_eq!(alert.state, AlertState::Active);
+        assert_eq!(alert.priority, 3);
+        assert_eq!(alerts.poll_transmit(t0), None);
+        assert_eq!(alerts.poll_timeout(), Some(t0 + ANNOUNCE_INTERVAL));
+        assert!(alerts.poll_transmit(t0 + ANNOUNCE_INTERVAL).is_some());
+
+        // raising it again does not announce it again
+        alerts.raise(key(AlertType::Alarm), 3).unwrap();
+        assert_eq!(alerts.poll_transmit(t0 + ANNOUNCE_INTERVAL), None);
+
+        alerts.raise(key(AlertType::Warning), 5).unwrap();
+        assert_eq!(alerts.raise(key(AlertType::Caution), 5), Err(()));
+    }
+
+    #[test]
+    fn silence() {
+        let mut alerts = AlertManager::<2>::new();
+        let key = key(AlertType::Alarm);
+        alerts.raise(key.clone(), 3).unwrap();
+        alerts.poll_transmit(Instant::ZERO);
+
+        let t1 = Instant::from_millis(500);
+        assert!(alerts.handle_response(
+            &response(key.clone(), AlertResponseCommand::TemporarySilence),
+            t1
+        ));
+
+        let alert = alerts.poll_transmit(t1).unwrap();
+        assert_eq!(alert.state, AlertState::Silenced);
+        assert!(alert.status.temporary_silence);
+
+        let t2 = t1 + SILENCE_DURATION;
+        assert_eq!(alerts.poll_transmit(t2).unwrap().state, AlertState::Active);
+    }
+
+    #[test]
+    fn acknowledge_and_rectify() {
+        let mut alerts = AlertManager::<2>::new();
+        let key = key(AlertType::Alarm);
+        alerts.raise(key.clone(), 3).unwrap();
+        alerts.poll_transmit(Instant::ZERO);
+
+        assert!(alerts.handle_response(
+            &response(key.clone(), AlertResponseCommand::Acknowledge),
+            Instant::ZERO
+        ));
+
+        let alert = alerts.poll_transmit(Instant::ZERO).unwrap();
+        assert_eq!(alert.state, AlertState::Acknowledged);
+        assert!(alert.status.acknowledged);
+        assert_eq!(alert.acknowledge_source_name, Some(DISPLAY));
+
+        alerts.rectify(&key);
+        assert_eq!(
+            alerts.poll_transmit(Instant::ZERO).unwrap().state,
+            AlertState::Normal
+        );
+        assert_eq!(alerts.state(&key), None);
+        assert_eq!(alerts.poll_timeout(), None);
+    }
+
+    #[test]
+    fn rectify_unacknowledged() {
+        let mut alerts = AlertManager::<2>::new();
+        let alarm = key(AlertType::Alarm);
+        let warning = key(AlertType::Warning);
+        alerts.raise(alarm.clone(), 3).unwrap();
+        alerts.raise(warning.clone(), 5).unwrap();
+
+        alerts.rectify(&alarm);
+        alerts.rectify(&warning);
+        assert_eq!(alerts.state(&alarm), Some(AlertState::AwaitingAcknowledge));
+        assert_eq!(alerts.state(&warning), Some(AlertState::Normal));
+
+        let frame_id = Id::new(2, AlertResponse::PGN, 42, DESTINATION_BROADCAST);
+        let msg = response(alarm.clone(), AlertResponseCommand::Acknowledge);
+        let mut buf = GenericArray::<u8, <AlertResponse as Message>::EncodedLen>::default();
+        let mut changed = false;
+
+        for packet in msg.encode_to_fast_packets(&mut buf, 1) {
+            let frame = NmeaFrame::new(frame_id, heapless::Vec::from_slice(&packet.0).unwrap());
+            changed |= alerts.handle_frame(&frame, Instant::ZERO);
+        }
+
+        assert!(changed);
+        assert_eq!(alerts.state(&alarm), Some(AlertState::Normal));
+    }
+
+    #[test]
+    fn raise_after_rectify() {
+        let mut alerts = AlertManager::<2>::new();
+        let key = key(AlertType::Warning);
+        alerts.raise(key.clone(), 5).unwrap();
+        alerts.poll_transmit(Instant::ZERO);
+
+        let t1 = Instant::from_millis(200);
+        alerts.rectify(&key);
+        alerts.raise(key.clone(), 5).unwrap();
+
+        // the condition came back before normal was announced
+        let alert = alerts.poll_transmit(t1).unwrap();
+        assert_eq!(alert.state, AlertState::Active);
+        assert_eq!(alerts.state(&key), Some(AlertState::Active));
+        assert_eq!(alerts.poll_timeout(), Some(t1 + ANNOUNCE_INTERVAL));
+    }
+
+    #[test]
+    fn service() {
+        let mut alerts = AlertManager::<2>::new();
+        alerts.raise(key(AlertType::Alarm), 3).unwrap();
+
+        let transmit = Service::poll_transmit(&mut alerts, Instant::ZERO).unwrap();
+        assert_eq!(transmit.id.pgn(), Alert::PGN);
+        assert_eq!(transmit.id.destination(), DESTINATION_BROADCAST);
+        assert!(transmit.fast_packet);
+
+        let alert = Alert::decode(&transmit.data).unwrap();
+        assert_eq!(alert.key, key(AlertType::Alarm));
+        assert_eq!(alert.state, AlertState::Active);
+        assert_eq!(
+            Service::poll_timeout(&alerts),
+            Some(Instant::ZERO + ANNOUNCE_INTERVAL)
+        );
+    }
+}
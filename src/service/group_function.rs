@@ -0,0 +1,369 @@
+//! Answering Command Group Functions (PGN 126208) for the PGNs that this
+//! device sends, for example to change the instance of a sensor.
+//!
+//! The application describes its PGNs and applies the new field values by
+//! implementing [`FieldHandler`]. [`CommandService`] owns the handler and
+//! can be run by the embassy and tokio event loops, which send the
+//! acknowledgements back to the commanding device. With
+//! [`blocking::Client`](crate::blocking::Client), received frames are
+//! passed to [`CommandHandler::handle_frame`] together with the handler,
+//! and the returned acknowledgement is sent with
+//! [`Client::send_fast_packet`](crate::blocking::Client::send_fast_packet).
+
+use super::{Service, Transmit};
+use crate::{
+    id::DESTINATION_BROADCAST,
+    time::Instant,
+    well_known::group_function::{
+        Acknowledge, Command, GroupFunction, ParameterErrorCode, PgnErrorCode,
+        TransmissionIntervalErrorCode, MAX_PARAMETERS,
+    },
+    Decoder, Id, Message, NmeaFrame,
+};
+
+/// The PGNs of a device whose fields can be changed by other devices.
+pub trait FieldHandler {
+    /// Whether the device sends `pgn`.
+    fn supports(&self, pgn: u32) -> bool;
+
+    /// The encoded length of a field of `pgn` in bytes, or [`None`] if the
+    /// PGN has no such field. Fields are numbered from 1.
+    fn field_len(&self, pgn: u32, field: u8) -> Option<usize>;
+
+    /// Change a field of `pgn` to the encoded `value`.
+    fn write_field(&mut self, pgn: u32, field: u8, value: &[u8]) -> Result<(), ParameterErrorCode>;
+
+    /// Change the priority `pgn` is sent with. Not supported by default.
+    fn set_priority(
+        &mut self,
+        pgn: u32,
+        priority: u8,
+    ) -> Result<(), TransmissionIntervalErrorCode> {
+        let _ = (pgn, priority);
+        Err(TransmissionIntervalErrorCode::NotSupported)
+    }
+}
+
+/// Applies Command Group Functions addressed to this device and
+/// acknowledges them. Up to `SOURCES` devices can be sending commands
+/// concurrently.
+#[derive(Default)]
+pub struct CommandHandler<const SOURCES: usize> {
+    decoder: Decoder<GroupFunction, SOURCES>,
+}
+
+impl<const SOURCES: usize> core::fmt::Debug for CommandHandler<SOURCES> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("CommandHandler").finish_non_exhaustive()
+    }
+}
+
+impl<const SOURCES: usize> CommandHandler<SOURCES> {
+    pub const fn new() -> Self {
+        Self {
+            decoder: Decoder::new(),
+        }
+    }
+
+    /// Apply a command, returning the acknowledgement to send back.
+    ///
+    /// Parameters are applied one by one. If a field is unknown, the rest
+    /// of the parameters cannot be read and are rejected together with it.
+    pub fn handle_command<H: FieldHandler>(command: &Command, handler: &mut H) -> Acknowledge {
+        let mut ack = Acknowledge {
+            pgn: command.pgn,
+            pgn_error: PgnErrorCode::Acknowledge,
+            interval_error: TransmissionIntervalErrorCode::Acknowledge,
+            parameter_errors: heapless::Vec::new(),
+        };
+        let count = command
+            .parameters
+            .len()
+            .min(ack.parameter_errors.capacity());
+
+        if !handler.supports(command.pgn) {
+            ack.pgn_error = PgnErrorCode::PgnNotSupported;
+            ack.interval_error = TransmissionIntervalErrorCode::NotSupported;
+            ack.parameter_errors
+                .resize(count, ParameterErrorCode::NotSupported)
+                .unwrap();
+            return ack;
+        }
+
+        if let Some(priority) = command.priority {
+            if let Err(err) = handler.set_priority(command.pgn, priority) {
+                ack.interval_error = err;
+            }
+        }
+
+        // read all values before applying any, since the field lengths are
+        // needed to split them
+        let values: heapless::Vec<_, MAX_PARAMETERS> = command
+            .parameters
+            .iter(|field| handler.field_len(command.pgn, field))
+            .take(count)
+            .map_while(Result::ok)
+            .collect();
+
+        for (field, value) in &values {
+            let code = match handler.write_field(command.pgn, *field, value) {
+                Ok(()) => ParameterErrorCode::Acknowledge,
+                Err(err) => err,
+            };
+            ack.parameter_errors.push(code).unwrap();
+        }
+
+        ack.parameter_errors
+            .resize(count, ParameterErrorCode::InvalidParameterField)
+            .unwrap();
+
+        ack
+    }
+
+    /// Process a frame received from the bus. If it completes a command
+    /// addressed to `address`, the command is applied and the destination
+    /// and acknowledgement to send back are returned.
+    pub fn handle_frame<H: FieldHandler>(
+        &mut self,
+        frame: &NmeaFrame,
+        address: u8,
+        handler: &mut H,
+    ) -> Option<(u8, GroupFunction)> {
+        let destination = frame.id.destination();
+
+        if frame.meta.local || (destination != address && destination != DESTINATION_BROADCAST) {
+            return None;
+        }
+
+        let received = self.decoder.decode(frame)?.ok()?;
+
+        let GroupFunction::Command(command) = &received.msg else {
+            return None;
+        };
+
+        let ack = Self::handle_command(command, handler);
+
+        Some((received.id.source(), GroupFunction::Acknowledge(ack)))
+    }
+}
+
+/// A [`CommandHandler`] together with the [`FieldHandler`] it applies the
+/// commands to, for running it as a [`Service`].
+pub struct CommandService<H, const SOURCES: usize> {
+    commands: CommandHandler<SOURCES>,
+    handler: H,
+    /// The destination and acknowledgement of the last command.
+    ack: Option<(u8, GroupFunction)>,
+}
+
+impl<H, const SOURCES: usize> core::fmt::Debug for CommandService<H, SOURCES> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("CommandService").finish_non_exhaustive()
+    }
+}
+
+impl<H: FieldHandler, const SOURCES: usize> CommandService<H, SOURCES> {
+    pub const fn new(handler: H) -> Self {
+        Self {
+            commands: CommandHandler::new(),
+            handler,
+            ack: None,
+        }
+    }
+
+    pub fn handler(&self) -> &H {
+        &self.handler
+    }
+
+    pub fn handler_mut(&mut self) -> &mut H {
+        &mut self.handler
+    }
+}
+
+impl<H: FieldHandler, const SOURCES: usize> Service for CommandService<H, SOURCES> {
+    fn handle_frame(&mut self, frame: &NmeaFrame, address: u8, _now: Instant) {
+        if let Some(ack) = self
+            .commands
+            .handle_frame(frame, address, &mut self.handler)
+        {
+            self.ack = Some(ack);
+        }
+    }
+
+    fn poll_transmit(&mut self, _now: Instant) -> Option<Transmit> {
+        let (destination, ack) = self.ack.take()?;
+        let id = Id::new(3, GroupFunction::PGN, 0, destination);
+
+        Some(Transmit::new(id, &ack))
+    }
+
+    fn poll_timeout(&self) -> Option<Instant> {
+        // acknowledgements are sent right after the command is received
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fast_packet::FastPacket, well_known::group_function::Parameters};
+
+    const INSTANCE_FIELD: u8 = 1;
+
+    /// A switch bank whose instance can be changed.
+    struct Device {
+        instance: u8,
+    }
+
+    impl FieldHandler for Device {
+        fn supports(&self, pgn: u32) -> bool {
+            pgn == 127501
+        }
+
+        fn field_len(&self, _pgn: u32, field: u8) -> Option<usize> {
+            match field {
+                INSTANCE_FIELD => Some(1),
+                2..=29 => Some(1),
+                _ => None,
+            }
+        }
+
+        fn write_field(
+            &mut self,
+            _pgn: u32,
+            field: u8,
+            value: &[u8],
+        ) -> Result<(), ParameterErrorCode> {
+            match (field, value) {
+                (INSTANCE_FIELD, &[0xff]) => Err(ParameterErrorCode::OutOfRange),
+                (INSTANCE_FIELD, &[instance]) => {
+                    self.instance = instance;
+                    Ok(())
+                }
+                _ => Err(ParameterErrorCode::ReadOrWriteNotSupported),
+            }
+        }
+    }
+
+    fn command(pgn: u32, parameters: &[(u8, &[u8])]) -> Command {
+        let mut command = Command {
+            pgn,
+            priority: None,
+            parameters: Parameters::new(),
+        };
+        for (field, value) in parameters {
+            command.parameters.push(*field, value).unwrap();
+        }
+        command
+    }
+
+    fn frames(msg: &GroupFunction, src: u8, dest: u8) -> impl Iterator<Item = NmeaFrame> {
+        let id = Id::new(3, GroupFunction::PGN, src, dest);
+        let mut buf = [0; 223];
+        let packets: heapless::Vec<FastPacket, 32> =
+            msg.encode_to_fast_packets(&mut buf, 0).collect();
+
+        packets
+            .into_iter()
+            .map(move |packet| NmeaFrame::new(id, heapless::Vec::from_slice(&packet.0).unwrap()))
+    }
+
+    #[test]
+    fn set_instance() {
+        let mut handler = CommandHandler::<2>::new();
+        let mut device = Device { instance: 0 };
+        let msg = GroupFunction::Command(command(127501, &[(INSTANCE_FIELD, &[3])]));
+
+        let mut reply = None;
+        for frame in frames(&msg, 10, 20) {
+            reply = handler.handle_frame(&frame, 20, &mut device);
+        }
+
+        let (dest, GroupFunction::Acknowledge(ack)) = reply.unwrap() else {
+            panic!("not an acknowledgement");
+        };
+        assert_eq!(dest, 10);
+        assert_eq!(device.instance, 3);
+        assert_eq!(ack.pgn, 127501);
+        assert_eq!(ack.pgn_error, PgnErrorCode::Acknowledge);
+        assert_eq!(
+            ack.interval_error,
+            TransmissionIntervalErrorCode::Acknowledge
+        );
+        assert_eq!(ack.parameter_errors, [ParameterErrorCode::Acknowledge]);
+
+        // commands for other devices are ignored
+        let mut reply = None;
+        for frame in frames(&msg, 10, 21) {
+            reply = handler.handle_frame(&frame, 20, &mut device);
+        }
+        assert_eq!(reply, None);
+    }
+
+    #[test]
+    fn parameter_errors() {
+        let mut device = Device { instance: 0 };
+        let mut command = command(
+            127501,
+            &[
+                (INSTANCE_FIELD, &[0xff]),
+                (2, &[0x01]),
+                (40, &[0x00]),
+                (2, &[0x01]),
+            ],
+        );
+        command.priority = Some(2);
+
+        let ack = CommandHandler::<1>::handle_command(&command, &mut device);
+
+        assert_eq!(ack.pgn_error, PgnErrorCode::Acknowledge);
+        assert_eq!(
+            ack.interval_error,
+            TransmissionIntervalErrorCode::NotSupported
+        );
+        assert_eq!(
+            ack.parameter_errors,
+            [
+                ParameterErrorCode::OutOfRange,
+                ParameterErrorCode::ReadOrWriteNotSupported,
+                ParameterErrorCode::InvalidParameterField,
+                ParameterErrorCode::InvalidParameterField,
+            ]
+        );
+        assert_eq!(device.instance, 0);
+    }
+
+    #[test]
+    fn unsupported_pgn() {
+        let mut device = Device { instance: 0 };
+        let command = command(127502, &[(INSTANCE_FIELD, &[3])]);
+
+        let ack = CommandHandler::<1>::handle_command(&command, &mut device);
+
+        assert_eq!(ack.pgn_error, PgnErrorCode::PgnNotSupported);
+        assert_eq!(ack.parameter_errors, [ParameterErrorCode::NotSupported]);
+        assert_eq!(device.instance, 0);
+    }
+
+    #[test]
+    fn service() {
+        let mut service = CommandService::<_, 2>::new(Device { instance: 0 });
+        let msg = GroupFunction::Command(command(127501, &[(INSTANCE_FIELD, &[3])]));
+
+        for frame in frames(&msg, 10, 20) {
+            assert_eq!(service.poll_transmit(Instant::ZERO), None);
+            service.handle_frame(&frame, 20, Instant::ZERO);
+        }
+
+        let transmit = service.poll_transmit(Instant::ZERO).unwrap();
+        assert_eq!(transmit.id.pgn(), GroupFunction::PGN);
+        assert_eq!(transmit.id.destination(), 10);
+        assert!(transmit.fast_packet);
+        assert!(matches!(
+            GroupFunction::decode(&transmit.data),
+            Ok(GroupFunction::Acknowledge(_))
+        ));
+        assert_eq!(service.handler().instance, 3);
+        assert_eq!(service.poll_transmit(Instant::ZERO), None);
+    }
+}
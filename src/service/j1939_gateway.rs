@@ -0,0 +1,404 @@
+//! A gateway that makes J1939 engines visible on NMEA 2000.
+//!
+//! Engine controllers speak J1939 on a bus of their own, so the frames
+//! received there are passed to [`EngineGateway::handle_frame`] rather than
+//! through an NMEA 2000 event loop. The [`EngineUpdate`]s returned by
+//! [`EngineGateway::poll_transmit`] are sent on the NMEA 2000 bus, the rapid
+//! updates as single frames and the dynamic parameters as fast packets, e.g.
+//! with
+//! [`blocking::Client::send_fast_packet`](crate::blocking::Client::send_fast_packet).
+//!
+//! The warning lamps of DM1 are mapped to the NMEA 2000 status flags: the
+//! malfunction indicator lamp to [`EngineStatus1::check_engine`], the amber
+//! warning lamp to [`EngineStatus2::warning_level_1`] and the red stop lamp
+//! to [`EngineStatus2::warning_level_2`]. Active trouble codes for the
+//! parameters that have a status flag of their own, such as the oil
+//! pressure, set that flag too.
+
+use core::time::Duration;
+
+use crate::{
+    j1939::{
+        transport::Reassembler, ActiveDiagnosticTroubleCodes, ElectronicEngineController1,
+        EngineFluidLevelPressure1, EngineHours, EngineTemperature1, FuelEconomy,
+        ENGINE_COOLANT_LEVEL, ENGINE_COOLANT_TEMPERATURE, ENGINE_FUEL_DELIVERY_PRESSURE,
+        ENGINE_OIL_LEVEL, ENGINE_OIL_PRESSURE, ENGINE_OIL_TEMPERATURE,
+    },
+    time::Instant,
+    well_known::engine::{
+        EngineInstance, EngineParametersDynamic, EngineParametersRapidUpdate, EngineStatus1,
+        EngineStatus2,
+    },
+    Message, NmeaFrame,
+};
+
+/// How often [`EngineParametersRapidUpdate`] is sent.
+pub const RAPID_UPDATE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How often [`EngineParametersDynamic`] is sent.
+pub const DYNAMIC_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How long an engine may be silent before the gateway stops sending its
+/// parameters.
+pub const ENGINE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// The longest DM1 that is reassembled.
+const MAX_DM1_LEN: usize = 130;
+
+/// A message to send on the NMEA 2000 bus.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum EngineUpdate {
+    Rapid(EngineParametersRapidUpdate),
+    Dynamic(EngineParametersDynamic),
+}
+
+#[derive(Debug, Clone)]
+struct Engine {
+    address: u8,
+    rapid: EngineParametersRapidUpdate,
+    dynamic: EngineParametersDynamic,
+    last_seen: Option<Instant>,
+    next_rapid: Instant,
+    next_dynamic: Instant,
+}
+
+impl Engine {
+    fn new(address: u8, instance: EngineInstance) -> Self {
+        Self {
+            address,
+            rapid: EngineParametersRapidUpdate {
+                instance,
+                speed: None,
+                boost_pressure: None,
+                tilt_trim: None,
+            },
+            dynamic: EngineParametersDynamic {
+                instance,
+                oil_pressure: None,
+                oil_temperature: None,
+                temperature: None,
+                alternator_potential: None,
+                fuel_rate: None,
+                total_engine_hours: None,
+                coolant_pressure: None,
+                fuel_pressure: None,
+                discrete_status_1: EngineStatus1::default(),
+                discrete_status_2: EngineStatus2::default(),
+                engine_load: None,
+                engine_torque: None,
+            },
+            last_seen: None,
+            next_rapid: Instant::ZERO,
+            next_dynamic: Instant::ZERO,
+        }
+    }
+
+    fn is_alive(&self, now: Instant) -> bool {
+        self.last_seen
+            .is_some_and(|t| now.saturating_duration_since(t) < ENGINE_TIMEOUT)
+    }
+
+    /// Apply a J1939 message. Returns false if the PGN is not supported.
+    fn update(&mut self, pgn: u32, data: &[u8]) -> bool {
+        match pgn {
+            ElectronicEngineController1::PGN => {
+                let Ok(msg) = ElectronicEngineController1::decode(data) else {
+                    return false;
+                };
+
+                self.rapid.speed = msg.speed;
+                self.dynamic.engine_torque = msg.actual_torque;
+            }
+            EngineTemperature1::PGN => {
+                let Ok(msg) = EngineTemperature1::decode(data) else {
+                    return false;
+                };
+
+                self.dynamic.temperature = msg.coolant_temperature;
+                self.dynamic.oil_temperature = msg.oil_temperature;
+            }
+            EngineFluidLevelPressure1::PGN => {
+                let Ok(msg) = EngineFluidLevelPressure1::decode(data) else {
+                    return false;
+                };
+
+                self.dynamic.oil_pressure = msg.oil_pressure;
+                self.dynamic.coolant_pressure = msg.coolant_pressure;
+                self.dynamic.fuel_pressure = msg.fuel_delivery_pressure;
+            }
+            FuelEconomy::PGN => {
+                let Ok(msg) = FuelEconomy::decode(data) else {
+                    return false;
+                };
+
+                self.dynamic.fuel_rate = msg.fuel_rate;
+            }
+            EngineHours::PGN => {
+                let Ok(msg) = EngineHours::decode(data) else {
+                    return false;
+                };
+
+                self.dynamic.total_engine_hours = msg.total_hours;
+            }
+            ActiveDiagnosticTroubleCodes::PGN => {
+                let Ok(msg) = ActiveDiagnosticTroubleCodes::decode(data) else {
+                    return false;
+                };
+
+                self.apply_diagnostics(&msg);
+            }
+            _ => return false,
+        }
+
+        true
+    }
+
+    fn apply_diagnostics(&mut self, dm1: &ActiveDiagnosticTroubleCodes) {
+        let active = |spn: u32| dm1.dtcs.iter().any(|dtc| dtc.spn == spn);
+
+        let status1 = &mut self.dynamic.discrete_status_1;
+        status1.check_engine = dm1.lamps.malfunction_indicator.unwrap_or(false);
+        status1.over_temperature =
+            active(ENGINE_COOLANT_TEMPERATURE.number) || active(ENGINE_OIL_TEMPERATURE.number);
+        status1.low_oil_pressure = active(ENGINE_OIL_PRESSURE.number);
+        status1.low_oil_level = active(ENGINE_OIL_LEVEL.number);
+        status1.low_fuel_pressure = active(ENGINE_FUEL_DELIVERY_PRESSURE.number);
+        status1.low_coolant_level = active(ENGINE_COOLANT_LEVEL.number);
+
+        let status2 = &mut self.dynamic.discrete_status_2;
+        status2.warning_level_1 = dm1.lamps.amber_warning.unwrap_or(false);
+        status2.warning_level_2 = dm1.lamps.red_stop.unwrap_or(false);
+    }
+}
+
+/// Converts the parameters of up to `N` J1939 engines to NMEA 2000.
+pub struct EngineGateway<const N: usize> {
+    engines: heapless::Vec<Engine, N>,
+    dm1: Reassembler<MAX_DM1_LEN, N>,
+}
+
+impl<const N: usize> Default for EngineGateway<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> core::fmt::Debug for EngineGateway<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("EngineGateway")
+            .field("engines", &self.engines)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<const N: usize> EngineGateway<N> {
+    pub const fn new() -> Self {
+        Self {
+            engines: heapless::Vec::new(),
+            dm1: Reassembler::new(),
+        }
+    }
+
+    /// Forward the engine with J1939 source address `address` as NMEA 2000
+    /// engine `instance`. Fails if `N` engines have already been added.
+    #[allow(clippy::result_unit_err)]
+    pub fn add_engine(&mut self, address: u8, instance: u8) -> Result<(), ()> {
+        if self.engines.iter().any(|e| e.address == address) {
+            return Ok(());
+        }
+
+        self.engines
+            .push(Engine::new(address, instance.into()))
+            .map_err(|_| ())
+    }
+
+    /// Process a frame received on the engine bus. Returns true if the
+    /// parameters of an engine were updated.
+    pub fn handle_frame(&mut self, frame: &NmeaFrame, now: Instant) -> bool {
+        let Some(engine) = self
+            .engines
+            .iter_mut()
+            .find(|e| e.address == frame.id.source())
+        else {
+            return false;
+        };
+
+        if !engine.is_alive(now) {
+            // don't mix stale parameters with new ones
+            *engine = Engine::new(engine.address, engine.rapid.instance);
+        }
+
+        let updated = match self.dm1.handle_frame(frame) {
+            Some(msg) => engine.update(msg.pgn, msg.data),
+            None => engine.update(frame.id.pgn(), &frame.data),
+        };
+
+        if updated {
+            engine.last_seen = Some(now);
+        }
+
+        updated
+    }
+
+    /// Returns the next message that is due. Nothing is sent for engines
+    /// that have been silent for [`ENGINE_TIMEOUT`].
+    pub fn poll_transmit(&mut self, now: Instant) -> Option<EngineUpdate> {
+        for engine in self.engines.iter_mut().filter(|e| e.is_alive(now)) {
+            if now >= engine.next_rapid {
+                engine.next_rapid = now + RAPID_UPDATE_INTERVAL;
+                return Some(EngineUpdate::Rapid(engine.rapid.clone()));
+            }
+
+            if now >= engine.next_dynamic {
+                engine.next_dynamic = now + DYNAMIC_INTERVAL;
+                return Some(EngineUpdate::Dynamic(engine.dynamic.clone()));
+            }
+        }
+
+        None
+    }
+
+    /// The next instant at which [`EngineGateway::poll_transmit`] should be
+    /// called, or [`None`] if no engine is alive at `now`.
+    #[must_use]
+    pub fn poll_timeout(&self, now: Instant) -> Option<Instant> {
+        self.engines
+            .iter()
+            .filter(|e| e.is_alive(now))
+            .map(|e| e.next_rapid.min(e.next_dynamic))
+            .min()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use generic_array::{typenum, GenericArray};
+
+    use super::*;
+    use crate::{
+        id::DESTINATION_BROADCAST,
+        j1939::{transport::bam, Dtc, Lamps},
+        Id,
+    };
+
+    const ENGINE: u8 = 0x00;
+
+    fn frame<T: Message<EncodedLen = typenum::U8>>(msg: &T) -> NmeaFrame {
+        NmeaFrame::from_message(Id::new(3, T::PGN, ENGINE, DESTINATION_BROADCAST), msg)
+    }
+
+    fn eec1(speed: f32) -> NmeaFrame {
+        frame(&ElectronicEngineController1 {
+            torque_mode: 0.into(),
+            drivers_demand_torque: None,
+            actual_torque: Some(40.0),
+            speed: Some(speed),
+            controlling_device: None,
+            demand_torque: None,
+        })
+    }
+
+    #[test]
+    fn convert() {
+        let mut gateway = EngineGateway::<2>::new();
+        gateway.add_engine(ENGINE, 1).unwrap();
+
+        let t0 = Instant::from_millis(1000);
+        assert_eq!(gateway.poll_transmit(t0), None);
+        assert!(gateway.handle_frame(&eec1(1800.0), t0));
+        assert!(gateway.handle_frame(
+            &frame(&EngineTemperature1 {
+                coolant_temperature: Some(358.15),
+                fuel_temperature: None,
+                oil_temperature: None,
+                turbo_oil_temperature: None,
+                intercooler_temperature: None,
+                intercooler_thermostat_opening: None,
+            }),
+            t0
+        ));
+
+        // other devices are ignored
+        let mut other = eec1(600.0);
+        other.id.set_source(0x03);
+        assert!(!gateway.handle_frame(&other, t0));
+
+        let Some(EngineUpdate::Rapid(rapid)) = gateway.poll_transmit(t0) else {
+            panic!();
+        };
+        assert_eq!(rapid.instance, EngineInstance::DualEngineStarboard);
+        assert_eq!(rapid.speed, Some(1800.0));
+
+        let Some(EngineUpdate::Dynamic(dynamic)) = gateway.poll_transmit(t0) else {
+            panic!();
+        };
+        assert_eq!(dynamic.engine_torque, Some(40.0));
+        assert!((dynamic.temperature.unwrap() - 358.15).abs() < 1e-3);
+
+        assert_eq!(gateway.poll_transmit(t0), None);
+        assert_eq!(gateway.poll_timeout(t0), Some(t0 + RAPID_UPDATE_INTERVAL));
+
+        // the engine goes silent
+        let t1 = t0 + ENGINE_TIMEOUT;
+        assert_eq!(gateway.poll_transmit(t1), None);
+        assert_eq!(gateway.poll_timeout(t1), None);
+    }
+
+    #[test]
+    fn diagnostics_over_bam() {
+        let mut gateway = EngineGateway::<1>::new();
+        gateway.add_engine(ENGINE, 0).unwrap();
+
+        let t0 = Instant::ZERO;
+        let dm1 = ActiveDiagnosticTroubleCodes {
+            lamps: Lamps {
+                malfunction_indicator: Some(false),
+                red_stop: Some(false),
+                amber_warning: Some(true),
+                protect: Some(false),
+            },
+            dtcs: heapless::Vec::from_slice(&[
+                Dtc {
+                    spn: ENGINE_OIL_PRESSURE.number,
+                    fmi: 1,
+                    occurrence_count: Some(1),
+                },
+                Dtc {
+                    spn: ENGINE_COOLANT_LEVEL.number,
+                    fmi: 1,
+                    occurrence_count: Some(2),
+                },
+            ])
+            .unwrap(),
+        };
+
+        let mut buf =
+            GenericArray::<u8, <ActiveDiagnosticTroubleCodes as Message>::EncodedLen>::default();
+        dm1.encode(&mut buf);
+
+        let mut updated = false;
+        for frame in bam(
+            ActiveDiagnosticTroubleCodes::PGN,
+            ENGINE,
+            &buf[..dm1.encoded_len()],
+        )
+        .unwrap()
+        {
+            updated = gateway.handle_frame(&frame, t0);
+        }
+        assert!(updated);
+
+        gateway.poll_transmit(t0);
+        let Some(EngineUpdate::Dynamic(dynamic)) = gateway.poll_transmit(t0) else {
+            panic!();
+        };
+
+        assert!(!dynamic.discrete_status_1.check_engine);
+        assert!(dynamic.discrete_status_1.low_oil_pressure);
+        assert!(dynamic.discrete_status_1.low_coolant_level);
+        assert!(!dynamic.discrete_status_1.over_temperature);
+        assert!(dynamic.discrete_status_2.warning_level_1);
+        assert!(!dynamic.discrete_status_2.warning_level_2);
+    }
+}
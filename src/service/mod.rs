@@ -0,0 +1,93 @@
+//! Device functions built on top of a [`Node`](crate::node::Node).
+//!
+//! The services are sans-IO state machines that do not depend on a
+//! particular driver: they are passed received frames and the current
+//! [`Instant`], and return the frames to send. The embassy and tokio event
+//! loops run the services that implement [`Service`] alongside the node,
+//! while the application keeps access to them, e.g. to read their state.
+//! With [`blocking::Client`](crate::blocking::Client) or any other driver,
+//! they are used directly.
+
+use generic_array::{typenum::Unsigned, GenericArray};
+
+use crate::{
+    fast_packet::{self, FastPacket},
+    time::Instant,
+    Id, Message, NmeaFrame,
+};
+
+pub mod alert;
+pub mod group_function;
+pub mod j1939_gateway;
+pub mod pgn_list;
+pub mod switch_bank;
+pub mod time_sync;
+
+/// A message returned by [`Service::poll_transmit`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transmit {
+    /// The source address is filled in by the client.
+    pub id: Id,
+    pub data: heapless::Vec<u8, { fast_packet::MAX_LEN }>,
+    /// Whether the message is sent as a fast packet. This is the case for
+    /// message types that do not always fit in a single frame.
+    pub fast_packet: bool,
+}
+
+impl Transmit {
+    /// Encode `msg`, to be sent as a fast packet if `T` is longer than 8
+    /// bytes.
+    pub fn new<T: Message>(id: Id, msg: &T) -> Self {
+        let mut buf = GenericArray::<u8, T::EncodedLen>::default();
+        msg.encode(&mut buf);
+
+        Self {
+            id,
+            data: heapless::Vec::from_slice(&buf[..msg.encoded_len()]).unwrap(),
+            fast_packet: T::EncodedLen::USIZE > 8,
+        }
+    }
+
+    /// The frames to send, using `group_no` for fast packets.
+    pub fn frames(&self, group_no: u8) -> impl Iterator<Item = NmeaFrame> + '_ {
+        let single = (!self.fast_packet)
+            .then(|| NmeaFrame::new(self.id, heapless::Vec::from_slice(&self.data).unwrap()));
+        let packets = self
+            .fast_packet
+            .then(|| fast_packet::Iter::new(&self.data, group_no))
+            .into_iter()
+            .flatten()
+            .map(|FastPacket(data)| {
+                NmeaFrame::new(self.id, heapless::Vec::from_slice(&data).unwrap())
+            });
+
+        single.into_iter().chain(packets)
+    }
+}
+
+impl From<NmeaFrame> for Transmit {
+    fn from(frame: NmeaFrame) -> Self {
+        Self {
+            id: frame.id,
+            data: heapless::Vec::from_slice(&frame.data).unwrap(),
+            fast_packet: false,
+        }
+    }
+}
+
+/// A service that an event loop can run alongside the node. The event loop
+/// passes it every received frame, sends the messages returned by
+/// [`Service::poll_transmit`] once the address claim has finished, and
+/// wakes up no later than [`Service::poll_timeout`].
+pub trait Service {
+    /// Process a frame received from the bus. `address` is the current
+    /// address of the node.
+    fn handle_frame(&mut self, frame: &NmeaFrame, address: u8, now: Instant);
+
+    /// The next message to send, if any.
+    fn poll_transmit(&mut self, now: Instant) -> Option<Transmit>;
+
+    /// The next instant at which [`Service::poll_transmit`] should be
+    /// called, if any.
+    fn poll_timeout(&self) -> Option<Instant>;
+}
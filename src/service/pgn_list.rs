@@ -0,0 +1,332 @@
+//! Answering requests for the PGNs this device sends and receives.
+//!
+//! The application registers the message types it sends and subscribes to
+//! with [`PgnLists`], which builds the transmit and receive [`PgnList`]s
+//! from them. Requests for PGN 126464, either as an ISO Request or as a
+//! Group Function Request, are answered with the lists.
+//!
+//! The embassy and tokio event loops have [`PgnLists`] built in and
+//! register the PGNs they send themselves. With
+//! [`blocking::Client`](crate::blocking::Client), the application passes
+//! received frames to [`PgnLists::handle_frame`] and sends the lists
+//! returned by [`PgnLists::poll_transmit`] with
+//! [`Client::send_fast_packet`](crate::blocking::Client::send_fast_packet).
+
+use heapless::Deque;
+
+use super::{Service, Transmit};
+use crate::{
+    id::DESTINATION_BROADCAST,
+    time::Instant,
+    well_known::{
+        group_function::GroupFunction,
+        pgn_list::{PgnList, PgnListFunction, MAX_PGN_LIST_LEN},
+        IsoAddressClaim, IsoRequest,
+    },
+    Decoder, Id, Message, NmeaFrame,
+};
+
+/// The number of devices that can send Group Function Requests
+/// concurrently.
+const REQUEST_SOURCES: usize = 4;
+
+/// The number of lists waiting for [`PgnLists::poll_transmit`]. If
+/// requests come in faster than the lists are sent, the oldest replies are
+/// dropped.
+const REPLY_QUEUE_LEN: usize = 4;
+
+/// The field of PGN 126464 holding the [`PgnListFunction`].
+const FUNCTION_FIELD: u8 = 1;
+
+/// The PGNs of `N` registered message types per direction, in addition to
+/// the network management PGNs that every device sends and receives.
+pub struct PgnLists<const N: usize> {
+    transmit: heapless::Vec<u32, N>,
+    receive: heapless::Vec<u32, N>,
+    requests: Decoder<GroupFunction, REQUEST_SOURCES>,
+    replies: Deque<(u8, PgnListFunction), REPLY_QUEUE_LEN>,
+}
+
+impl<const N: usize> Default for PgnLists<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> core::fmt::Debug for PgnLists<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("PgnLists")
+            .field("transmit", &self.transmit)
+            .field("receive", &self.receive)
+            .field("replies", &self.replies)
+            .finish()
+    }
+}
+
+/// The PGN of ISO Acknowledgement.
+const ISO_ACKNOWLEDGEMENT_PGN: u32 = 59392;
+
+/// Sent by the node and by [`PgnLists`] itself. NMEA 2000 also requires
+/// every device to list ISO Acknowledgement and Group Function, with which
+/// it answers requests and commands.
+const NETWORK_TRANSMIT: [u32; 4] = [
+    ISO_ACKNOWLEDGEMENT_PGN,
+    IsoAddressClaim::PGN,
+    GroupFunction::PGN,
+    PgnList::PGN,
+];
+
+/// Handled by the node and by [`PgnLists`] itself.
+const NETWORK_RECEIVE: [u32; 3] = [IsoRequest::PGN, IsoAddressClaim::PGN, GroupFunction::PGN];
+
+impl<const N: usize> PgnLists<N> {
+    pub const fn new() -> Self {
+        Self {
+            transmit: heapless::Vec::new(),
+            receive: heapless::Vec::new(),
+            requests: Decoder::new(),
+            replies: Deque::new(),
+        }
+    }
+
+    /// Register a PGN for one of the lists. Fails if `N` PGNs have
+    /// already been registered for that list.
+    pub(crate) fn register(&mut self, function: PgnListFunction, pgn: u32) -> Result<(), ()> {
+        let pgns = match function {
+            PgnListFunction::Transmit => &mut self.transmit,
+            PgnListFunction::Receive => &mut self.receive,
+            PgnListFunction::Unknown(_) => return Ok(()),
+        };
+
+        if pgns.contains(&pgn) {
+            return Ok(());
+        }
+
+        pgns.push(pgn).map_err(|_| ())
+    }
+
+    /// Register a message type that the application sends. Fails if `N`
+    /// types have already been registered.
+    #[allow(clippy::result_unit_err)]
+    pub fn register_transmit<T: Message>(&mut self) -> Result<(), ()> {
+        self.register(PgnListFunction::Transmit, T::PGN)
+    }
+
+    /// Register a message type that the application subscribes to. Fails
+    /// if `N` types have already been registered.
+    #[allow(clippy::result_unit_err)]
+    pub fn register_receive<T: Message>(&mut self) -> Result<(), ()> {
+        self.register(PgnListFunction::Receive, T::PGN)
+    }
+
+    /// The transmit or receive list, with network management PGNs first.
+    /// Registered PGNs that do not fit in a single message are left out.
+    /// Lists with an unknown function are empty.
+    #[must_use]
+    pub fn list(&self, function: PgnListFunction) -> PgnList {
+        let mut pgns = heapless::Vec::<u32, MAX_PGN_LIST_LEN>::new();
+
+        let (network, registered) = match function {
+            PgnListFunction::Transmit => (&NETWORK_TRANSMIT[..], &self.transmit),
+            PgnListFunction::Receive => (&NETWORK_RECEIVE[..], &self.receive),
+            PgnListFunction::Unknown(_) => return PgnList { function, pgns },
+        };
+
+        for &pgn in network.iter().chain(registered.iter()) {
+            if !pgns.contains(&pgn) && pgns.push(pgn).is_err() {
+                break;
+            }
+        }
+
+        PgnList { function, pgns }
+    }
+
+    fn reply(&mut self, dest: u8, function: PgnListFunction) {
+        if self.replies.is_full() {
+            self.replies.pop_front();
+        }
+
+        // cannot fail since we just made room
+        let _ = self.replies.push_back((dest, function));
+    }
+
+    /// Process a frame received from the bus. Returns true if it was a
+    /// request for our PGN lists addressed to `address` or broadcast.
+    pub fn handle_frame(&mut self, frame: &NmeaFrame, address: u8) -> bool {
+        let destination = frame.id.destination();
+
+        if frame.meta.local || (destination != address && destination != DESTINATION_BROADCAST) {
+            return false;
+        }
+
+        // replies to broadcast requests are broadcast as well
+        let reply_to = if destination == DESTINATION_BROADCAST {
+            DESTINATION_BROADCAST
+        } else {
+            frame.id.source()
+        };
+
+        let selected = if frame.id.pgn() == IsoRequest::PGN {
+            match IsoRequest::decode(&frame.data) {
+                Ok(request) if request.pgn == PgnList::PGN => None,
+                _ => return false,
+            }
+        } else {
+            let Some(Ok(received)) = self.requests.decode(frame) else {
+                return false;
+            };
+            let GroupFunction::Request(request) = received.msg else {
+                return false;
+            };
+
+            if request.pgn != PgnList::PGN {
+                return false;
+            }
+
+            // the request may select one of the lists
+            request
+                .parameters
+                .iter(|field| (field == FUNCTION_FIELD).then_some(1))
+                .find_map(|parameter| match parameter {
+                    Ok((FUNCTION_FIELD, &[function @ 0..=1])) => Some(function.into()),
+                    _ => None,
+                })
+        };
+
+        match selected {
+            Some(function) => self.reply(reply_to, function),
+            None => {
+                self.reply(reply_to, PgnListFunction::Transmit);
+                self.reply(reply_to, PgnListFunction::Receive);
+            }
+        }
+
+        true
+    }
+
+    /// Returns the next list to send and its destination.
+    pub fn poll_transmit(&mut self) -> Option<(u8, PgnList)> {
+        let (dest, function) = self.replies.pop_front()?;
+
+        Some((dest, self.list(function)))
+    }
+}
+
+impl<const N: usize> Service for PgnLists<N> {
+    fn handle_frame(&mut self, frame: &NmeaFrame, address: u8, _now: Instant) {
+        PgnLists::handle_frame(self, frame, address);
+    }
+
+    fn poll_transmit(&mut self, _now: Instant) -> Option<Transmit> {
+        let (dest, list) = PgnLists::poll_transmit(self)?;
+        let id = Id::new(6, PgnList::PGN, 0, dest);
+
+        Some(Transmit::new(id, &list))
+    }
+
+    fn poll_timeout(&self) -> Option<Instant> {
+        // replies are sent right after the request is received
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fast_packet::FastPacket,
+        well_known::{
+            group_function::{Parameters, Request},
+            switching::{BinarySwitchBankStatus, SwitchBankControl},
+        },
+    };
+
+    fn lists() -> PgnLists<4> {
+        let mut lists = PgnLists::new();
+        lists.register_transmit::<BinarySwitchBankStatus>().unwrap();
+        lists.register_transmit::<BinarySwitchBankStatus>().unwrap();
+        lists.register_receive::<SwitchBankControl>().unwrap();
+        lists
+    }
+
+    #[test]
+    fn build() {
+        let lists = lists();
+
+        assert_eq!(
+            lists.list(PgnListFunction::Transmit).pgns,
+            [59392, 60928, 126208, 126464, 127501]
+        );
+        assert_eq!(
+            lists.list(PgnListFunction::Receive).pgns,
+            [59904, 60928, 126208, 127502]
+        );
+    }
+
+    #[test]
+    fn unknown_function() {
+        let mut lists = lists();
+        lists
+            .register(PgnListFunction::Unknown(2), 130_306)
+            .unwrap();
+
+        assert!(lists.list(PgnListFunction::Unknown(2)).pgns.is_empty());
+        assert!(!lists
+            .list(PgnListFunction::Transmit)
+            .pgns
+            .contains(&130_306));
+    }
+
+    #[test]
+    fn iso_request() {
+        let mut lists = lists();
+        let request = |pgn, dest| {
+            NmeaFrame::from_message(Id::new(6, IsoRequest::PGN, 30, dest), &IsoRequest { pgn })
+        };
+
+        assert!(!lists.handle_frame(&request(126996, 20), 20));
+        assert!(!lists.handle_frame(&request(PgnList::PGN, 21), 20));
+        assert_eq!(lists.poll_transmit(), None);
+
+        assert!(lists.handle_frame(&request(PgnList::PGN, 20), 20));
+        let (dest, list) = lists.poll_transmit().unwrap();
+        assert_eq!(dest, 30);
+        assert_eq!(list.function, PgnListFunction::Transmit);
+        let (_, list) = lists.poll_transmit().unwrap();
+        assert_eq!(list.function, PgnListFunction::Receive);
+        assert_eq!(lists.poll_transmit(), None);
+
+        assert!(lists.handle_frame(&request(PgnList::PGN, DESTINATION_BROADCAST), 20));
+        assert_eq!(lists.poll_transmit().unwrap().0, DESTINATION_BROADCAST);
+    }
+
+    #[test]
+    fn group_function_request() {
+        let mut lists = lists();
+        let mut parameters = Parameters::new();
+        parameters
+            .push(FUNCTION_FIELD, &[PgnListFunction::Receive.into()])
+            .unwrap();
+        let msg = GroupFunction::Request(Request {
+            pgn: PgnList::PGN,
+            interval: None,
+            offset: None,
+            parameters,
+        });
+
+        let id = Id::new(3, GroupFunction::PGN, 30, 20);
+        let mut buf = [0; 223];
+        let mut handled = false;
+        for packet in msg.encode_to_fast_packets(&mut buf, 0) {
+            let FastPacket(data) = packet;
+            let frame = NmeaFrame::new(id, heapless::Vec::from_slice(&data).unwrap());
+            handled = lists.handle_frame(&frame, 20);
+        }
+
+        assert!(handled);
+        let (dest, list) = lists.poll_transmit().unwrap();
+        assert_eq!(dest, 30);
+        assert_eq!(list.function, PgnListFunction::Receive);
+        assert_eq!(lists.poll_transmit(), None);
+    }
+}
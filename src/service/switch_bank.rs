@@ -0,0 +1,274 @@
+//! A switch bank device, such as a relay module or a switch panel.
+//!
+//! [`SwitchBank`] implements [`Service`], so the embassy and tokio event
+//! loops can run it: control commands for its instance are applied as they
+//! arrive and its status is sent periodically and on change. The
+//! application shares the bank with the event loop to operate channels and
+//! to update its outputs from [`SwitchBank::channels`].
+//!
+//! ```no_run
+//! # async fn run(mut event_loop: nmea2000::client::EventLoop<'static, impl nmea2000::client::AsyncCan>) {
+//! use core::cell::RefCell;
+//!
+//! use nmea2000::{client::Shared, service::switch_bank::SwitchBank};
+//!
+//! static BANK: Shared<SwitchBank<4>> = Shared::new(RefCell::new(SwitchBank::new(0)));
+//!
+//! event_loop.add_service(&BANK).unwrap();
+//!
+//! loop {
+//!     event_loop.poll().await.ok();
+//!     let channels = BANK.lock(|bank| *bank.borrow().channels());
+//!     // drive the relays from `channels`
+//! }
+//! # }
+//! ```
+//!
+//! With [`blocking::Client`], frames returned by
+//! [`Client::poll`](crate::blocking::Client::poll) are passed to
+//! [`SwitchBank::handle_frame`] and the status frames are sent with
+//! [`Client::send`](crate::blocking::Client::send).
+//!
+//! [`blocking::Client`]: crate::blocking::Client
+
+use core::time::Duration;
+
+use super::{Service, Transmit};
+use crate::{
+    id::DESTINATION_BROADCAST,
+    time::Instant,
+    well_known::switching::{BinarySwitchBankStatus, SwitchBankControl, SWITCH_BANK_SIZE},
+    Id, Message, NmeaFrame,
+};
+
+/// How often the status is sent when nothing changes.
+pub const STATUS_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Keeps the state of the `N` channels of a switch bank, which must be at
+/// most [`SWITCH_BANK_SIZE`]. The status ([`BinarySwitchBankStatus`]) is
+/// sent periodically and as soon as a channel changes, and control
+/// commands ([`SwitchBankControl`]) addressed to the bank's instance are
+/// applied.
+#[derive(Debug, Clone)]
+pub struct SwitchBank<const N: usize> {
+    instance: u8,
+    channels: [bool; N],
+    interval: Duration,
+    /// When the next status should be sent. Changes set this to
+    /// [`Instant::ZERO`] to send the status right away.
+    next_status: Instant,
+}
+
+impl<const N: usize> SwitchBank<N> {
+    const VALID: () = assert!(N <= SWITCH_BANK_SIZE, "too many channels");
+
+    /// Create a switch bank with all channels off.
+    pub const fn new(instance: u8) -> Self {
+        let () = Self::VALID;
+
+        Self {
+            instance,
+            channels: [false; N],
+            interval: STATUS_INTERVAL,
+            next_status: Instant::ZERO,
+        }
+    }
+
+    /// Change how often the status is sent when nothing changes. Defaults
+    /// to [`STATUS_INTERVAL`].
+    #[must_use]
+    pub const fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn instance(&self) -> u8 {
+        self.instance
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn channels(&self) -> &[bool; N] {
+        &self.channels
+    }
+
+    /// Turn a channel on or off, for example when a physical switch has been
+    /// operated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is not less than `N`.
+    pub fn set(&mut self, channel: usize, on: bool) {
+        if self.channels[channel] != on {
+            self.channels[channel] = on;
+            self.next_status = Instant::ZERO;
+        }
+    }
+
+    /// The current status of the bank.
+    #[must_use]
+    pub fn status(&self) -> BinarySwitchBankStatus {
+        let mut indicators = [None; SWITCH_BANK_SIZE];
+
+        for (indicator, &on) in indicators.iter_mut().zip(&self.channels) {
+            *indicator = Some(on);
+        }
+
+        BinarySwitchBankStatus {
+            instance: self.instance,
+            indicators,
+        }
+    }
+
+    /// Apply a control command. Commands for other instances are ignored.
+    /// Returns true if any channel changed.
+    pub fn handle_control(&mut self, control: &SwitchBankControl) -> bool {
+        if control.instance != self.instance {
+            return false;
+        }
+
+        let mut changed = false;
+
+        for (channel, switch) in self.channels.iter_mut().zip(control.switches) {
+            if let Some(on) = switch {
+                changed |= *channel != on;
+                *channel = on;
+            }
+        }
+
+        if changed {
+            self.next_status = Instant::ZERO;
+        }
+
+        changed
+    }
+
+    /// Process a frame received from the bus. Returns true if any channel
+    /// changed, in which case the application should update its outputs
+    /// from [`SwitchBank::channels`].
+    pub fn handle_frame(&mut self, frame: &NmeaFrame) -> bool {
+        if frame.id.pgn() != SwitchBankControl::PGN {
+            return false;
+        }
+
+        match SwitchBankControl::decode(&frame.data) {
+            Ok(control) => self.handle_control(&control),
+            Err(()) => false,
+        }
+    }
+
+    /// Returns the status frame if it is due, either because a channel has
+    /// changed or because the interval has elapsed. The source address is
+    /// filled in by the client.
+    pub fn poll_transmit(&mut self, now: Instant) -> Option<NmeaFrame> {
+        if now < self.next_status {
+            return None;
+        }
+
+        self.next_status = now + self.interval;
+
+        let id = Id::new(3, BinarySwitchBankStatus::PGN, 0, DESTINATION_BROADCAST);
+
+        Some(NmeaFrame::from_message(id, &self.status()))
+    }
+
+    /// The instant at which the next status is due.
+    #[must_use]
+    pub fn poll_timeout(&self) -> Instant {
+        self.next_status
+    }
+}
+
+impl<const N: usize> Service for SwitchBank<N> {
+    fn handle_frame(&mut self, frame: &NmeaFrame, _address: u8, _now: Instant) {
+        SwitchBank::handle_frame(self, frame);
+    }
+
+    fn poll_transmit(&mut self, now: Instant) -> Option<Transmit> {
+        SwitchBank::poll_transmit(self, now).map(Transmit::from)
+    }
+
+    fn poll_timeout(&self) -> Option<Instant> {
+        Some(SwitchBank::poll_timeout(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn control(instance: u8, switches: &[(usize, bool)]) -> NmeaFrame {
+        let mut msg = SwitchBankControl {
+            instance,
+            switches: [None; SWITCH_BANK_SIZE],
+        };
+
+        for &(i, on) in switches {
+            msg.switches[i] = Some(on);
+        }
+
+        NmeaFrame::from_message(
+            Id::new(3, SwitchBankControl::PGN, 42, DESTINATION_BROADCAST),
+            &msg,
+        )
+    }
+
+    fn status(frame: NmeaFrame) -> BinarySwitchBankStatus {
+        BinarySwitchBankStatus::decode(&frame.data).unwrap()
+    }
+
+    #[test]
+    fn periodic_status() {
+        let mut bank = SwitchBank::<4>::new(1);
+        let t0 = Instant::from_millis(1000);
+
+        let msg = status(bank.poll_transmit(t0).unwrap());
+        assert_eq!(msg.instance, 1);
+        assert_eq!(
+            msg.indicators[..5],
+            [Some(false), Some(false), Some(false), Some(false), None]
+        );
+
+        assert_eq!(bank.poll_timeout(), t0 + STATUS_INTERVAL);
+        assert_eq!(bank.poll_transmit(Instant::from_millis(1500)), None);
+        assert!(bank.poll_transmit(t0 + STATUS_INTERVAL).is_some());
+    }
+
+    #[test]
+    fn status_on_change() {
+        let mut bank = SwitchBank::<4>::new(1);
+        let t0 = Instant::from_millis(1000);
+        bank.poll_transmit(t0);
+
+        bank.set(2, false);
+        assert_eq!(bank.poll_transmit(t0), None);
+
+        bank.set(2, true);
+        assert_eq!(bank.poll_timeout(), Instant::ZERO);
+        assert_eq!(
+            status(bank.poll_transmit(t0).unwrap()).indicators[2],
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn apply_control() {
+        let mut bank = SwitchBank::<4>::new(1);
+        bank.poll_transmit(Instant::ZERO);
+
+        // another instance
+        assert!(!bank.handle_frame(&control(2, &[(0, true)])));
+        assert_eq!(bank.channels(), &[false; 4]);
+
+        // channels outside of the bank are ignored
+        assert!(bank.handle_frame(&control(1, &[(0, true), (3, true), (10, true)])));
+        assert_eq!(bank.channels(), &[true, false, false, true]);
+        assert!(bank.poll_transmit(Instant::from_millis(1)).is_some());
+
+        // unchanged
+        assert!(!bank.handle_frame(&control(1, &[(0, true)])));
+        assert_eq!(bank.poll_transmit(Instant::from_millis(2)), None);
+    }
+}
@@ -0,0 +1,325 @@
+//! Wall-clock time for devices without a real-time clock, synchronized
+//! using System Time (PGN 126992).
+//!
+//! [`TimeSync`] picks a time source on the bus, preferring GNSS receivers
+//! over radio time signals over local clocks, and keeps the offset between
+//! UTC and the local [`Instant`]. A source is kept until a better one shows
+//! up or it has been silent for [`SOURCE_TIMEOUT`]. A device that is the
+//! master clock can instead broadcast its own time.
+//!
+//! [`TimeSync`] implements [`Service`], so the embassy and tokio event loops
+//! keep it synchronized, and broadcast the time if it is the master clock.
+//! The application shares it with the event loop and reads the time with
+//! [`TimeSync::utc`]. With [`blocking::Client`](crate::blocking::Client),
+//! received frames are passed to [`TimeSync::handle_frame`] and the frames
+//! returned by [`TimeSync::poll_transmit`] are sent with
+//! [`Client::send`](crate::blocking::Client::send).
+
+use core::time::Duration;
+
+use super::{Service, Transmit};
+use crate::{
+    id::DESTINATION_BROADCAST,
+    time::Instant,
+    well_known::system::{SystemTime, TimeSource},
+    Id, Message, NmeaFrame,
+};
+
+/// How long a time source may be silent before another one is picked.
+pub const SOURCE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How often System Time is sent by the master clock.
+pub const BROADCAST_INTERVAL: Duration = Duration::from_secs(1);
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// Lower is better.
+fn rank(source: TimeSource) -> u8 {
+    match source {
+        TimeSource::Gps | TimeSource::Glonass => 0,
+        TimeSource::RadioStation => 1,
+        TimeSource::LocalCesiumClock => 2,
+        TimeSource::LocalRubidiumClock => 3,
+        TimeSource::LocalCrystalClock => 4,
+        TimeSource::Unknown(_) => 5,
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Source {
+    address: u8,
+    kind: TimeSource,
+    last_seen: Instant,
+}
+
+/// See the [module-level documentation](self) for more information.
+#[derive(Debug, Clone, Default)]
+pub struct TimeSync {
+    /// UTC at the epoch of the local clock.
+    offset: Option<Duration>,
+    source: Option<Source>,
+    /// The kind of clock we are, if we are the master clock.
+    master: Option<TimeSource>,
+    sid: u8,
+    next_broadcast: Instant,
+}
+
+impl TimeSync {
+    /// Follow the time of the best source on the bus.
+    pub const fn new() -> Self {
+        Self {
+            offset: None,
+            source: None,
+            master: None,
+            sid: 0,
+            next_broadcast: Instant::ZERO,
+        }
+    }
+
+    /// Act as the master clock, broadcasting System Time every
+    /// [`BROADCAST_INTERVAL`] once the time has been set with
+    /// [`TimeSync::set_utc`]. System Time from other devices is ignored.
+    pub const fn master(source: TimeSource) -> Self {
+        Self {
+            master: Some(source),
+            ..Self::new()
+        }
+    }
+
+    /// Set the time since 1970-01-01 (UTC) at the local instant `now`, for
+    /// example from a GNSS receiver attached to this device.
+    pub fn set_utc(&mut self, utc: Duration, now: Instant) {
+        self.offset = utc.checked_sub(Duration::from_micros(now.as_micros()));
+    }
+
+    /// The time since 1970-01-01 (UTC) at the local instant `now`, or
+    /// [`None`] if no time has been received yet.
+    #[must_use]
+    pub fn utc(&self, now: Instant) -> Option<Duration> {
+        Some(self.offset? + Duration::from_micros(now.as_micros()))
+    }
+
+    /// The current time since 1970-01-01 (UTC) according to
+    /// [`embassy_time`], or [`None`] if no time has been received yet.
+    #[cfg(feature = "client")]
+    #[must_use]
+    pub fn now_utc(&self) -> Option<Duration> {
+        self.utc(embassy_time::Instant::now().into())
+    }
+
+    /// The address and kind of the current time source, if any.
+    #[must_use]
+    pub fn source(&self) -> Option<(u8, TimeSource)> {
+        self.source.map(|s| (s.address, s.kind))
+    }
+
+    fn prefers(&self, address: u8, kind: TimeSource, now: Instant) -> bool {
+        match self.source {
+            None => true,
+            Some(current) => {
+                current.address == address
+                    || rank(kind) < rank(current.kind)
+                    || now.saturating_duration_since(current.last_seen) >= SOURCE_TIMEOUT
+            }
+        }
+    }
+
+    /// Process a frame received from the bus. Returns true if the time was
+    /// updated.
+    pub fn handle_frame(&mut self, frame: &NmeaFrame, now: Instant) -> bool {
+        if self.master.is_some() || frame.meta.local || frame.id.pgn() != SystemTime::PGN {
+            return false;
+        }
+
+        let Ok(msg) = SystemTime::decode(&frame.data) else {
+            return false;
+        };
+        let Some(utc) = msg.utc() else {
+            return false;
+        };
+        let address = frame.id.source();
+
+        if !self.prefers(address, msg.source, now) {
+            return false;
+        }
+
+        self.source = Some(Source {
+            address,
+            kind: msg.source,
+            last_seen: now,
+        });
+        self.set_utc(utc, frame.meta.timestamp.unwrap_or(now));
+
+        true
+    }
+
+    /// Returns System Time if we are the master clock and it is due. The
+    /// source address is filled in by the client.
+    pub fn poll_transmit(&mut self, now: Instant) -> Option<NmeaFrame> {
+        let source = self.master?;
+        let utc = self.utc(now)?;
+
+        if now < self.next_broadcast {
+            return None;
+        }
+
+        self.next_broadcast = now + BROADCAST_INTERVAL;
+
+        let days = utc.as_secs() / SECONDS_PER_DAY;
+        let msg = SystemTime {
+            sid: Some(self.sid),
+            source,
+            date: u16::try_from(days).ok(),
+            time: Some(utc - Duration::from_secs(days * SECONDS_PER_DAY)),
+        };
+        // 0xff means that the sequence ID is not available
+        self.sid = (self.sid + 1) % 0xfd;
+
+        let id = Id::new(3, SystemTime::PGN, 0, DESTINATION_BROADCAST);
+
+        Some(NmeaFrame::from_message(id, &msg))
+    }
+
+    /// The next instant at which [`TimeSync::poll_transmit`] should be
+    /// called.
+    #[must_use]
+    pub fn poll_timeout(&self) -> Option<Instant> {
+        if self.master.is_some() && self.offset.is_some() {
+            Some(self.next_broadcast)
+        } else {
+            None
+        }
+    }
+}
+
+impl Service for TimeSync {
+    fn handle_frame(&mut self, frame: &NmeaFrame, _address: u8, now: Instant) {
+        TimeSync::handle_frame(self, frame, now);
+    }
+
+    fn poll_transmit(&mut self, now: Instant) -> Option<Transmit> {
+        TimeSync::poll_transmit(self, now).map(Transmit::from)
+    }
+
+    fn poll_timeout(&self) -> Option<Instant> {
+        TimeSync::poll_timeout(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Metadata;
+
+    /// 2022-01-08 12:34:56.7
+    const UTC: Duration = Duration::from_millis(19000 * 86_400_000 + 45_296_700);
+
+    fn system_time(src: u8, source: TimeSource, utc: Duration, received: Instant) -> NmeaFrame {
+        let days = utc.as_secs() / SECONDS_PER_DAY;
+        let msg = SystemTime {
+            sid: None,
+            source,
+            date: Some(days as u16),
+            time: Some(utc - Duration::from_secs(days * SECONDS_PER_DAY)),
+        };
+        let mut frame = NmeaFrame::from_message(
+            Id::new(3, SystemTime::PGN, src, DESTINATION_BROADCAST),
+            &msg,
+        );
+        frame.meta = Metadata::received(received);
+        frame
+    }
+
+    #[test]
+    fn follow_source() {
+        let mut sync = TimeSync::new();
+        let t0 = Instant::from_millis(2000);
+        assert_eq!(sync.utc(t0), None);
+
+        assert!(sync.handle_frame(&system_time(10, TimeSource::Gps, UTC, t0), t0));
+        assert_eq!(sync.source(), Some((10, TimeSource::Gps)));
+        assert_eq!(sync.utc(t0), Some(UTC));
+        assert_eq!(
+            sync.utc(Instant::from_millis(2500)),
+            Some(UTC + Duration::from_millis(500))
+        );
+        assert_eq!(sync.poll_transmit(t0), None);
+    }
+
+    #[test]
+    fn prefer_gnss() {
+        let mut sync = TimeSync::new();
+        let t0 = Instant::from_millis(2000);
+        let skewed = UTC + Duration::from_secs(3);
+
+        sync.handle_frame(
+            &system_time(20, TimeSource::LocalCrystalClock, skewed, t0),
+            t0,
+        );
+        assert!(sync.handle_frame(&system_time(10, TimeSource::Gps, UTC, t0), t0));
+
+        // worse sources are ignored while the current one is alive
+        let t1 = Instant::from_millis(3000);
+        assert!(!sync.handle_frame(
+            &system_time(20, TimeSource::LocalCrystalClock, skewed, t1),
+            t1
+        ));
+        assert_eq!(sync.source(), Some((10, TimeSource::Gps)));
+
+        // but not after it has been silent for too long
+        let t2 = t0 + SOURCE_TIMEOUT;
+        assert!(sync.handle_frame(
+            &system_time(20, TimeSource::LocalCrystalClock, skewed, t2),
+            t2
+        ));
+        assert_eq!(sync.source(), Some((20, TimeSource::LocalCrystalClock)));
+        assert_eq!(sync.utc(t2), Some(skewed));
+    }
+
+    #[test]
+    fn ignore_local() {
+        let mut sync = TimeSync::new();
+        let mut frame = system_time(10, TimeSource::Gps, UTC, Instant::ZERO);
+        frame.meta = Metadata::local(Instant::ZERO);
+
+        assert!(!sync.handle_frame(&frame, Instant::ZERO));
+    }
+
+    #[test]
+    fn master() {
+        let mut sync = TimeSync::master(TimeSource::LocalCrystalClock);
+        let t0 = Instant::from_millis(2000);
+        assert_eq!(sync.poll_transmit(t0), None);
+        assert_eq!(sync.poll_timeout(), None);
+
+        sync.set_utc(UTC, t0);
+        assert!(!sync.handle_frame(&system_time(10, TimeSource::Gps, UTC, t0), t0));
+
+        let frame = sync.poll_transmit(t0).unwrap();
+        let msg = SystemTime::decode(&frame.data).unwrap();
+        assert_eq!(msg.sid, Some(0));
+        assert_eq!(msg.source, TimeSource::LocalCrystalClock);
+        assert_eq!(msg.utc(), Some(UTC));
+
+        assert_eq!(sync.poll_timeout(), Some(t0 + BROADCAST_INTERVAL));
+        assert_eq!(sync.poll_transmit(Instant::from_millis(2500)), None);
+
+        let frame = sync.poll_transmit(t0 + BROADCAST_INTERVAL).unwrap();
+        let msg = SystemTime::decode(&frame.data).unwrap();
+        assert_eq!(msg.sid, Some(1));
+        assert_eq!(msg.utc(), Some(UTC + BROADCAST_INTERVAL));
+    }
+
+    #[test]
+    fn service() {
+        let mut sync = TimeSync::master(TimeSource::Gps);
+        let t0 = Instant::from_millis(2000);
+        sync.set_utc(UTC, t0);
+
+        let transmit = Service::poll_transmit(&mut sync, t0).unwrap();
+        assert_eq!(transmit.id.pgn(), SystemTime::PGN);
+        assert!(!transmit.fast_packet);
+        assert_eq!(SystemTime::decode(&transmit.data).unwrap().utc(), Some(UTC));
+        assert_eq!(Service::poll_timeout(&sync), Some(t0 + BROADCAST_INTERVAL));
+    }
+}
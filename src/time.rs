@@ -0,0 +1,118 @@
+//! Runtime-independent time keeping. The protocol core in [`crate::node`]
+//! does not read a clock itself; instead, the driver passes the current
+//! [`Instant`] along with every event.
+
+use core::{
+    fmt,
+    ops::{Add, AddAssign, Sub},
+    time::Duration,
+};
+
+/// A point in time on some monotonic clock, with microsecond resolution.
+/// The epoch is arbitrary (typically the boot of the device), so instants
+/// are only meaningful relative to each other.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Instant(u64);
+
+impl Instant {
+    /// The epoch of the clock.
+    pub const ZERO: Self = Self(0);
+
+    #[inline]
+    #[must_use]
+    pub const fn from_micros(micros: u64) -> Self {
+        Self(micros)
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn from_millis(millis: u64) -> Self {
+        Self(millis * 1_000)
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn as_micros(self) -> u64 {
+        self.0
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn as_millis(self) -> u64 {
+        self.0 / 1_000
+    }
+
+    /// Returns the amount of time elapsed from `earlier` to `self`, or zero
+    /// if `earlier` is later than `self`.
+    #[inline]
+    #[must_use]
+    pub const fn saturating_duration_since(self, earlier: Self) -> Duration {
+        Duration::from_micros(self.0.saturating_sub(earlier.0))
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn checked_add(self, duration: Duration) -> Option<Self> {
+        if duration.as_micros() > u64::MAX as u128 {
+            return None;
+        }
+
+        match self.0.checked_add(duration.as_micros() as u64) {
+            Some(micros) => Some(Self(micros)),
+            None => None,
+        }
+    }
+}
+
+impl Add<Duration> for Instant {
+    type Output = Self;
+
+    /// # Panics
+    ///
+    /// Panics if the result overflows.
+    fn add(self, rhs: Duration) -> Self::Output {
+        self.checked_add(rhs)
+            .expect("overflow when adding duration to instant")
+    }
+}
+
+impl AddAssign<Duration> for Instant {
+    fn add_assign(&mut self, rhs: Duration) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Instant {
+    type Output = Duration;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.saturating_duration_since(rhs)
+    }
+}
+
+impl fmt::Debug for Instant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Instant({}µs)", self.0)
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Instant {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(fmt, "Instant({=u64}µs)", self.0)
+    }
+}
+
+#[cfg(feature = "client")]
+impl From<embassy_time::Instant> for Instant {
+    fn from(instant: embassy_time::Instant) -> Self {
+        Self(instant.as_micros())
+    }
+}
+
+#[cfg(feature = "client")]
+impl From<Instant> for embassy_time::Instant {
+    fn from(instant: Instant) -> Self {
+        Self::from_micros(instant.0)
+    }
+}
@@ -0,0 +1,455 @@
+//! A [tokio](https://tokio.rs) flavored counterpart of [`crate::client`],
+//! for use on regular operating systems. Like its embassy sibling, the
+//! [`EventLoop`] drives a [`Node`] and must be polled continuously, while
+//! any number of [`Client`]s send frames and subscribe to messages.
+//!
+//! The event loop answers requests for the PGN lists of the device itself.
+//! The PGNs of sent frames are added to the transmit list, and the message
+//! types passed to [`Client::subscribe`] to the receive list. It also runs
+//! the [`Service`]s added with [`EventLoop::add_service`].
+
+use core::{future::Future, time::Duration};
+use std::{
+    sync::{Arc, Mutex, PoisonError},
+    vec::Vec,
+};
+
+use ::tokio::{
+    sync::{
+        broadcast::{self, error::RecvError},
+        mpsc::{self, error::SendError},
+    },
+    time::{sleep_until, Instant},
+};
+use generic_array::GenericArray;
+
+use crate::{
+    node::Node,
+    service::{pgn_list::PgnLists, Service, Transmit},
+    time,
+    well_known::{
+        pgn_list::{PgnListFunction, MAX_PGN_LIST_LEN},
+        DeviceName,
+    },
+    Decoder, Id, Message, Metadata, NmeaFrame, Received,
+};
+
+/// Similar to [`crate::client::AsyncCan`], but the returned futures are
+/// [`Send`] so that the event loop can be spawned on a multi-threaded
+/// runtime.
+///
+/// The futures returned by [`AsyncCan::receive`] must be cancel safe, since
+/// the event loop drops them whenever it has something else to do.
+pub trait AsyncCan: Send {
+    type Error: Send;
+
+    type Frame: embedded_can::Frame + Send;
+
+    fn send(&mut self, frame: Self::Frame) -> impl Future<Output = Result<(), Self::Error>> + Send;
+
+    fn receive(&mut self) -> impl Future<Output = Result<Self::Frame, Self::Error>> + Send;
+
+    /// The time at which a received frame was timestamped by the controller,
+    /// if it supports hardware timestamps. Otherwise, the event loop
+    /// timestamps frames when they are received.
+    ///
+    /// The controller's clock has to be mapped to tokio's [`Instant`], e.g.
+    /// by subtracting the age of the hardware timestamp from
+    /// [`Instant::now`] when the frame is read. The event loop converts it
+    /// to [`Metadata::timestamp`], which counts from the creation of the
+    /// event loop. Timestamps from before that are clamped to zero.
+    fn timestamp(_frame: &Self::Frame) -> Option<Instant> {
+        None
+    }
+}
+
+impl<T> AsyncCan for &mut T
+where
+    T: AsyncCan,
+{
+    type Error = T::Error;
+    type Frame = T::Frame;
+
+    fn send(&mut self, frame: Self::Frame) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        (*self).send(frame)
+    }
+
+    fn receive(&mut self) -> impl Future<Output = Result<Self::Frame, Self::Error>> + Send {
+        (*self).receive()
+    }
+
+    fn timestamp(frame: &Self::Frame) -> Option<Instant> {
+        T::timestamp(frame)
+    }
+}
+
+pub enum Error<C: AsyncCan> {
+    Can(C::Error),
+    Decode,
+}
+
+impl<C: AsyncCan> core::fmt::Debug for Error<C>
+where
+    C::Error: core::fmt::Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Can(e) => f.debug_tuple("Can").field(e).finish(),
+            Self::Decode => f.write_str("Decode"),
+        }
+    }
+}
+
+/// The number of received frames buffered for each [`Subscription`] before
+/// it starts missing frames.
+pub const SUBSCRIPTION_CAPACITY: usize = 64;
+
+/// The number of devices a [`Subscription`] can reassemble fast packets
+/// from concurrently.
+pub const SUBSCRIPTION_SOURCES: usize = 8;
+
+/// A [`Service`] shared between the application and the [`EventLoop`]
+/// running it.
+pub type Shared<S> = Arc<Mutex<S>>;
+
+pub struct EventLoop<C: AsyncCan> {
+    node: Node,
+    can: C,
+    epoch: Instant,
+    rx: mpsc::Receiver<NmeaFrame>,
+    frames: broadcast::Sender<NmeaFrame>,
+    loopback: bool,
+    pgn_lists: PgnLists<MAX_PGN_LIST_LEN>,
+    /// PGNs subscribed to by the clients, to be added to the receive list.
+    subscribed: mpsc::UnboundedReceiver<u32>,
+    services: Vec<Shared<dyn Service + Send>>,
+    group_no: u8,
+}
+
+impl<C: AsyncCan> core::fmt::Debug for EventLoop<C> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("EventLoop")
+            .field("node", &self.node)
+            .field("loopback", &self.loopback)
+            .field("pgn_lists", &self.pgn_lists)
+            .field("services", &self.services.len())
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Client {
+    tx: mpsc::Sender<NmeaFrame>,
+    frames: broadcast::Sender<NmeaFrame>,
+    subscribed: mpsc::UnboundedSender<u32>,
+    group_no: u8,
+}
+
+async fn receive_n2k<C>(can: &mut C, epoch: Instant) -> Result<Option<NmeaFrame>, C::Error>
+where
+    C: AsyncCan,
+{
+    let frame = can.receive().await?;
+    let timestamp = C::timestamp(&frame).unwrap_or_else(Instant::now);
+    let timestamp =
+        time::Instant::from_micros(timestamp.saturating_duration_since(epoch).as_micros() as u64);
+
+    Ok(NmeaFrame::from_can_frame(
+        &frame,
+        Metadata::received(timestamp),
+    ))
+}
+
+/// Create an event loop and a client connected to it. `capacity` is the
+/// number of outgoing frames that can be queued before [`Client::send`]
+/// waits for the event loop.
+pub fn new<C: AsyncCan>(
+    name: impl Into<DeviceName>,
+    can: C,
+    capacity: usize,
+) -> (EventLoop<C>, Client) {
+    let (tx, rx) = mpsc::channel(capacity);
+    let (frames, _) = broadcast::channel(SUBSCRIPTION_CAPACITY);
+    let (subscribed_tx, subscribed) = mpsc::unbounded_channel();
+
+    let event_loop = EventLoop {
+        node: Node::new(name),
+        can,
+        epoch: Instant::now(),
+        rx,
+        frames: frames.clone(),
+        loopback: false,
+        pgn_lists: PgnLists::new(),
+        subscribed,
+        services: Vec::new(),
+        group_no: 0,
+    };
+    let client = Client {
+        tx,
+        frames,
+        subscribed: subscribed_tx,
+        group_no: 0,
+    };
+
+    (event_loop, client)
+}
+
+impl<C: AsyncCan> EventLoop<C> {
+    pub fn src(&self) -> u8 {
+        self.node.src()
+    }
+
+    /// The underlying protocol state machine.
+    pub fn node(&self) -> &Node {
+        &self.node
+    }
+
+    /// If enabled, frames sent by the [`Client`]s are also returned from
+    /// [`EventLoop::poll`] and delivered to subscriptions once they have
+    /// been sent, with [`Metadata::local`] set. This gives local consumers
+    /// the same view of the bus as other devices have, since CAN controllers
+    /// do not receive their own frames. Disabled by default.
+    pub fn set_loopback(&mut self, enabled: bool) {
+        self.loopback = enabled;
+    }
+
+    /// The PGN lists sent in reply to requests. Message types that are
+    /// received without a subscription, e.g. by decoding the frames returned
+    /// by [`EventLoop::poll`], can be registered here.
+    pub fn pgn_lists_mut(&mut self) -> &mut PgnLists<MAX_PGN_LIST_LEN> {
+        &mut self.pgn_lists
+    }
+
+    /// Run a service, such as a
+    /// [`SwitchBank`](crate::service::switch_bank::SwitchBank), alongside
+    /// the node. It is passed the received frames and the messages it
+    /// returns are sent once the address claim has finished.
+    pub fn add_service<S: Service + Send + 'static>(&mut self, service: Shared<S>) {
+        self.services.push(service);
+    }
+
+    fn now(&self) -> time::Instant {
+        time::Instant::from_micros(self.epoch.elapsed().as_micros() as u64)
+    }
+
+    /// Send all frames queued by the node.
+    async fn flush(&mut self) -> Result<(), C::Error> {
+        while let Some(frame) = self.node.poll_transmit() {
+            self.can.send(frame.to_can_frame()).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Send the PGN lists that were requested.
+    async fn send_pgn_lists(&mut self, now: time::Instant) -> Result<(), C::Error> {
+        while let Some(transmit) = Service::poll_transmit(&mut self.pgn_lists, now) {
+            self.send_transmit(&transmit).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Send a message on behalf of the event loop itself.
+    async fn send_transmit(&mut self, transmit: &Transmit) -> Result<(), C::Error> {
+        self.group_no = self.group_no.wrapping_add(1);
+
+        for mut frame in transmit.frames(self.group_no) {
+            self.node.prepare_transmit(&mut frame);
+            self.can.send(frame.to_can_frame()).await?;
+        }
+
+        // a full list is not an error, the lists are best effort
+        let _ = self
+            .pgn_lists
+            .register(PgnListFunction::Transmit, transmit.id.pgn());
+
+        Ok(())
+    }
+
+    /// Send the messages of the services that are due.
+    async fn send_services(&mut self, now: time::Instant) -> Result<(), C::Error> {
+        if !self.node.is_ready() {
+            return Ok(());
+        }
+
+        for i in 0..self.services.len() {
+            loop {
+                let transmit = self.services[i]
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .poll_transmit(now);
+                let Some(transmit) = transmit else {
+                    break;
+                };
+
+                self.send_transmit(&transmit).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The earliest instant at which the node or a service has to be
+    /// woken up. Services cannot send before the address claim has
+    /// finished.
+    fn poll_timeout(&self) -> Option<time::Instant> {
+        let services = self.node.is_ready().then_some(&self.services[..]);
+
+        services
+            .into_iter()
+            .flatten()
+            .filter_map(|s| {
+                s.lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .poll_timeout()
+            })
+            .chain(self.node.poll_timeout())
+            .min()
+    }
+
+    pub async fn send_address_claim(&mut self) -> Result<(), C::Error> {
+        self.node.send_address_claim();
+        self.flush().await
+    }
+
+    pub async fn poll(&mut self) -> Result<NmeaFrame, Error<C>> {
+        loop {
+            let now = self.now();
+
+            if !self.node.is_started() {
+                self.node.start(now);
+            }
+
+            self.node.handle_timeout(now);
+            self.flush().await.map_err(Error::Can)?;
+            self.send_services(now).await.map_err(Error::Can)?;
+
+            while let Ok(pgn) = self.subscribed.try_recv() {
+                // a full list is not an error, the lists are best effort
+                let _ = self.pgn_lists.register(PgnListFunction::Receive, pgn);
+            }
+
+            let deadline = self
+                .poll_timeout()
+                .map(|t| self.epoch + Duration::from_micros(t.as_micros()));
+            let timer_fut = async {
+                match deadline {
+                    Some(deadline) => sleep_until(deadline).await,
+                    None => core::future::pending().await,
+                }
+            };
+
+            // wait for the address claim to finish before sending
+            let is_ready = self.node.is_ready();
+
+            ::tokio::select! {
+                () = timer_fut => {}
+                Some(mut f) = self.rx.recv(), if is_ready => {
+                    self.node.prepare_transmit(&mut f);
+                    self.can.send(f.to_can_frame()).await.map_err(Error::Can)?;
+                    let _ = self.pgn_lists.register(PgnListFunction::Transmit, f.id.pgn());
+
+                    if self.loopback {
+                        f.meta = Metadata::local(self.now());
+                        let _ = self.frames.send(f.clone());
+
+                        return Ok(f);
+                    }
+                }
+                res = receive_n2k(&mut self.can, self.epoch) => {
+                    if let Some(f) = res.map_err(Error::Can)? {
+                        let now = self.now();
+                        self.node.handle_frame(&f, now);
+                        self.flush().await.map_err(Error::Can)?;
+
+                        for service in &self.services {
+                            service
+                                .lock()
+                                .unwrap_or_else(PoisonError::into_inner)
+                                .handle_frame(&f, self.node.src(), now);
+                        }
+                        self.send_services(now).await.map_err(Error::Can)?;
+
+                        if self.node.is_ready() && self.pgn_lists.handle_frame(&f, self.node.src()) {
+                            self.send_pgn_lists(now).await.map_err(Error::Can)?;
+                        }
+
+                        // nobody subscribing is not an error
+                        let _ = self.frames.send(f.clone());
+
+                        return Ok(f);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Client {
+    pub async fn send(&mut self, frame: NmeaFrame) -> Result<(), SendError<NmeaFrame>> {
+        self.tx.send(frame).await
+    }
+
+    /// Send a fast packet message. See [`crate::fast_packet`] for more
+    /// information.
+    pub async fn send_fast_packet<T>(
+        &mut self,
+        msg: T,
+        prio: u8,
+        dest: u8,
+    ) -> Result<(), SendError<NmeaFrame>>
+    where
+        T: Message,
+    {
+        let id = Id::new(prio, T::PGN, 0, dest);
+
+        let mut buf: GenericArray<u8, T::EncodedLen> = GenericArray::default();
+
+        self.group_no = self.group_no.wrapping_add(1);
+
+        for fast_packet in msg.encode_to_fast_packets(buf.as_mut_slice(), self.group_no) {
+            let frame = NmeaFrame::new(id, fast_packet.0.as_ref().try_into().unwrap());
+            self.send(frame).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Receive messages of type `T` from the bus. Messages longer than 8
+    /// bytes are assumed to be sent as fast packets and are reassembled
+    /// before being decoded. `T` is added to the receive PGN list.
+    pub fn subscribe<T: Message>(&self) -> Subscription<T> {
+        // the event loop is gone if this fails, and so is the list
+        let _ = self.subscribed.send(T::PGN);
+
+        Subscription {
+            rx: self.frames.subscribe(),
+            decoder: Decoder::new(),
+        }
+    }
+}
+
+/// A stream of decoded messages, created by [`Client::subscribe`].
+pub struct Subscription<T: Message> {
+    rx: broadcast::Receiver<NmeaFrame>,
+    decoder: Decoder<T, SUBSCRIPTION_SOURCES>,
+}
+
+impl<T: Message> Subscription<T> {
+    /// Wait for the next message. Returns [`None`] once the event loop has
+    /// been dropped.
+    pub async fn recv(&mut self) -> Option<Result<Received<T>, T::DecodeError>> {
+        loop {
+            let frame = match self.rx.recv().await {
+                Ok(frame) => frame,
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return None,
+            };
+
+            if let Some(res) = self.decoder.decode(&frame) {
+                return Some(res);
+            }
+        }
+    }
+}
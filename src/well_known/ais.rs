@@ -0,0 +1,1167 @@
+//! Reports received or transmitted by an AIS transceiver.
+//!
+//! Strings are sent as AIS 6-bit ASCII converted to 8-bit ASCII and padded
+//! with `@`. The padding is stripped when decoding and added when encoding.
+
+use core::time::Duration;
+
+use generic_array::typenum::{self, Unsigned};
+
+use super::{
+    get_fixed_str, get_lau_str, get_time, lau_str_len, put_lau_str, put_padded_str, put_time,
+};
+use crate::{Buf, BufMut, Message};
+
+lookup! {
+    /// How many times a message has been repeated.
+    pub enum RepeatIndicator: u8 {
+        Initial = 0,
+        FirstRetransmission = 1,
+        SecondRetransmission = 2,
+        FinalRetransmission = 3,
+    }
+}
+
+lookup! {
+    /// How the transceiver came by a report.
+    pub enum AisTransceiver: u8 {
+        ChannelAVdlReception = 0,
+        ChannelBVdlReception = 1,
+        ChannelAVdlTransmission = 2,
+        ChannelBVdlTransmission = 3,
+        OwnInformationNotBroadcast = 4,
+    }
+}
+
+lookup! {
+    /// Navigational status of a Class A vessel.
+    pub enum NavStatus: u8 {
+        UnderWayUsingEngine = 0,
+        AtAnchor = 1,
+        NotUnderCommand = 2,
+        RestrictedManoeuverability = 3,
+        ConstrainedByDraught = 4,
+        Moored = 5,
+        Aground = 6,
+        EngagedInFishing = 7,
+        UnderWaySailing = 8,
+        HazardousMaterialHighSpeed = 9,
+        HazardousMaterialWingInGround = 10,
+        PowerDrivenVesselTowingAstern = 11,
+        PowerDrivenVesselPushingAhead = 12,
+        AisSart = 14,
+    }
+}
+
+lookup! {
+    pub enum SpecialManeuver: u8 {
+        NotAvailable = 0,
+        NotEngaged = 1,
+        Engaged = 2,
+    }
+}
+
+lookup! {
+    /// The kind of device used to determine a position.
+    pub enum PositionFixDevice: u8 {
+        Undefined = 0,
+        Gps = 1,
+        Glonass = 2,
+        GpsGlonass = 3,
+        LoranC = 4,
+        Chayka = 5,
+        IntegratedNavigationSystem = 6,
+        Surveyed = 7,
+        Galileo = 8,
+        InternalGnss = 15,
+    }
+}
+
+lookup! {
+    pub enum AisUnitType: u8 {
+        Sotdma = 0,
+        CarrierSense = 1,
+    }
+}
+
+lookup! {
+    pub enum AisMode: u8 {
+        Autonomous = 0,
+        Assigned = 1,
+    }
+}
+
+lookup! {
+    pub enum AisCommunicationState: u8 {
+        Sotdma = 0,
+        Itdma = 1,
+    }
+}
+
+lookup! {
+    pub enum AisVersion: u8 {
+        Itu1371_1 = 0,
+        Itu1371_3 = 1,
+        Itu1371_5 = 2,
+    }
+}
+
+lookup! {
+    /// The kind of aid to navigation.
+    pub enum AtonType: u8 {
+        Default = 0,
+        ReferencePoint = 1,
+        Racon = 2,
+        FixedOffshoreStructure = 3,
+        FixedLightWithoutSectors = 5,
+        FixedLightWithSectors = 6,
+        FixedLeadingLightFront = 7,
+        FixedLeadingLightRear = 8,
+        FixedBeaconCardinalN = 9,
+        FixedBeaconCardinalE = 10,
+        FixedBeaconCardinalS = 11,
+        FixedBeaconCardinalW = 12,
+        FixedBeaconPortHand = 13,
+        FixedBeaconStarboardHand = 14,
+        FixedBeaconPreferredChannelPortHand = 15,
+        FixedBeaconPreferredChannelStarboardHand = 16,
+        FixedBeaconIsolatedDanger = 17,
+        FixedBeaconSafeWater = 18,
+        FixedBeaconSpecialMark = 19,
+        FloatingCardinalN = 20,
+        FloatingCardinalE = 21,
+        FloatingCardinalS = 22,
+        FloatingCardinalW = 23,
+        FloatingPortHand = 24,
+        FloatingStarboardHand = 25,
+        FloatingPreferredChannelPortHand = 26,
+        FloatingPreferredChannelStarboardHand = 27,
+        FloatingIsolatedDanger = 28,
+        FloatingSafeWater = 29,
+        FloatingSpecialMark = 30,
+        LightVessel = 31,
+    }
+}
+
+/// Read a fixed length AIS string.
+fn get_ais_str<const N: usize>(buf: &mut &[u8]) -> heapless::String<N> {
+    get_fixed_str(buf, N)
+}
+
+/// Write a fixed length AIS string, padded with `@`. Characters outside of
+/// Latin-1 are replaced with `?`.
+fn put_ais_str(buf: &mut &mut [u8], s: &str, len: usize) {
+    put_padded_str(buf, s, len, b'@');
+}
+
+/// Read the message ID and repeat indicator byte.
+fn get_message_id(buf: &mut &[u8]) -> (u8, RepeatIndicator) {
+    let b = buf.get_u8();
+    (b & 0x3f, (b >> 6).into())
+}
+
+fn put_message_id(buf: &mut &mut [u8], id: u8, repeat: RepeatIndicator) {
+    buf.put_u8((id & 0x3f) | (u8::from(repeat) << 6));
+}
+
+/// A position along with how it was determined, as included in the
+/// position reports.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AisPosition {
+    pub longitude: Option<f64>,
+    pub latitude: Option<f64>,
+    /// Whether the position accuracy is better than 10 m.
+    pub high_accuracy: bool,
+    /// Whether receiver autonomous integrity monitoring is in use.
+    pub raim: bool,
+    /// The second of the UTC minute when the report was generated. 60 means
+    /// that it is not available, 61 manual input, 62 dead reckoning and 63
+    /// that the positioning system is inoperative.
+    pub timestamp: u8,
+}
+
+impl AisPosition {
+    fn get(buf: &mut &[u8]) -> Self {
+        let longitude = buf.get_fixed_i32(1e-7);
+        let latitude = buf.get_fixed_i32(1e-7);
+        let b = buf.get_u8();
+
+        Self {
+            longitude,
+            latitude,
+            high_accuracy: b & 0x01 != 0,
+            raim: b & 0x02 != 0,
+            timestamp: b >> 2,
+        }
+    }
+
+    fn put(&self, buf: &mut &mut [u8]) {
+        buf.put_fixed_i32(self.longitude, 1e-7);
+        buf.put_fixed_i32(self.latitude, 1e-7);
+        buf.put_u8(
+            u8::from(self.high_accuracy) | (u8::from(self.raim) << 1) | (self.timestamp << 2),
+        );
+    }
+}
+
+/// Read the 19-bit communication state and the transceiver information.
+fn get_communication(buf: &mut &[u8]) -> (u32, AisTransceiver) {
+    let b = buf.get_u24();
+    (b & 0x7ffff, ((b >> 19) as u8).into())
+}
+
+fn put_communication(buf: &mut &mut [u8], state: u32, transceiver: AisTransceiver) {
+    buf.put_u24((state & 0x7ffff) | (u32::from(u8::from(transceiver) & 0x1f) << 19));
+}
+
+/// The dimensions of a vessel and the location of its position reference
+/// point, in meters.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Dimensions {
+    pub length: Option<f32>,
+    pub beam: Option<f32>,
+    pub position_from_starboard: Option<f32>,
+    pub position_from_bow: Option<f32>,
+}
+
+impl Dimensions {
+    fn get(buf: &mut &[u8]) -> Self {
+        Self {
+            length: buf.get_fixed_u16(0.1),
+            beam: buf.get_fixed_u16(0.1),
+            position_from_starboard: buf.get_fixed_u16(0.1),
+            position_from_bow: buf.get_fixed_u16(0.1),
+        }
+    }
+
+    fn put(&self, buf: &mut &mut [u8]) {
+        buf.put_fixed_u16(self.length, 0.1);
+        buf.put_fixed_u16(self.beam, 0.1);
+        buf.put_fixed_u16(self.position_from_starboard, 0.1);
+        buf.put_fixed_u16(self.position_from_bow, 0.1);
+    }
+}
+
+/// PGN 129038, AIS messages 1, 2 and 3. This is a fast packet.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ClassAPositionReport {
+    pub message_id: u8,
+    pub repeat: RepeatIndicator,
+    pub mmsi: u32,
+    pub position: AisPosition,
+    /// Course over ground in radians.
+    pub cog: Option<f32>,
+    /// Speed over ground in m/s.
+    pub sog: Option<f32>,
+    pub communication_state: u32,
+    pub transceiver: AisTransceiver,
+    /// True heading in radians.
+    pub heading: Option<f32>,
+    /// Rate of turn in rad/s.
+    pub rate_of_turn: Option<f32>,
+    pub nav_status: NavStatus,
+    pub special_maneuver: SpecialManeuver,
+    /// Sequence ID, used to tie together related messages.
+    pub sid: Option<u8>,
+}
+
+impl Message for ClassAPositionReport {
+    const PGN: u32 = 129038;
+
+    type EncodedLen = typenum::U28;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        put_message_id(&mut buf, self.message_id, self.repeat);
+        buf.put_u32(self.mmsi);
+        self.position.put(&mut buf);
+        buf.put_fixed_u16(self.cog, 1e-4);
+        buf.put_fixed_u16(self.sog, 0.01);
+        put_communication(&mut buf, self.communication_state, self.transceiver);
+        buf.put_fixed_u16(self.heading, 1e-4);
+        buf.put_fixed_i16(self.rate_of_turn, 3.125e-5);
+        buf.put_u8(
+            0xc0 | (u8::from(self.nav_status) & 0x0f)
+                | ((u8::from(self.special_maneuver) & 0x03) << 4),
+        );
+        buf.put_reserved(1);
+        buf.put_opt_u8(self.sid);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        let (message_id, repeat) = get_message_id(&mut data);
+        let mmsi = data.get_u32();
+        let position = AisPosition::get(&mut data);
+        let cog = data.get_fixed_u16(1e-4);
+        let sog = data.get_fixed_u16(0.01);
+        let (communication_state, transceiver) = get_communication(&mut data);
+        let heading = data.get_fixed_u16(1e-4);
+        let rate_of_turn = data.get_fixed_i16(3.125e-5);
+        let status = data.get_u8();
+        data.advance(1);
+
+        Ok(Self {
+            message_id,
+            repeat,
+            mmsi,
+            position,
+            cog,
+            sog,
+            communication_state,
+            transceiver,
+            heading,
+            rate_of_turn,
+            nav_status: (status & 0x0f).into(),
+            special_maneuver: ((status >> 4) & 0x03).into(),
+            sid: data.get_opt_u8(),
+        })
+    }
+}
+
+/// PGN 129039, AIS message 18. This is a fast packet.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ClassBPositionReport {
+    pub message_id: u8,
+    pub repeat: RepeatIndicator,
+    pub mmsi: u32,
+    pub position: AisPosition,
+    /// Course over ground in radians.
+    pub cog: Option<f32>,
+    /// Speed over ground in m/s.
+    pub sog: Option<f32>,
+    pub communication_state: u32,
+    pub transceiver: AisTransceiver,
+    /// True heading in radians.
+    pub heading: Option<f32>,
+    pub unit_type: AisUnitType,
+    pub integrated_display: bool,
+    pub dsc: bool,
+    /// Whether the whole marine band can be used, as opposed to only the top
+    /// 525 kHz.
+    pub entire_band: bool,
+    pub can_handle_msg22: bool,
+    pub mode: AisMode,
+    pub communication_state_type: AisCommunicationState,
+}
+
+impl Message for ClassBPositionReport {
+    const PGN: u32 = 129039;
+
+    type EncodedLen = typenum::U27;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        put_message_id(&mut buf, self.message_id, self.repeat);
+        buf.put_u32(self.mmsi);
+        self.position.put(&mut buf);
+        buf.put_fixed_u16(self.cog, 1e-4);
+        buf.put_fixed_u16(self.sog, 0.01);
+        put_communication(&mut buf, self.communication_state, self.transceiver);
+        buf.put_fixed_u16(self.heading, 1e-4);
+        buf.put_reserved(1);
+        buf.put_u8(
+            0x03 | ((u8::from(self.unit_type) & 0x01) << 2)
+                | (u8::from(self.integrated_display) << 3)
+                | (u8::from(self.dsc) << 4)
+                | (u8::from(self.entire_band) << 5)
+                | (u8::from(self.can_handle_msg22) << 6)
+                | ((u8::from(self.mode) & 0x01) << 7),
+        );
+        buf.put_u16(0xfffe | u16::from(u8::from(self.communication_state_type) & 0x01));
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        let (message_id, repeat) = get_message_id(&mut data);
+        let mmsi = data.get_u32();
+        let position = AisPosition::get(&mut data);
+        let cog = data.get_fixed_u16(1e-4);
+        let sog = data.get_fixed_u16(0.01);
+        let (communication_state, transceiver) = get_communication(&mut data);
+        let heading = data.get_fixed_u16(1e-4);
+        data.advance(1);
+        let flags = data.get_u8();
+
+        Ok(Self {
+            message_id,
+            repeat,
+            mmsi,
+            position,
+            cog,
+            sog,
+            communication_state,
+            transceiver,
+            heading,
+            unit_type: ((flags >> 2) & 0x01).into(),
+            integrated_display: flags & 0x08 != 0,
+            dsc: flags & 0x10 != 0,
+            entire_band: flags & 0x20 != 0,
+            can_handle_msg22: flags & 0x40 != 0,
+            mode: (flags >> 7).into(),
+            communication_state_type: ((data.get_u16() & 0x01) as u8).into(),
+        })
+    }
+}
+
+/// PGN 129040, AIS message 19. This is a fast packet.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ClassBExtendedPositionReport {
+    pub message_id: u8,
+    pub repeat: RepeatIndicator,
+    pub mmsi: u32,
+    pub position: AisPosition,
+    /// Course over ground in radians.
+    pub cog: Option<f32>,
+    /// Speed over ground in m/s.
+    pub sog: Option<f32>,
+    /// Type of ship and cargo, as defined by ITU-R M.1371.
+    pub ship_type: u8,
+    /// True heading in radians.
+    pub heading: Option<f32>,
+    pub fix_device: PositionFixDevice,
+    pub dimensions: Dimensions,
+    pub name: heapless::String<20>,
+    /// Whether data terminal equipment is available.
+    pub dte_available: bool,
+    pub mode: AisMode,
+    pub transceiver: AisTransceiver,
+}
+
+impl Message for ClassBExtendedPositionReport {
+    const PGN: u32 = 129040;
+
+    type EncodedLen = typenum::U54;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        put_message_id(&mut buf, self.message_id, self.repeat);
+        buf.put_u32(self.mmsi);
+        self.position.put(&mut buf);
+        buf.put_fixed_u16(self.cog, 1e-4);
+        buf.put_fixed_u16(self.sog, 0.01);
+        buf.put_reserved(2);
+        buf.put_u8(self.ship_type);
+        buf.put_fixed_u16(self.heading, 1e-4);
+        buf.put_u8(0x0f | (u8::from(self.fix_device) << 4));
+        self.dimensions.put(&mut buf);
+        put_ais_str(&mut buf, &self.name, 20);
+        buf.put_u16(
+            0xf83c
+                | u16::from(!self.dte_available)
+                | (u16::from(u8::from(self.mode) & 0x01) << 1)
+                | (u16::from(u8::from(self.transceiver) & 0x1f) << 6),
+        );
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        let (message_id, repeat) = get_message_id(&mut data);
+        let mmsi = data.get_u32();
+        let position = AisPosition::get(&mut data);
+        let cog = data.get_fixed_u16(1e-4);
+        let sog = data.get_fixed_u16(0.01);
+        data.advance(2);
+        let ship_type = data.get_u8();
+        let heading = data.get_fixed_u16(1e-4);
+        let fix_device = (data.get_u8() >> 4).into();
+        let dimensions = Dimensions::get(&mut data);
+        let name = get_ais_str(&mut data);
+        let flags = data.get_u16();
+
+        Ok(Self {
+            message_id,
+            repeat,
+            mmsi,
+            position,
+            cog,
+            sog,
+            ship_type,
+            heading,
+            fix_device,
+            dimensions,
+            name,
+            dte_available: flags & 0x01 == 0,
+            mode: ((flags >> 1) as u8 & 0x01).into(),
+            transceiver: ((flags >> 6) as u8 & 0x1f).into(),
+        })
+    }
+}
+
+/// The maximum length in bytes of [`AtonReport::name`], including the name
+/// extension.
+pub const MAX_ATON_NAME_LEN: usize = 34;
+
+/// PGN 129041, AIS message 21. This is a fast packet.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AtonReport {
+    pub message_id: u8,
+    pub repeat: RepeatIndicator,
+    pub mmsi: u32,
+    pub position: AisPosition,
+    /// Length or diameter in meters.
+    pub length: Option<f32>,
+    /// Beam or diameter in meters.
+    pub beam: Option<f32>,
+    /// Distance from the reference point to the starboard edge in meters.
+    pub position_from_starboard: Option<f32>,
+    /// Distance from the reference point to the north facing edge in meters.
+    pub position_from_north: Option<f32>,
+    pub aton_type: AtonType,
+    pub off_position: bool,
+    pub virtual_aton: bool,
+    pub assigned_mode: bool,
+    pub fix_device: PositionFixDevice,
+    /// Status bits, as defined by IALA.
+    pub aton_status: u8,
+    pub transceiver: AisTransceiver,
+    pub name: heapless::String<MAX_ATON_NAME_LEN>,
+}
+
+impl AtonReport {
+    const FIXED_LEN: usize = 26;
+}
+
+impl Message for AtonReport {
+    const PGN: u32 = 129041;
+
+    // 26 + 2 + MAX_ATON_NAME_LEN
+    type EncodedLen = typenum::U62;
+
+    type DecodeError = ();
+
+    fn encoded_len(&self) -> usize {
+        Self::FIXED_LEN + lau_str_len(&self.name)
+    }
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        put_message_id(&mut buf, self.message_id, self.repeat);
+        buf.put_u32(self.mmsi);
+        self.position.put(&mut buf);
+        buf.put_fixed_u16(self.length, 0.1);
+        buf.put_fixed_u16(self.beam, 0.1);
+        buf.put_fixed_u16(self.position_from_starboard, 0.1);
+        buf.put_fixed_u16(self.position_from_north, 0.1);
+        buf.put_u8(
+            (u8::from(self.aton_type) & 0x1f)
+                | (u8::from(self.off_position) << 5)
+                | (u8::from(self.virtual_aton) << 6)
+                | (u8::from(self.assigned_mode) << 7),
+        );
+        buf.put_u8(0xe1 | ((u8::from(self.fix_device) & 0x0f) << 1));
+        buf.put_u8(self.aton_status);
+        buf.put_u8(0xe0 | (u8::from(self.transceiver) & 0x1f));
+        put_lau_str(&mut buf, &self.name);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::FIXED_LEN {
+            return Err(());
+        }
+
+        let (message_id, repeat) = get_message_id(&mut data);
+        let mmsi = data.get_u32();
+        let position = AisPosition::get(&mut data);
+        let length = data.get_fixed_u16(0.1);
+        let beam = data.get_fixed_u16(0.1);
+        let position_from_starboard = data.get_fixed_u16(0.1);
+        let position_from_north = data.get_fixed_u16(0.1);
+        let flags = data.get_u8();
+        let fix_device = ((data.get_u8() >> 1) & 0x0f).into();
+
+        Ok(Self {
+            message_id,
+            repeat,
+            mmsi,
+            position,
+            length,
+            beam,
+            position_from_starboard,
+            position_from_north,
+            aton_type: (flags & 0x1f).into(),
+            off_position: flags & 0x20 != 0,
+            virtual_aton: flags & 0x40 != 0,
+            assigned_mode: flags & 0x80 != 0,
+            fix_device,
+            aton_status: data.get_u8(),
+            transceiver: (data.get_u8() & 0x1f).into(),
+            name: get_lau_str(&mut data).ok_or(())?,
+        })
+    }
+}
+
+/// PGN 129793, AIS messages 4 and 11. This is a fast packet.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct UtcDateReport {
+    pub message_id: u8,
+    pub repeat: RepeatIndicator,
+    pub mmsi: u32,
+    pub longitude: Option<f64>,
+    pub latitude: Option<f64>,
+    /// Whether the position accuracy is better than 10 m.
+    pub high_accuracy: bool,
+    pub raim: bool,
+    /// Time since midnight (UTC).
+    pub time: Option<Duration>,
+    pub communication_state: u32,
+    pub transceiver: AisTransceiver,
+    /// Days since 1970-01-01.
+    pub date: Option<u16>,
+    pub fix_device: PositionFixDevice,
+}
+
+impl Message for UtcDateReport {
+    const PGN: u32 = 129793;
+
+    type EncodedLen = typenum::U25;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        put_message_id(&mut buf, self.message_id, self.repeat);
+        buf.put_u32(self.mmsi);
+        buf.put_fixed_i32(self.longitude, 1e-7);
+        buf.put_fixed_i32(self.latitude, 1e-7);
+        buf.put_u8(0xfc | u8::from(self.high_accuracy) | (u8::from(self.raim) << 1));
+        put_time(&mut buf, self.time);
+        put_communication(&mut buf, self.communication_state, self.transceiver);
+        buf.put_opt_u16(self.date);
+        buf.put_u8(0x0f | (u8::from(self.fix_device) << 4));
+        buf.put_reserved(1);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        let (message_id, repeat) = get_message_id(&mut data);
+        let mmsi = data.get_u32();
+        let longitude = data.get_fixed_i32(1e-7);
+        let latitude = data.get_fixed_i32(1e-7);
+        let flags = data.get_u8();
+        let time = get_time(&mut data);
+        let (communication_state, transceiver) = get_communication(&mut data);
+
+        Ok(Self {
+            message_id,
+            repeat,
+            mmsi,
+            longitude,
+            latitude,
+            high_accuracy: flags & 0x01 != 0,
+            raim: flags & 0x02 != 0,
+            time,
+            communication_state,
+            transceiver,
+            date: data.get_opt_u16(),
+            fix_device: (data.get_u8() >> 4).into(),
+        })
+    }
+}
+
+/// PGN 129794, AIS message 5. This is a fast packet.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ClassAStaticData {
+    pub message_id: u8,
+    pub repeat: RepeatIndicator,
+    pub mmsi: u32,
+    pub imo_number: Option<u32>,
+    pub callsign: heapless::String<7>,
+    pub name: heapless::String<20>,
+    /// Type of ship and cargo, as defined by ITU-R M.1371.
+    pub ship_type: u8,
+    pub dimensions: Dimensions,
+    /// Estimated date of arrival, in days since 1970-01-01.
+    pub eta_date: Option<u16>,
+    /// Estimated time of arrival since midnight (UTC).
+    pub eta_time: Option<Duration>,
+    /// Draft in meters.
+    pub draft: Option<f32>,
+    pub destination: heapless::String<20>,
+    pub ais_version: AisVersion,
+    pub fix_device: PositionFixDevice,
+    /// Whether data terminal equipment is available.
+    pub dte_available: bool,
+    pub transceiver: AisTransceiver,
+}
+
+impl Message for ClassAStaticData {
+    const PGN: u32 = 129794;
+
+    type EncodedLen = typenum::U75;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        put_message_id(&mut buf, self.message_id, self.repeat);
+        buf.put_u32(self.mmsi);
+        buf.put_opt_u32(self.imo_number);
+        put_ais_str(&mut buf, &self.callsign, 7);
+        put_ais_str(&mut buf, &self.name, 20);
+        buf.put_u8(self.ship_type);
+        self.dimensions.put(&mut buf);
+        buf.put_opt_u16(self.eta_date);
+        put_time(&mut buf, self.eta_time);
+        buf.put_fixed_u16(self.draft, 0.01);
+        put_ais_str(&mut buf, &self.destination, 20);
+        buf.put_u8(
+            0x80 | (u8::from(self.ais_version) & 0x03)
+                | ((u8::from(self.fix_device) & 0x0f) << 2)
+                | (u8::from(!self.dte_available) << 6),
+        );
+        buf.put_u8(0xe0 | (u8::from(self.transceiver) & 0x1f));
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        let (message_id, repeat) = get_message_id(&mut data);
+        let mmsi = data.get_u32();
+        let imo_number = data.get_opt_u32();
+        let callsign = get_ais_str(&mut data);
+        let name = get_ais_str(&mut data);
+        let ship_type = data.get_u8();
+        let dimensions = Dimensions::get(&mut data);
+        let eta_date = data.get_opt_u16();
+        let eta_time = get_time(&mut data);
+        let draft = data.get_fixed_u16(0.01);
+        let destination = get_ais_str(&mut data);
+        let flags = data.get_u8();
+
+        Ok(Self {
+            message_id,
+            repeat,
+            mmsi,
+            imo_number,
+            callsign,
+            name,
+            ship_type,
+            dimensions,
+            eta_date,
+            eta_time,
+            draft,
+            destination,
+            ais_version: (flags & 0x03).into(),
+            fix_device: ((flags >> 2) & 0x0f).into(),
+            dte_available: flags & 0x40 == 0,
+            transceiver: (data.get_u8() & 0x1f).into(),
+        })
+    }
+}
+
+/// PGN 129798, AIS message 9. This is a fast packet.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SarAircraftPositionReport {
+    pub message_id: u8,
+    pub repeat: RepeatIndicator,
+    pub mmsi: u32,
+    pub position: AisPosition,
+    /// Course over ground in radians.
+    pub cog: Option<f32>,
+    /// Speed over ground in m/s, with a resolution of 0.1 m/s.
+    pub sog: Option<f32>,
+    pub communication_state: u32,
+    pub transceiver: AisTransceiver,
+    /// Altitude in meters.
+    pub altitude: Option<f64>,
+    /// Whether data terminal equipment is available.
+    pub dte_available: bool,
+}
+
+impl Message for SarAircraftPositionReport {
+    const PGN: u32 = 129798;
+
+    type EncodedLen = typenum::U31;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        put_message_id(&mut buf, self.message_id, self.repeat);
+        buf.put_u32(self.mmsi);
+        self.position.put(&mut buf);
+        buf.put_fixed_u16(self.cog, 1e-4);
+        buf.put_fixed_u16(self.sog, 0.1);
+        put_communication(&mut buf, self.communication_state, self.transceiver);
+        buf.put_fixed_i64(self.altitude, 1e-6);
+        buf.put_reserved(1);
+        buf.put_u8(0xfe | u8::from(!self.dte_available));
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        let (message_id, repeat) = get_message_id(&mut data);
+        let mmsi = data.get_u32();
+        let position = AisPosition::get(&mut data);
+        let cog = data.get_fixed_u16(1e-4);
+        let sog = data.get_fixed_u16(0.1);
+        let (communication_state, transceiver) = get_communication(&mut data);
+        let altitude = data.get_fixed_i64(1e-6);
+        data.advance(1);
+
+        Ok(Self {
+            message_id,
+            repeat,
+            mmsi,
+            position,
+            cog,
+            sog,
+            communication_state,
+            transceiver,
+            altitude,
+            dte_available: data.get_u8() & 0x01 == 0,
+        })
+    }
+}
+
+/// PGN 129809, AIS message 24 part A. This is a fast packet.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ClassBStaticDataPartA {
+    pub message_id: u8,
+    pub repeat: RepeatIndicator,
+    pub mmsi: u32,
+    pub name: heapless::String<20>,
+    pub transceiver: AisTransceiver,
+    pub sid: Option<u8>,
+}
+
+impl Message for ClassBStaticDataPartA {
+    const PGN: u32 = 129809;
+
+    type EncodedLen = typenum::U27;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        put_message_id(&mut buf, self.message_id, self.repeat);
+        buf.put_u32(self.mmsi);
+        put_ais_str(&mut buf, &self.name, 20);
+        buf.put_u8(0xe0 | (u8::from(self.transceiver) & 0x1f));
+        buf.put_opt_u8(self.sid);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        let (message_id, repeat) = get_message_id(&mut data);
+
+        Ok(Self {
+            message_id,
+            repeat,
+            mmsi: data.get_u32(),
+            name: get_ais_str(&mut data),
+            transceiver: (data.get_u8() & 0x1f).into(),
+            sid: data.get_opt_u8(),
+        })
+    }
+}
+
+/// PGN 129810, AIS message 24 part B. This is a fast packet.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ClassBStaticDataPartB {
+    pub message_id: u8,
+    pub repeat: RepeatIndicator,
+    pub mmsi: u32,
+    /// Type of ship and cargo, as defined by ITU-R M.1371.
+    pub ship_type: u8,
+    pub vendor_id: heapless::String<7>,
+    pub callsign: heapless::String<7>,
+    pub dimensions: Dimensions,
+    /// The MMSI of the mothership, for auxiliary craft.
+    pub mothership_mmsi: Option<u32>,
+    pub transceiver: AisTransceiver,
+    pub sid: Option<u8>,
+}
+
+impl Message for ClassBStaticDataPartB {
+    const PGN: u32 = 129810;
+
+    type EncodedLen = typenum::U35;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        put_message_id(&mut buf, self.message_id, self.repeat);
+        buf.put_u32(self.mmsi);
+        buf.put_u8(self.ship_type);
+        put_ais_str(&mut buf, &self.vendor_id, 7);
+        put_ais_str(&mut buf, &self.callsign, 7);
+        self.dimensions.put(&mut buf);
+        buf.put_opt_u32(self.mothership_mmsi);
+        buf.put_reserved(1);
+        buf.put_u8(0xe0 | (u8::from(self.transceiver) & 0x1f));
+        buf.put_opt_u8(self.sid);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        let (message_id, repeat) = get_message_id(&mut data);
+        let mmsi = data.get_u32();
+        let ship_type = data.get_u8();
+        let vendor_id = get_ais_str(&mut data);
+        let callsign = get_ais_str(&mut data);
+        let dimensions = Dimensions::get(&mut data);
+        let mothership_mmsi = data.get_opt_u32();
+        data.advance(1);
+
+        Ok(Self {
+            message_id,
+            repeat,
+            mmsi,
+            ship_type,
+            vendor_id,
+            callsign,
+            dimensions,
+            mothership_mmsi,
+            transceiver: (data.get_u8() & 0x1f).into(),
+            sid: data.get_opt_u8(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use generic_array::GenericArray;
+
+    use super::*;
+    use crate::{well_known::round_trip, Decoder, Id, NmeaFrame};
+
+    // No recordings of AIS transponders were at hand when these tests were
+    // written, so the messages were encoded by hand from canboat's field
+    // definitions. They check the layout against canboat, not against the
+    // quirks of real devices, and should be replaced with recordings once
+    // those are available.
+
+    const CLASS_A_POSITION_REPORT: [u8; 28] = [
+        0x01, 0xea, 0x38, 0x95, 0x0e, 0x00, 0x63, 0x9f, 0x02, 0xc0, 0x4f, 0xef, 0x1e, 0x89, 0x39,
+        0x30, 0x00, 0x02, 0x34, 0x12, 0x00, 0xe0, 0x2e, 0xc0, 0xfe, 0xd0, 0xff, 0xff,
+    ];
+
+    #[test]
+    fn class_a_position_report() {
+        let msg: ClassAPositionReport = round_trip(&CLASS_A_POSITION_REPORT);
+
+        assert_eq!(msg.message_id, 1);
+        assert_eq!(msg.repeat, RepeatIndicator::Initial);
+        assert_eq!(msg.mmsi, 244_660_458);
+        assert!((msg.position.longitude.unwrap() - 4.4).abs() < 1e-9);
+        assert!((msg.position.latitude.unwrap() - 51.9).abs() < 1e-9);
+        assert!(msg.position.high_accuracy);
+        assert!(!msg.position.raim);
+        assert_eq!(msg.position.timestamp, 34);
+        assert!((msg.sog.unwrap() - 5.12).abs() < 1e-6);
+        assert_eq!(msg.communication_state, 0x1234);
+        assert_eq!(msg.transceiver, AisTransceiver::ChannelAVdlReception);
+        assert!((msg.rate_of_turn.unwrap() + 0.01).abs() < 1e-6);
+        assert_eq!(msg.nav_status, NavStatus::UnderWayUsingEngine);
+        assert_eq!(msg.special_maneuver, SpecialManeuver::NotEngaged);
+        assert_eq!(msg.sid, None);
+    }
+
+    #[test]
+    fn decode_fast_packets() {
+        let msg = ClassAPositionReport::decode(&CLASS_A_POSITION_REPORT).unwrap();
+        let id = Id::new(4, ClassAPositionReport::PGN, 43, 0xff);
+
+        let mut buf = GenericArray::<u8, <ClassAPositionReport as Message>::EncodedLen>::default();
+        let mut decoder = Decoder::<ClassAPositionReport, 1>::new();
+        let mut decoded = None;
+
+        for packet in msg.encode_to_fast_packets(&mut buf, 2) {
+            assert_eq!(decoded, None);
+            decoded = decoder.decode(&NmeaFrame::new(
+                id,
+                heapless::Vec::from_slice(&packet.0).unwrap(),
+            ));
+        }
+
+        assert_eq!(decoded.unwrap().unwrap().msg, msg);
+    }
+
+    #[test]
+    fn class_b_position_report() {
+        let msg: ClassBPositionReport = round_trip(&[
+            0x12, 0xc0, 0x5a, 0x27, 0x14, 0x00, 0x3e, 0x0b, 0xb7, 0x80, 0xd2, 0x87, 0x16, 0x32,
+            0xff, 0xff, 0x00, 0x00, 0xc1, 0x01, 0x0e, 0xff, 0xff, 0xff, 0x77, 0xff, 0xff,
+        ]);
+
+        assert_eq!(msg.message_id, 18);
+        assert_eq!(msg.mmsi, 338_123_456);
+        assert!(msg.position.raim);
+        assert_eq!(msg.cog, None);
+        assert_eq!(msg.sog, Some(0.0));
+        assert_eq!(msg.communication_state, 0x601c1);
+        assert_eq!(msg.transceiver, AisTransceiver::ChannelBVdlReception);
+        assert_eq!(msg.heading, None);
+        assert_eq!(msg.unit_type, AisUnitType::CarrierSense);
+        assert!(!msg.integrated_display);
+        assert!(msg.dsc);
+        assert!(msg.entire_band);
+        assert!(msg.can_handle_msg22);
+        assert_eq!(msg.mode, AisMode::Autonomous);
+        assert_eq!(msg.communication_state_type, AisCommunicationState::Itdma);
+    }
+
+    #[test]
+    fn class_b_extended_position_report() {
+        let msg: ClassBExtendedPositionReport = round_trip(&[
+            0x13, 0xc1, 0xf9, 0xdf, 0x15, 0xc0, 0x8d, 0xfa, 0xd5, 0x20, 0x40, 0x96, 0x18, 0xf3,
+            0xb8, 0x7a, 0xfa, 0x00, 0xff, 0xff, 0x25, 0xff, 0xff, 0x1f, 0x78, 0x00, 0x28, 0x00,
+            0x14, 0x00, 0x3c, 0x00, 0x53, 0x45, 0x41, 0x20, 0x42, 0x52, 0x45, 0x45, 0x5a, 0x45,
+            0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x7c, 0xf8,
+        ]);
+
+        assert_eq!(msg.mmsi, 367_000_001);
+        assert_eq!(msg.position.timestamp, 60);
+        assert_eq!(msg.ship_type, 37);
+        assert_eq!(msg.fix_device, PositionFixDevice::Gps);
+        assert_eq!(msg.dimensions.length, Some(12.0));
+        assert_eq!(msg.dimensions.position_from_bow, Some(6.0));
+        assert_eq!(msg.name, "SEA BREEZE");
+        assert!(msg.dte_available);
+        assert_eq!(msg.transceiver, AisTransceiver::ChannelBVdlReception);
+    }
+
+    #[test]
+    fn aton_report() {
+        let msg: AtonReport = round_trip(&[
+            0x15, 0x31, 0x86, 0x27, 0x3b, 0x00, 0x48, 0xe8, 0x01, 0x60, 0x63, 0x9b, 0x1e, 0xf5,
+            0x32, 0x00, 0x32, 0x00, 0x19, 0x00, 0x19, 0x00, 0x59, 0xef, 0x00, 0xe0, 0x0d, 0x01,
+            0x57, 0x49, 0x45, 0x4c, 0x49, 0x4e, 0x47, 0x45, 0x4e, 0x20, 0x33,
+        ]);
+
+        assert_eq!(msg.mmsi, 992_446_001);
+        assert_eq!(msg.position.timestamp, 61);
+        assert_eq!(msg.length, Some(5.0));
+        assert_eq!(msg.aton_type, AtonType::FloatingStarboardHand);
+        assert!(!msg.off_position);
+        assert!(msg.virtual_aton);
+        assert_eq!(msg.fix_device, PositionFixDevice::Surveyed);
+        assert_eq!(msg.name, "WIELINGEN 3");
+    }
+
+    #[test]
+    fn utc_date_report() {
+        let msg: UtcDateReport = round_trip(&[
+            0x04, 0x10, 0x43, 0x25, 0x00, 0x40, 0x9c, 0x71, 0x02, 0x00, 0x92, 0xfe, 0x1e, 0xfd,
+            0x00, 0xcc, 0xbf, 0x19, 0x00, 0x20, 0x00, 0x38, 0x4a, 0x7f, 0xff,
+        ]);
+
+        assert_eq!(msg.mmsi, 2_442_000);
+        assert_eq!(msg.time, Some(Duration::from_secs(43_200)));
+        assert_eq!(msg.date, Some(19000));
+        assert_eq!(msg.fix_device, PositionFixDevice::Surveyed);
+    }
+
+    #[test]
+    fn class_a_static_data() {
+        let msg: ClassAStaticData = round_trip(&[
+            0x05, 0xea, 0x38, 0x95, 0x0e, 0x29, 0x78, 0x8a, 0x00, 0x50, 0x44, 0x41, 0x42, 0x40,
+            0x40, 0x40, 0x4e, 0x4f, 0x52, 0x44, 0x49, 0x43, 0x20, 0x53, 0x50, 0x49, 0x52, 0x49,
+            0x54, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x46, 0x08, 0x07, 0x18, 0x01, 0x8c,
+            0x00, 0xdc, 0x05, 0x3d, 0x4a, 0x00, 0x2a, 0x75, 0x15, 0xb6, 0x03, 0x52, 0x4f, 0x54,
+            0x54, 0x45, 0x52, 0x44, 0x41, 0x4d, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40,
+            0x40, 0x40, 0x40, 0x84, 0xe2,
+        ]);
+
+        assert_eq!(msg.imo_number, Some(9_074_729));
+        assert_eq!(msg.callsign, "PDAB");
+        assert_eq!(msg.name, "NORDIC SPIRIT");
+        assert_eq!(msg.ship_type, 70);
+        assert_eq!(msg.dimensions.length, Some(180.0));
+        assert_eq!(msg.eta_date, Some(19005));
+        assert_eq!(msg.eta_time, Some(Duration::from_secs(36_000)));
+        assert!((msg.draft.unwrap() - 9.5).abs() < 1e-6);
+        assert_eq!(msg.destination, "ROTTERDAM");
+        assert_eq!(msg.ais_version, AisVersion::Itu1371_1);
+        assert_eq!(msg.fix_device, PositionFixDevice::Gps);
+        assert!(msg.dte_available);
+        assert_eq!(msg.transceiver, AisTransceiver::ChannelAVdlTransmission);
+    }
+
+    #[test]
+    fn sar_aircraft_position_report() {
+        let msg: SarAircraftPositionReport = round_trip(&[
+            0x09, 0xff, 0x45, 0xa1, 0x06, 0x80, 0xf0, 0xfa, 0x02, 0x80, 0x28, 0x97, 0x1f, 0x50,
+            0x10, 0x27, 0x58, 0x02, 0x00, 0x00, 0x00, 0x00, 0xa3, 0xe1, 0x11, 0x00, 0x00, 0x00,
+            0x00, 0xff, 0xfe,
+        ]);
+
+        assert_eq!(msg.mmsi, 111_232_511);
+        assert!((msg.sog.unwrap() - 60.0).abs() < 1e-4);
+        assert_eq!(msg.altitude, Some(300.0));
+        assert!(msg.dte_available);
+    }
+
+    #[test]
+    fn class_b_static_data() {
+        let msg: ClassBStaticDataPartA = round_trip(&[
+            0x18, 0xc0, 0x5a, 0x27, 0x14, 0x4c, 0x49, 0x54, 0x54, 0x4c, 0x45, 0x20, 0x57, 0x49,
+            0x4e, 0x47, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0xe1, 0xff,
+        ]);
+
+        assert_eq!(msg.message_id, 24);
+        assert_eq!(msg.name, "LITTLE WING");
+
+        let msg: ClassBStaticDataPartB = round_trip(&[
+            0x18, 0xc0, 0x5a, 0x27, 0x14, 0x24, 0x53, 0x52, 0x54, 0x40, 0x40, 0x40, 0x40, 0x57,
+            0x44, 0x4b, 0x31, 0x32, 0x33, 0x34, 0x64, 0x00, 0x23, 0x00, 0x0f, 0x00, 0x50, 0x00,
+            0xff, 0xff, 0xff, 0xff, 0xff, 0xe1, 0xff,
+        ]);
+
+        assert_eq!(msg.ship_type, 36);
+        assert_eq!(msg.vendor_id, "SRT");
+        assert_eq!(msg.callsign, "WDK1234");
+        assert_eq!(msg.dimensions.beam, Some(3.5));
+        assert_eq!(msg.mothership_mmsi, None);
+    }
+
+    #[test]
+    fn non_latin1_name() {
+        let mut msg = ClassBStaticDataPartA::decode(&[
+            0x18, 0xc0, 0x5a, 0x27, 0x14, 0x4c, 0x49, 0x54, 0x54, 0x4c, 0x45, 0x20, 0x57, 0x49,
+            0x4e, 0x47, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0xe1, 0xff,
+        ])
+        .unwrap();
+        msg.name = heapless::String::try_from("SKÅL ⚓").unwrap();
+
+        let mut buf = [0; 27];
+        msg.encode(&mut buf);
+
+        assert_eq!(buf[5..12], *b"SK\xc5L ?@");
+        assert_eq!(ClassBStaticDataPartA::decode(&buf).unwrap().name, "SKÅL ?");
+    }
+}
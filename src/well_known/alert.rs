@@ -0,0 +1,630 @@
+//! Alerts in the style of IEC 62923: alarms, warnings and cautions raised
+//! by a device and acknowledged or silenced from a display.
+
+use generic_array::typenum::{self, Unsigned};
+
+use super::{get_lau_str, lau_str_len, put_lau_str, DeviceName};
+use crate::{Buf, BufMut, Message};
+
+lookup! {
+    /// The severity of an alert.
+    pub enum AlertType: u8 {
+        EmergencyAlarm = 1,
+        Alarm = 2,
+        Warning = 5,
+        Caution = 8,
+    }
+}
+
+lookup! {
+    pub enum AlertCategory: u8 {
+        Navigational = 0,
+        Technical = 1,
+    }
+}
+
+lookup! {
+    pub enum AlertTriggerCondition: u8 {
+        Manual = 0,
+        Auto = 1,
+        Test = 2,
+        Disabled = 3,
+    }
+}
+
+lookup! {
+    pub enum AlertThresholdStatus: u8 {
+        Normal = 0,
+        ThresholdExceeded = 1,
+        ExtremeThresholdExceeded = 2,
+        LowThresholdExceeded = 3,
+        Acknowledged = 4,
+        AwaitingAcknowledge = 5,
+    }
+}
+
+lookup! {
+    pub enum AlertState: u8 {
+        Disabled = 0,
+        Normal = 1,
+        Active = 2,
+        Silenced = 3,
+        Acknowledged = 4,
+        /// The condition has been rectified, but the alert has not yet been
+        /// acknowledged.
+        AwaitingAcknowledge = 5,
+    }
+}
+
+lookup! {
+    pub enum AlertResponseCommand: u8 {
+        Acknowledge = 0,
+        TemporarySilence = 1,
+        TestCommandOff = 2,
+        TestCommandOn = 3,
+    }
+}
+
+lookup! {
+    /// The language of an [`AlertText`].
+    pub enum AlertLanguage: u8 {
+        EnglishUs = 0,
+        EnglishUk = 1,
+        Arabic = 2,
+        ChineseSimplified = 3,
+        Croatian = 4,
+        Danish = 5,
+        Dutch = 6,
+        Finnish = 7,
+        French = 8,
+        German = 9,
+        Greek = 10,
+        Italian = 11,
+        Japanese = 12,
+        Korean = 13,
+        Norwegian = 14,
+        Polish = 15,
+        Portuguese = 16,
+        Russian = 17,
+        Spanish = 18,
+        Swedish = 19,
+    }
+}
+
+flags! {
+    /// The acknowledgement, silence and escalation state of an [`Alert`]
+    /// and which of them the alert supports.
+    pub struct AlertStatus: u8 {
+        temporary_silence = 0,
+        acknowledged = 1,
+        escalated = 2,
+        temporary_silence_support = 3,
+        acknowledge_support = 4,
+        escalation_support = 5,
+    }
+}
+
+/// Identifies an alert. Every alert message starts with these fields, and
+/// a response refers to the alert with the same key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AlertKey {
+    pub alert_type: AlertType,
+    pub category: AlertCategory,
+    pub system: u8,
+    pub sub_system: u8,
+    pub id: u16,
+    /// The NAME of the device that raised the alert.
+    pub source_name: DeviceName,
+    pub source_instance: u8,
+    pub source_index: u8,
+    /// Incremented each time the same alert is raised again.
+    pub occurrence: u8,
+}
+
+impl AlertKey {
+    const LEN: usize = 16;
+
+    fn get(buf: &mut &[u8]) -> Self {
+        let byte = buf.get_u8();
+
+        Self {
+            alert_type: (byte & 0x0f).into(),
+            category: (byte >> 4).into(),
+            system: buf.get_u8(),
+            sub_system: buf.get_u8(),
+            id: buf.get_u16(),
+            source_name: buf.get_u64().into(),
+            source_instance: buf.get_u8(),
+            source_index: buf.get_u8(),
+            occurrence: buf.get_u8(),
+        }
+    }
+
+    fn put(&self, buf: &mut &mut [u8]) {
+        buf.put_u8((u8::from(self.alert_type) & 0x0f) | (u8::from(self.category) << 4));
+        buf.put_u8(self.system);
+        buf.put_u8(self.sub_system);
+        buf.put_u16(self.id);
+        buf.put_u64(self.source_name.0);
+        buf.put_u8(self.source_instance);
+        buf.put_u8(self.source_index);
+        buf.put_u8(self.occurrence);
+    }
+}
+
+/// PGN 126983, the state of an alert. This is a fast packet.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Alert {
+    pub key: AlertKey,
+    pub status: AlertStatus,
+    /// The NAME of the device that acknowledged the alert.
+    pub acknowledge_source_name: Option<DeviceName>,
+    pub trigger_condition: AlertTriggerCondition,
+    pub threshold_status: AlertThresholdStatus,
+    pub priority: u8,
+    pub state: AlertState,
+}
+
+impl Message for Alert {
+    const PGN: u32 = 126983;
+
+    type EncodedLen = typenum::U28;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        self.key.put(&mut buf);
+        buf.put_u8(0xc0 | u8::from(self.status));
+        buf.put_u64(self.acknowledge_source_name.map_or(u64::MAX, |name| name.0));
+        buf.put_u8(
+            (u8::from(self.trigger_condition) & 0x0f) | (u8::from(self.threshold_status) << 4),
+        );
+        buf.put_u8(self.priority);
+        buf.put_u8(self.state.into());
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        let key = AlertKey::get(&mut data);
+        let status = data.get_u8().into();
+        let acknowledge_source_name = match data.get_u64() {
+            u64::MAX => None,
+            name => Some(name.into()),
+        };
+        let conditions = data.get_u8();
+
+        Ok(Self {
+            key,
+            status,
+            acknowledge_source_name,
+            trigger_condition: (conditions & 0x0f).into(),
+            threshold_status: (conditions >> 4).into(),
+            priority: data.get_u8(),
+            state: data.get_u8().into(),
+        })
+    }
+}
+
+/// PGN 126984, a command from a display to acknowledge or silence an alert.
+/// This is a fast packet.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AlertResponse {
+    pub key: AlertKey,
+    /// The NAME of the device sending the response.
+    pub acknowledge_source_name: DeviceName,
+    pub command: AlertResponseCommand,
+}
+
+impl Message for AlertResponse {
+    const PGN: u32 = 126984;
+
+    type EncodedLen = typenum::U25;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        self.key.put(&mut buf);
+        buf.put_u64(self.acknowledge_source_name.0);
+        buf.put_u8(0xfc | (u8::from(self.command) & 0x03));
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        Ok(Self {
+            key: AlertKey::get(&mut data),
+            acknowledge_source_name: data.get_u64().into(),
+            command: (data.get_u8() & 0x03).into(),
+        })
+    }
+}
+
+/// The maximum length of the texts in an [`AlertText`].
+pub const MAX_ALERT_TEXT_LEN: usize = 40;
+
+/// PGN 126985, a human-readable description of an alert. This is a fast
+/// packet.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AlertText {
+    pub key: AlertKey,
+    pub language: AlertLanguage,
+    pub description: heapless::String<MAX_ALERT_TEXT_LEN>,
+    /// Where the alert was raised, e.g. "Engine room".
+    pub location: heapless::String<MAX_ALERT_TEXT_LEN>,
+}
+
+impl AlertText {
+    const FIXED_LEN: usize = AlertKey::LEN + 1;
+}
+
+impl Message for AlertText {
+    const PGN: u32 = 126985;
+
+    // 17 + 2 * (2 + MAX_ALERT_TEXT_LEN)
+    type EncodedLen = typenum::U101;
+
+    type DecodeError = ();
+
+    fn encoded_len(&self) -> usize {
+        Self::FIXED_LEN + lau_str_len(&self.description) + lau_str_len(&self.location)
+    }
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        self.key.put(&mut buf);
+        buf.put_u8(self.language.into());
+        put_lau_str(&mut buf, &self.description);
+        put_lau_str(&mut buf, &self.location);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::FIXED_LEN {
+            return Err(());
+        }
+
+        Ok(Self {
+            key: AlertKey::get(&mut data),
+            language: data.get_u8().into(),
+            description: get_lau_str(&mut data).ok_or(())?,
+            location: get_lau_str(&mut data).ok_or(())?,
+        })
+    }
+}
+
+/// PGN 126986, how an alert is configured. This is a fast packet.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AlertConfiguration {
+    pub key: AlertKey,
+    pub alert_control: u8,
+    pub user_defined_alert_assignment: u8,
+    pub reactivation_period: Option<u8>,
+    pub temporary_silence_period: Option<u8>,
+    pub escalation_period: Option<u8>,
+}
+
+impl Message for AlertConfiguration {
+    const PGN: u32 = 126986;
+
+    type EncodedLen = typenum::U20;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        self.key.put(&mut buf);
+        buf.put_u8(
+            (self.alert_control & 0x03) | ((self.user_defined_alert_assignment & 0x03) << 2) | 0xf0,
+        );
+        buf.put_opt_u8(self.reactivation_period);
+        buf.put_opt_u8(self.temporary_silence_period);
+        buf.put_opt_u8(self.escalation_period);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        let key = AlertKey::get(&mut data);
+        let control = data.get_u8();
+
+        Ok(Self {
+            key,
+            alert_control: control & 0x03,
+            user_defined_alert_assignment: (control >> 2) & 0x03,
+            reactivation_period: data.get_opt_u8(),
+            temporary_silence_period: data.get_opt_u8(),
+            escalation_period: data.get_opt_u8(),
+        })
+    }
+}
+
+/// The maximum number of parameters in an [`AlertThreshold`] or
+/// [`AlertValue`].
+pub const MAX_ALERT_PARAMETERS: usize = 8;
+
+/// A threshold of an [`AlertThreshold`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Threshold {
+    pub parameter: u8,
+    pub trigger_method: u8,
+    pub data_format: u8,
+    pub level: u64,
+}
+
+/// PGN 126987, the thresholds at which an alert is raised. This is a fast
+/// packet.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AlertThreshold {
+    pub key: AlertKey,
+    pub thresholds: heapless::Vec<Threshold, MAX_ALERT_PARAMETERS>,
+}
+
+impl AlertThreshold {
+    const FIXED_LEN: usize = AlertKey::LEN + 1;
+}
+
+impl Message for AlertThreshold {
+    const PGN: u32 = 126987;
+
+    // 17 + 11 * MAX_ALERT_PARAMETERS
+    type EncodedLen = typenum::U105;
+
+    type DecodeError = ();
+
+    fn encoded_len(&self) -> usize {
+        Self::FIXED_LEN + 11 * self.thresholds.len()
+    }
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        self.key.put(&mut buf);
+        buf.put_u8(self.thresholds.len() as u8);
+
+        for threshold in &self.thresholds {
+            buf.put_u8(threshold.parameter);
+            buf.put_u8(threshold.trigger_method);
+            buf.put_u8(threshold.data_format);
+            buf.put_u64(threshold.level);
+        }
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::FIXED_LEN {
+            return Err(());
+        }
+
+        let key = AlertKey::get(&mut data);
+        let n = usize::from(data.get_u8());
+
+        if n > MAX_ALERT_PARAMETERS || data.remaining() < 11 * n {
+            return Err(());
+        }
+
+        let mut thresholds = heapless::Vec::new();
+
+        for _ in 0..n {
+            let threshold = Threshold {
+                parameter: data.get_u8(),
+                trigger_method: data.get_u8(),
+                data_format: data.get_u8(),
+                level: data.get_u64(),
+            };
+
+            // cannot fail since n <= MAX_ALERT_PARAMETERS
+            let _ = thresholds.push(threshold);
+        }
+
+        Ok(Self { key, thresholds })
+    }
+}
+
+/// A value of an [`AlertValue`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Value {
+    pub parameter: u8,
+    pub data_format: u8,
+    pub data: u64,
+}
+
+/// PGN 126988, the values that caused an alert. This is a fast packet.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AlertValue {
+    pub key: AlertKey,
+    pub values: heapless::Vec<Value, MAX_ALERT_PARAMETERS>,
+}
+
+impl AlertValue {
+    const FIXED_LEN: usize = AlertKey::LEN + 1;
+}
+
+impl Message for AlertValue {
+    const PGN: u32 = 126988;
+
+    // 17 + 10 * MAX_ALERT_PARAMETERS
+    type EncodedLen = typenum::U97;
+
+    type DecodeError = ();
+
+    fn encoded_len(&self) -> usize {
+        Self::FIXED_LEN + 10 * self.values.len()
+    }
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        self.key.put(&mut buf);
+        buf.put_u8(self.values.len() as u8);
+
+        for value in &self.values {
+            buf.put_u8(value.parameter);
+            buf.put_u8(value.data_format);
+            buf.put_u64(value.data);
+        }
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::FIXED_LEN {
+            return Err(());
+        }
+
+        let key = AlertKey::get(&mut data);
+        let n = usize::from(data.get_u8());
+
+        if n > MAX_ALERT_PARAMETERS || data.remaining() < 10 * n {
+            return Err(());
+        }
+
+        let mut values = heapless::Vec::new();
+
+        for _ in 0..n {
+            let value = Value {
+                parameter: data.get_u8(),
+                data_format: data.get_u8(),
+                data: data.get_u64(),
+            };
+
+            // cannot fail since n <= MAX_ALERT_PARAMETERS
+            let _ = values.push(value);
+        }
+
+        Ok(Self { key, values })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::well_known::round_trip;
+
+    const KEY: [u8; 16] = [
+        0x12, 0x05, 0x01, 0x2c, 0x01, 0x05, 0x04, 0x03, 0x02, 0x01, 0x00, 0x00, 0x20, 0x00, 0xff,
+        0x01,
+    ];
+
+    fn with_key<const N: usize>(data: &[u8]) -> heapless::Vec<u8, N> {
+        let mut buf = heapless::Vec::from_slice(&KEY).unwrap();
+        buf.extend_from_slice(data).unwrap();
+        buf
+    }
+
+    fn assert_key(key: &AlertKey) {
+        assert_eq!(key.alert_type, AlertType::Alarm);
+        assert_eq!(key.category, AlertCategory::Technical);
+        assert_eq!(key.system, 5);
+        assert_eq!(key.sub_system, 1);
+        assert_eq!(key.id, 300);
+        assert_eq!(key.source_name, DeviceName(0x2000_0001_0203_0405));
+        assert_eq!(key.source_instance, 0);
+        assert_eq!(key.source_index, 0xff);
+        assert_eq!(key.occurrence, 1);
+    }
+
+    #[test]
+    fn alert() {
+        let msg: Alert = round_trip(&with_key::<28>(&[
+            0xd8, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x11, 0x03, 0x02,
+        ]));
+
+        assert_key(&msg.key);
+        assert_eq!(
+            msg.status,
+            AlertStatus {
+                temporary_silence_support: true,
+                acknowledge_support: true,
+                ..Default::default()
+            }
+        );
+        assert_eq!(msg.acknowledge_source_name, None);
+        assert_eq!(msg.trigger_condition, AlertTriggerCondition::Auto);
+        assert_eq!(
+            msg.threshold_status,
+            AlertThresholdStatus::ThresholdExceeded
+        );
+        assert_eq!(msg.priority, 3);
+        assert_eq!(msg.state, AlertState::Active);
+    }
+
+    #[test]
+    fn alert_response() {
+        let msg: AlertResponse = round_trip(&with_key::<25>(&[
+            0x42, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0xfc,
+        ]));
+
+        assert_key(&msg.key);
+        assert_eq!(
+            msg.acknowledge_source_name,
+            DeviceName(0x1000_0000_0000_0042)
+        );
+        assert_eq!(msg.command, AlertResponseCommand::Acknowledge);
+    }
+
+    #[test]
+    fn alert_text() {
+        let msg: AlertText = round_trip(&with_key::<48>(&[
+            0x00, 0x12, 0x01, 0x48, 0x69, 0x67, 0x68, 0x20, 0x62, 0x69, 0x6c, 0x67, 0x65, 0x20,
+            0x6c, 0x65, 0x76, 0x65, 0x6c, 0x0d, 0x01, 0x45, 0x6e, 0x67, 0x69, 0x6e, 0x65, 0x20,
+            0x72, 0x6f, 0x6f, 0x6d,
+        ]));
+
+        assert_eq!(msg.language, AlertLanguage::EnglishUs);
+        assert_eq!(msg.description, "High bilge level");
+        assert_eq!(msg.location, "Engine room");
+    }
+
+    #[test]
+    fn alert_text_utf16() {
+        let msg = AlertText {
+            key: AlertKey::get(&mut &KEY[..]),
+            language: AlertLanguage::French,
+            description: heapless::String::try_from("Niveau de cale élevé").unwrap(),
+            location: heapless::String::try_from("Pompe de cale activée").unwrap(),
+        };
+
+        let mut buf = [0; 101];
+        let len = msg.encoded_len();
+        msg.encode(&mut buf[..len]);
+
+        // the description is sent as UTF-16
+        assert_eq!(buf[17..22], [42, 0x00, b'N', 0x00, b'i']);
+        assert_eq!(buf[57..59], [0xe9, 0x00]);
+        // the location is truncated to 40 bytes at a character boundary
+        assert_eq!(buf[59..61], [42, 0x00]);
+        assert_eq!(len, 101);
+
+        let decoded = AlertText::decode(&buf[..len]).unwrap();
+        assert_eq!(decoded.description, msg.description);
+        assert_eq!(decoded.location, "Pompe de cale activé");
+    }
+
+    #[test]
+    fn alert_threshold() {
+        let msg: AlertThreshold = round_trip(&with_key::<28>(&[
+            0x01, 0x01, 0x02, 0x03, 0xf4, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ]));
+
+        assert_eq!(
+            msg.thresholds,
+            [Threshold {
+                parameter: 1,
+                trigger_method: 2,
+                data_format: 3,
+                level: 500,
+            }]
+        );
+
+        // more parameters than there is data for
+        let mut data = with_key::<28>(&[0x02]);
+        data.extend_from_slice(&[0; 11]).unwrap();
+        assert_eq!(AlertThreshold::decode(&data), Err(()));
+    }
+}
@@ -0,0 +1,470 @@
+//! Batteries, chargers, inverters and other DC sources.
+
+use core::time::Duration;
+
+use generic_array::typenum::{self, Unsigned};
+
+use super::{get_opt_bool, put_opt_bool};
+use crate::{Buf, BufMut, Message};
+
+lookup! {
+    pub enum DcSource: u8 {
+        Battery = 0,
+        Alternator = 1,
+        Converter = 2,
+        SolarCell = 3,
+        WindGenerator = 4,
+    }
+}
+
+lookup! {
+    pub enum ChargerState: u8 {
+        NotCharging = 0,
+        Bulk = 1,
+        Absorption = 2,
+        Overcharge = 3,
+        Equalise = 4,
+        Float = 5,
+        NoFloat = 6,
+        ConstantVi = 7,
+        Disabled = 8,
+        Fault = 9,
+    }
+}
+
+lookup! {
+    pub enum ChargerMode: u8 {
+        Standalone = 0,
+        Primary = 1,
+        Secondary = 2,
+        Echo = 3,
+    }
+}
+
+lookup! {
+    pub enum InverterState: u8 {
+        Invert = 0,
+        AcPassthrough = 1,
+        LoadSense = 2,
+        Fault = 3,
+        Disabled = 4,
+    }
+}
+
+lookup! {
+    pub enum BatteryType: u8 {
+        Flooded = 0,
+        Gel = 1,
+        Agm = 2,
+    }
+}
+
+lookup! {
+    pub enum NominalVoltage: u8 {
+        V6 = 0,
+        V12 = 1,
+        V24 = 2,
+        V32 = 3,
+        V36 = 4,
+        V42 = 5,
+        V48 = 6,
+    }
+}
+
+lookup! {
+    pub enum BatteryChemistry: u8 {
+        Lead = 0,
+        LithiumIon = 1,
+        NickelCadmium = 2,
+        ZincOxide = 3,
+        NickelMetalHydride = 4,
+    }
+}
+
+/// PGN 127506, the state of a DC source. This is a fast packet.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DcDetailedStatus {
+    /// Sequence ID, used to tie together related messages.
+    pub sid: Option<u8>,
+    pub instance: u8,
+    pub source: DcSource,
+    /// State of charge in percent.
+    pub state_of_charge: Option<u8>,
+    /// State of health in percent.
+    pub state_of_health: Option<u8>,
+    /// Time remaining at the current rate of discharge, with a resolution of
+    /// one minute.
+    pub time_remaining: Option<Duration>,
+    /// Ripple voltage in volts.
+    pub ripple_voltage: Option<f32>,
+    /// Remaining capacity in ampere hours.
+    pub remaining_capacity: Option<u16>,
+}
+
+impl Message for DcDetailedStatus {
+    const PGN: u32 = 127506;
+
+    type EncodedLen = typenum::U11;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_opt_u8(self.sid);
+        buf.put_u8(self.instance);
+        buf.put_u8(self.source.into());
+        buf.put_opt_u8(self.state_of_charge);
+        buf.put_opt_u8(self.state_of_health);
+        buf.put_opt_u16(self.time_remaining.map(|t| (t.as_secs() / 60) as u16));
+        buf.put_fixed_u16(self.ripple_voltage, 0.001);
+        buf.put_opt_u16(self.remaining_capacity);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        Ok(Self {
+            sid: data.get_opt_u8(),
+            instance: data.get_u8(),
+            source: data.get_u8().into(),
+            state_of_charge: data.get_opt_u8(),
+            state_of_health: data.get_opt_u8(),
+            time_remaining: data
+                .get_opt_u16()
+                .map(|t| Duration::from_secs(u64::from(t) * 60)),
+            ripple_voltage: data.get_fixed_u16(0.001),
+            remaining_capacity: data.get_opt_u16(),
+        })
+    }
+}
+
+/// PGN 127507, the state of a battery charger.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ChargerStatus {
+    pub instance: u8,
+    pub battery_instance: u8,
+    pub state: ChargerState,
+    pub mode: ChargerMode,
+    pub enabled: Option<bool>,
+    pub equalization_pending: Option<bool>,
+    /// Equalization time remaining, with a resolution of one minute.
+    pub equalization_time_remaining: Option<Duration>,
+}
+
+impl Message for ChargerStatus {
+    const PGN: u32 = 127507;
+
+    type EncodedLen = typenum::U8;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_u8(self.instance);
+        buf.put_u8(self.battery_instance);
+        buf.put_u8((u8::from(self.state) & 0x0f) | (u8::from(self.mode) << 4));
+        buf.put_u8(
+            0xf0 | put_opt_bool(self.enabled) | (put_opt_bool(self.equalization_pending) << 2),
+        );
+        buf.put_opt_u16(
+            self.equalization_time_remaining
+                .map(|t| (t.as_secs() / 60) as u16),
+        );
+        buf.put_reserved(2);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        let instance = data.get_u8();
+        let battery_instance = data.get_u8();
+        let state_mode = data.get_u8();
+        let flags = data.get_u8();
+
+        Ok(Self {
+            instance,
+            battery_instance,
+            state: (state_mode & 0x0f).into(),
+            mode: (state_mode >> 4).into(),
+            enabled: get_opt_bool(flags),
+            equalization_pending: get_opt_bool(flags >> 2),
+            equalization_time_remaining: data
+                .get_opt_u16()
+                .map(|t| Duration::from_secs(u64::from(t) * 60)),
+        })
+    }
+}
+
+/// PGN 127508, battery voltage, current and temperature.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BatteryStatus {
+    pub instance: u8,
+    /// Voltage in volts.
+    pub voltage: Option<f32>,
+    /// Current in amperes, positive when charging.
+    pub current: Option<f32>,
+    /// Temperature in kelvin.
+    pub temperature: Option<f32>,
+    pub sid: Option<u8>,
+}
+
+impl Message for BatteryStatus {
+    const PGN: u32 = 127508;
+
+    type EncodedLen = typenum::U8;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_u8(self.instance);
+        buf.put_fixed_i16(self.voltage, 0.01);
+        buf.put_fixed_i16(self.current, 0.1);
+        buf.put_fixed_u16(self.temperature, 0.01);
+        buf.put_opt_u8(self.sid);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        Ok(Self {
+            instance: data.get_u8(),
+            voltage: data.get_fixed_i16(0.01),
+            current: data.get_fixed_i16(0.1),
+            temperature: data.get_fixed_u16(0.01),
+            sid: data.get_opt_u8(),
+        })
+    }
+}
+
+/// PGN 127509, the state of an inverter.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct InverterStatus {
+    pub instance: u8,
+    pub ac_instance: u8,
+    pub dc_instance: u8,
+    pub state: InverterState,
+    pub enabled: Option<bool>,
+}
+
+impl Message for InverterStatus {
+    const PGN: u32 = 127509;
+
+    type EncodedLen = typenum::U4;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_u8(self.instance);
+        buf.put_u8(self.ac_instance);
+        buf.put_u8(self.dc_instance);
+        buf.put_u8(0xc0 | (u8::from(self.state) & 0x0f) | (put_opt_bool(self.enabled) << 4));
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        let instance = data.get_u8();
+        let ac_instance = data.get_u8();
+        let dc_instance = data.get_u8();
+        let state_enabled = data.get_u8();
+
+        Ok(Self {
+            instance,
+            ac_instance,
+            dc_instance,
+            state: (state_enabled & 0x0f).into(),
+            enabled: get_opt_bool(state_enabled >> 4),
+        })
+    }
+}
+
+/// PGN 127513, the configuration of a battery.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BatteryConfigurationStatus {
+    pub instance: u8,
+    pub battery_type: BatteryType,
+    pub supports_equalization: Option<bool>,
+    pub nominal_voltage: NominalVoltage,
+    pub chemistry: BatteryChemistry,
+    /// Capacity in ampere hours.
+    pub capacity: Option<u16>,
+    /// Temperature coefficient in percent.
+    pub temperature_coefficient: Option<i8>,
+    /// Peukert exponent, between 1.0 and 1.504.
+    pub peukert_exponent: Option<f32>,
+    /// Charge efficiency factor in percent.
+    pub charge_efficiency_factor: Option<i8>,
+}
+
+impl Message for BatteryConfigurationStatus {
+    const PGN: u32 = 127513;
+
+    type EncodedLen = typenum::U8;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_u8(self.instance);
+        buf.put_u8(
+            0xc0 | (u8::from(self.battery_type) & 0x0f)
+                | (put_opt_bool(self.supports_equalization) << 4),
+        );
+        buf.put_u8((u8::from(self.nominal_voltage) & 0x0f) | (u8::from(self.chemistry) << 4));
+        buf.put_opt_u16(self.capacity);
+        buf.put_i8(self.temperature_coefficient.unwrap_or(i8::MAX));
+        buf.put_fixed_u8(self.peukert_exponent.map(|e| e - 1.0), 0.002);
+        buf.put_i8(self.charge_efficiency_factor.unwrap_or(i8::MAX));
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        let instance = data.get_u8();
+        let type_equalization = data.get_u8();
+        let voltage_chemistry = data.get_u8();
+
+        Ok(Self {
+            instance,
+            battery_type: (type_equalization & 0x0f).into(),
+            supports_equalization: get_opt_bool(type_equalization >> 4),
+            nominal_voltage: (voltage_chemistry & 0x0f).into(),
+            chemistry: (voltage_chemistry >> 4).into(),
+            capacity: data.get_opt_u16(),
+            temperature_coefficient: match data.get_i8() {
+                i8::MAX => None,
+                value => Some(value),
+            },
+            peukert_exponent: data.get_fixed_u8(0.002).map(|e| e + 1.0),
+            charge_efficiency_factor: match data.get_i8() {
+                i8::MAX => None,
+                value => Some(value),
+            },
+        })
+    }
+}
+
+/// PGN 127751, the voltage and current of a DC connection.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DcVoltageCurrent {
+    pub sid: Option<u8>,
+    pub connection: u8,
+    /// Voltage in volts.
+    pub voltage: Option<f32>,
+    /// Current in amperes.
+    pub current: Option<f32>,
+}
+
+impl Message for DcVoltageCurrent {
+    const PGN: u32 = 127751;
+
+    type EncodedLen = typenum::U8;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_opt_u8(self.sid);
+        buf.put_u8(self.connection);
+        buf.put_fixed_u16(self.voltage, 0.1);
+        buf.put_fixed_i24(self.current, 0.01);
+        buf.put_reserved(1);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        Ok(Self {
+            sid: data.get_opt_u8(),
+            connection: data.get_u8(),
+            voltage: data.get_fixed_u16(0.1),
+            current: data.get_fixed_i24(0.01),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::well_known::round_trip;
+
+    #[test]
+    fn dc_detailed_status() {
+        let msg: DcDetailedStatus = round_trip(&[
+            0x04, 0x00, 0x00, 0x55, 0x62, 0xd2, 0x00, 0x2c, 0x01, 0xff, 0xff,
+        ]);
+
+        assert_eq!(msg.source, DcSource::Battery);
+        assert_eq!(msg.state_of_charge, Some(85));
+        assert_eq!(msg.state_of_health, Some(98));
+        assert_eq!(msg.time_remaining, Some(Duration::from_secs(210 * 60)));
+        assert!((msg.ripple_voltage.unwrap() - 0.3).abs() < 1e-6);
+        assert_eq!(msg.remaining_capacity, None);
+    }
+
+    #[test]
+    fn charger_status() {
+        let msg: ChargerStatus = round_trip(&[0x00, 0x01, 0x12, 0xf1, 0xff, 0xff, 0xff, 0xff]);
+
+        assert_eq!(msg.state, ChargerState::Absorption);
+        assert_eq!(msg.mode, ChargerMode::Primary);
+        assert_eq!(msg.enabled, Some(true));
+        assert_eq!(msg.equalization_pending, Some(false));
+        assert_eq!(msg.equalization_time_remaining, None);
+    }
+
+    #[test]
+    fn battery_status() {
+        let msg: BatteryStatus = round_trip(&[0x01, 0x0c, 0x05, 0x8d, 0xff, 0x4b, 0x73, 0x07]);
+
+        assert!((msg.voltage.unwrap() - 12.92).abs() < 1e-4);
+        assert!((msg.current.unwrap() + 11.5).abs() < 1e-4);
+        assert!((msg.temperature.unwrap() - 295.15).abs() < 1e-3);
+        assert_eq!(msg.sid, Some(7));
+    }
+
+    #[test]
+    fn inverter_status() {
+        let msg: InverterStatus = round_trip(&[0x00, 0x01, 0x02, 0xd1]);
+
+        assert_eq!(msg.state, InverterState::AcPassthrough);
+        assert_eq!(msg.enabled, Some(true));
+    }
+
+    #[test]
+    fn battery_configuration_status() {
+        let msg: BatteryConfigurationStatus =
+            round_trip(&[0x00, 0xc2, 0x11, 0xc8, 0x00, 0x01, 0x32, 0x5f]);
+
+        assert_eq!(msg.battery_type, BatteryType::Agm);
+        assert_eq!(msg.supports_equalization, Some(false));
+        assert_eq!(msg.nominal_voltage, NominalVoltage::V12);
+        assert_eq!(msg.chemistry, BatteryChemistry::LithiumIon);
+        assert_eq!(msg.capacity, Some(200));
+        assert_eq!(msg.temperature_coefficient, Some(1));
+        assert!((msg.peukert_exponent.unwrap() - 1.1).abs() < 1e-6);
+        assert_eq!(msg.charge_efficiency_factor, Some(95));
+    }
+
+    #[test]
+    fn dc_voltage_current() {
+        let msg: DcVoltageCurrent = round_trip(&[0x00, 0x02, 0x83, 0x00, 0x2a, 0xea, 0xff, 0xff]);
+
+        assert!((msg.voltage.unwrap() - 13.1).abs() < 1e-4);
+        assert!((msg.current.unwrap() + 55.9).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,420 @@
+//! Engine, transmission and fuel consumption data.
+//!
+//! Generated from the canboat PGN database by `cargo xtask codegen`. Do
+//! not edit this file; change `xtask/canboat.json` or the generator
+//! instead.
+
+use core::time::Duration;
+
+use generic_array::typenum::{self, Unsigned};
+
+use super::{get_lau_str, lau_str_len, put_lau_str};
+use crate::{Buf, BufMut, Message};
+
+lookup! {
+    /// Canboat lookup `ENGINE_INSTANCE`.
+    pub enum EngineInstance: u8 {
+        SingleEngineOrDualEnginePort = 0,
+        DualEngineStarboard = 1,
+    }
+}
+
+lookup! {
+    /// Canboat lookup `GEAR_STATUS`.
+    pub enum GearStatus: u8 {
+        Forward = 0,
+        Neutral = 1,
+        Reverse = 2,
+    }
+}
+
+flags! {
+    /// Canboat bit lookup `ENGINE_STATUS_1`.
+    pub struct EngineStatus1: u16 {
+        check_engine = 0,
+        over_temperature = 1,
+        low_oil_pressure = 2,
+        low_oil_level = 3,
+        low_fuel_pressure = 4,
+        low_system_voltage = 5,
+        low_coolant_level = 6,
+        water_flow = 7,
+        water_in_fuel = 8,
+        charge_indicator = 9,
+        preheat_indicator = 10,
+        high_boost_pressure = 11,
+        rev_limit_exceeded = 12,
+        egr_system = 13,
+        throttle_position_sensor = 14,
+        emergency_stop = 15,
+    }
+}
+
+flags! {
+    /// Canboat bit lookup `ENGINE_STATUS_2`.
+    pub struct EngineStatus2: u16 {
+        warning_level_1 = 0,
+        warning_level_2 = 1,
+        power_reduction = 2,
+        maintenance_needed = 3,
+        engine_comm_error = 4,
+        sub_or_secondary_throttle = 5,
+        neutral_start_protect = 6,
+        engine_shutting_down = 7,
+    }
+}
+
+flags! {
+    /// Canboat bit lookup `TRANSMISSION_STATUS`.
+    pub struct TransmissionStatus: u8 {
+        check_temperature = 0,
+        over_temperature = 1,
+        low_oil_pressure = 2,
+        low_oil_level = 3,
+        sail_drive = 4,
+    }
+}
+
+/// PGN 127488, Engine Parameters, Rapid Update.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct EngineParametersRapidUpdate {
+    pub instance: EngineInstance,
+    /// Speed in rpm.
+    pub speed: Option<f32>,
+    /// Boost pressure in pascals.
+    pub boost_pressure: Option<f32>,
+    /// Tilt/Trim in percent.
+    pub tilt_trim: Option<f32>,
+}
+
+impl Message for EngineParametersRapidUpdate {
+    const PGN: u32 = 127488;
+
+    type EncodedLen = typenum::U8;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_u8(self.instance.into());
+        buf.put_fixed_u16(self.speed, 0.25);
+        buf.put_fixed_u16(self.boost_pressure, 100.0);
+        buf.put_fixed_i8(self.tilt_trim, 1.0);
+        buf.put_reserved(2);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        let instance = data.get_u8().into();
+        let speed = data.get_fixed_u16(0.25);
+        let boost_pressure = data.get_fixed_u16(100.0);
+        let tilt_trim = data.get_fixed_i8(1.0);
+        data.advance(2);
+
+        Ok(Self {
+            instance,
+            speed,
+            boost_pressure,
+            tilt_trim,
+        })
+    }
+}
+
+/// PGN 127489, Engine Parameters, Dynamic. This is a fast packet.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct EngineParametersDynamic {
+    pub instance: EngineInstance,
+    /// Oil pressure in pascals.
+    pub oil_pressure: Option<f32>,
+    /// Oil temperature in kelvin.
+    pub oil_temperature: Option<f32>,
+    /// Temperature in kelvin.
+    pub temperature: Option<f32>,
+    /// Alternator potential in volts.
+    pub alternator_potential: Option<f32>,
+    /// Fuel rate in liters per hour.
+    pub fuel_rate: Option<f32>,
+    pub total_engine_hours: Option<Duration>,
+    /// Coolant pressure in pascals.
+    pub coolant_pressure: Option<f32>,
+    /// Fuel pressure in pascals.
+    pub fuel_pressure: Option<f32>,
+    pub discrete_status_1: EngineStatus1,
+    pub discrete_status_2: EngineStatus2,
+    /// Engine load in percent.
+    pub engine_load: Option<f32>,
+    /// Engine torque in percent.
+    pub engine_torque: Option<f32>,
+}
+
+impl Message for EngineParametersDynamic {
+    const PGN: u32 = 127489;
+
+    type EncodedLen = typenum::U26;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_u8(self.instance.into());
+        buf.put_fixed_u16(self.oil_pressure, 100.0);
+        buf.put_fixed_u16(self.oil_temperature, 0.1);
+        buf.put_fixed_u16(self.temperature, 0.01);
+        buf.put_fixed_i16(self.alternator_potential, 0.01);
+        buf.put_fixed_i16(self.fuel_rate, 0.1);
+        buf.put_opt_u32(self.total_engine_hours.map(|d| d.as_secs() as u32));
+        buf.put_fixed_u16(self.coolant_pressure, 100.0);
+        buf.put_fixed_u16(self.fuel_pressure, 1000.0);
+        buf.put_reserved(1);
+        buf.put_u16(self.discrete_status_1.into());
+        buf.put_u16(self.discrete_status_2.into());
+        buf.put_fixed_i8(self.engine_load, 1.0);
+        buf.put_fixed_i8(self.engine_torque, 1.0);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        let instance = data.get_u8().into();
+        let oil_pressure = data.get_fixed_u16(100.0);
+        let oil_temperature = data.get_fixed_u16(0.1);
+        let temperature = data.get_fixed_u16(0.01);
+        let alternator_potential = data.get_fixed_i16(0.01);
+        let fuel_rate = data.get_fixed_i16(0.1);
+        let total_engine_hours = data
+            .get_opt_u32()
+            .map(|t| Duration::from_secs(u64::from(t)));
+        let coolant_pressure = data.get_fixed_u16(100.0);
+        let fuel_pressure = data.get_fixed_u16(1000.0);
+        data.advance(1);
+        let discrete_status_1 = data.get_u16().into();
+        let discrete_status_2 = data.get_u16().into();
+        let engine_load = data.get_fixed_i8(1.0);
+        let engine_torque = data.get_fixed_i8(1.0);
+
+        Ok(Self {
+            instance,
+            oil_pressure,
+            oil_temperature,
+            temperature,
+            alternator_potential,
+            fuel_rate,
+            total_engine_hours,
+            coolant_pressure,
+            fuel_pressure,
+            discrete_status_1,
+            discrete_status_2,
+            engine_load,
+            engine_torque,
+        })
+    }
+}
+
+/// PGN 127493, Transmission Parameters, Dynamic.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TransmissionParametersDynamic {
+    pub instance: EngineInstance,
+    pub transmission_gear: GearStatus,
+    /// Oil pressure in pascals.
+    pub oil_pressure: Option<f32>,
+    /// Oil temperature in kelvin.
+    pub oil_temperature: Option<f32>,
+    pub discrete_status_1: TransmissionStatus,
+}
+
+impl Message for TransmissionParametersDynamic {
+    const PGN: u32 = 127493;
+
+    type EncodedLen = typenum::U8;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_u8(self.instance.into());
+        buf.put_u8((u8::from(self.transmission_gear) & 0x03) | 0xfc);
+        buf.put_fixed_u16(self.oil_pressure, 100.0);
+        buf.put_fixed_u16(self.oil_temperature, 0.1);
+        buf.put_u8(self.discrete_status_1.into());
+        buf.put_reserved(1);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        let instance = data.get_u8().into();
+        let b = data.get_u8();
+        let transmission_gear = (b & 0x03).into();
+        let oil_pressure = data.get_fixed_u16(100.0);
+        let oil_temperature = data.get_fixed_u16(0.1);
+        let discrete_status_1 = data.get_u8().into();
+        data.advance(1);
+
+        Ok(Self {
+            instance,
+            transmission_gear,
+            oil_pressure,
+            oil_temperature,
+            discrete_status_1,
+        })
+    }
+}
+
+/// PGN 127496, Trip Parameters, Vessel. This is a fast packet.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TripParametersVessel {
+    pub time_to_empty: Option<Duration>,
+    /// Distance to empty in meters.
+    pub distance_to_empty: Option<f64>,
+    /// Estimated fuel remaining in liters.
+    pub estimated_fuel_remaining: Option<f32>,
+    pub trip_run_time: Option<Duration>,
+}
+
+impl Message for TripParametersVessel {
+    const PGN: u32 = 127496;
+
+    type EncodedLen = typenum::U14;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_opt_u32(self.time_to_empty.map(|d| d.as_millis() as u32));
+        buf.put_fixed_u32(self.distance_to_empty, 0.01);
+        buf.put_fixed_u16(self.estimated_fuel_remaining, 1.0);
+        buf.put_opt_u32(self.trip_run_time.map(|d| d.as_millis() as u32));
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        let time_to_empty = data
+            .get_opt_u32()
+            .map(|t| Duration::from_millis(u64::from(t)));
+        let distance_to_empty = data.get_fixed_u32(0.01);
+        let estimated_fuel_remaining = data.get_fixed_u16(1.0);
+        let trip_run_time = data
+            .get_opt_u32()
+            .map(|t| Duration::from_millis(u64::from(t)));
+
+        Ok(Self {
+            time_to_empty,
+            distance_to_empty,
+            estimated_fuel_remaining,
+            trip_run_time,
+        })
+    }
+}
+
+/// PGN 127497, Trip Parameters, Engine. This is a fast packet.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TripParametersEngine {
+    pub instance: EngineInstance,
+    /// Trip fuel used in liters.
+    pub trip_fuel_used: Option<f32>,
+    /// Fuel rate, average in liters per hour.
+    pub fuel_rate_average: Option<f32>,
+    /// Fuel rate, economy in liters per hour.
+    pub fuel_rate_economy: Option<f32>,
+    /// Instantaneous fuel economy in liters per hour.
+    pub instantaneous_fuel_economy: Option<f32>,
+}
+
+impl Message for TripParametersEngine {
+    const PGN: u32 = 127497;
+
+    type EncodedLen = typenum::U9;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_u8(self.instance.into());
+        buf.put_fixed_u16(self.trip_fuel_used, 1.0);
+        buf.put_fixed_i16(self.fuel_rate_average, 0.1);
+        buf.put_fixed_i16(self.fuel_rate_economy, 0.1);
+        buf.put_fixed_i16(self.instantaneous_fuel_economy, 0.1);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        let instance = data.get_u8().into();
+        let trip_fuel_used = data.get_fixed_u16(1.0);
+        let fuel_rate_average = data.get_fixed_i16(0.1);
+        let fuel_rate_economy = data.get_fixed_i16(0.1);
+        let instantaneous_fuel_economy = data.get_fixed_i16(0.1);
+
+        Ok(Self {
+            instance,
+            trip_fuel_used,
+            fuel_rate_average,
+            fuel_rate_economy,
+            instantaneous_fuel_economy,
+        })
+    }
+}
+
+/// PGN 127498, Engine Parameters, Static. This is a fast packet.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct EngineParametersStatic {
+    pub instance: EngineInstance,
+    /// Rated engine speed in rpm.
+    pub rated_engine_speed: Option<f32>,
+    pub vin: heapless::String<32>,
+    pub software_id: heapless::String<32>,
+}
+
+impl EngineParametersStatic {
+    const FIXED_LEN: usize = 3;
+}
+
+impl Message for EngineParametersStatic {
+    const PGN: u32 = 127498;
+
+    type EncodedLen = typenum::U71;
+
+    type DecodeError = ();
+
+    fn encoded_len(&self) -> usize {
+        Self::FIXED_LEN + lau_str_len(&self.vin) + lau_str_len(&self.software_id)
+    }
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_u8(self.instance.into());
+        buf.put_fixed_u16(self.rated_engine_speed, 0.25);
+        put_lau_str(&mut buf, &self.vin);
+        put_lau_str(&mut buf, &self.software_id);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::FIXED_LEN {
+            return Err(());
+        }
+
+        let instance = data.get_u8().into();
+        let rated_engine_speed = data.get_fixed_u16(0.25);
+        let vin = get_lau_str(&mut data).ok_or(())?;
+        let software_id = get_lau_str(&mut data).ok_or(())?;
+
+        Ok(Self {
+            instance,
+            rated_engine_speed,
+            vin,
+            software_id,
+        })
+    }
+}
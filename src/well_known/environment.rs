@@ -0,0 +1,594 @@
+//! Wind, temperature, humidity and pressure measurements.
+
+use core::time::Duration;
+
+use generic_array::typenum::{self, Unsigned};
+
+use super::{get_lau_str, get_time, lau_str_len, put_lau_str, put_time, ResidualMode};
+use crate::{Buf, BufMut, Message};
+
+lookup! {
+    /// What a wind angle and speed are relative to.
+    pub enum WindReference: u8 {
+        /// True wind, ground referenced to true north.
+        TrueNorth = 0,
+        /// True wind, ground referenced to magnetic north.
+        Magnetic = 1,
+        Apparent = 2,
+        /// True wind, referenced to the heading of the boat.
+        TrueBoat = 3,
+        /// True wind, referenced to the water.
+        TrueWater = 4,
+    }
+}
+
+lookup! {
+    /// Where a temperature is measured.
+    pub enum TemperatureSource: u8 {
+        Sea = 0,
+        Outside = 1,
+        Inside = 2,
+        EngineRoom = 3,
+        MainCabin = 4,
+        LiveWell = 5,
+        BaitWell = 6,
+        Refrigeration = 7,
+        HeatingSystem = 8,
+        DewPoint = 9,
+        ApparentWindChill = 10,
+        TheoreticalWindChill = 11,
+        HeatIndex = 12,
+        Freezer = 13,
+        ExhaustGas = 14,
+        ShaftSeal = 15,
+    }
+}
+
+lookup! {
+    /// Where a humidity is measured.
+    pub enum HumiditySource: u8 {
+        Inside = 0,
+        Outside = 1,
+    }
+}
+
+lookup! {
+    /// What a pressure is measured in.
+    pub enum PressureSource: u8 {
+        Atmospheric = 0,
+        Water = 1,
+        Steam = 2,
+        CompressedAir = 3,
+        Hydraulic = 4,
+        Filter = 5,
+        AltimeterSetting = 6,
+        Oil = 7,
+        Fuel = 8,
+    }
+}
+
+/// PGN 130306, wind speed and angle.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct WindData {
+    /// Sequence ID, used to tie together related messages.
+    pub sid: Option<u8>,
+    /// Wind speed in m/s.
+    pub speed: Option<f32>,
+    /// Wind angle in radians.
+    pub angle: Option<f32>,
+    pub reference: WindReference,
+}
+
+impl Message for WindData {
+    const PGN: u32 = 130306;
+
+    type EncodedLen = typenum::U8;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_opt_u8(self.sid);
+        buf.put_fixed_u16(self.speed, 0.01);
+        buf.put_fixed_u16(self.angle, 1e-4);
+        buf.put_u8(0xf8 | (u8::from(self.reference) & 0x07));
+        buf.put_reserved(2);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        Ok(Self {
+            sid: data.get_opt_u8(),
+            speed: data.get_fixed_u16(0.01),
+            angle: data.get_fixed_u16(1e-4),
+            reference: (data.get_u8() & 0x07).into(),
+        })
+    }
+}
+
+/// PGN 130310, water and air temperature and atmospheric pressure. This has
+/// been superseded by [`EnvironmentalParameters`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct OutsideEnvironmentalParameters {
+    pub sid: Option<u8>,
+    /// Water temperature in kelvin.
+    pub water_temperature: Option<f32>,
+    /// Outside air temperature in kelvin.
+    pub air_temperature: Option<f32>,
+    /// Atmospheric pressure in pascals.
+    pub atmospheric_pressure: Option<f32>,
+}
+
+impl Message for OutsideEnvironmentalParameters {
+    const PGN: u32 = 130310;
+
+    type EncodedLen = typenum::U8;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_opt_u8(self.sid);
+        buf.put_fixed_u16(self.water_temperature, 0.01);
+        buf.put_fixed_u16(self.air_temperature, 0.01);
+        buf.put_fixed_u16(self.atmospheric_pressure, 100.0);
+        buf.put_reserved(1);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        Ok(Self {
+            sid: data.get_opt_u8(),
+            water_temperature: data.get_fixed_u16(0.01),
+            air_temperature: data.get_fixed_u16(0.01),
+            atmospheric_pressure: data.get_fixed_u16(100.0),
+        })
+    }
+}
+
+/// PGN 130311, temperature, humidity and atmospheric pressure.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct EnvironmentalParameters {
+    pub sid: Option<u8>,
+    pub temperature_source: TemperatureSource,
+    pub humidity_source: HumiditySource,
+    /// Temperature in kelvin.
+    pub temperature: Option<f32>,
+    /// Relative humidity in percent.
+    pub humidity: Option<f32>,
+    /// Atmospheric pressure in pascals.
+    pub atmospheric_pressure: Option<f32>,
+}
+
+impl Message for EnvironmentalParameters {
+    const PGN: u32 = 130311;
+
+    type EncodedLen = typenum::U8;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_opt_u8(self.sid);
+        buf.put_u8(
+            (u8::from(self.temperature_source) & 0x3f) | (u8::from(self.humidity_source) << 6),
+        );
+        buf.put_fixed_u16(self.temperature, 0.01);
+        buf.put_fixed_i16(self.humidity, 0.004);
+        buf.put_fixed_u16(self.atmospheric_pressure, 100.0);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        let sid = data.get_opt_u8();
+        let sources = data.get_u8();
+
+        Ok(Self {
+            sid,
+            temperature_source: (sources & 0x3f).into(),
+            humidity_source: (sources >> 6).into(),
+            temperature: data.get_fixed_u16(0.01),
+            humidity: data.get_fixed_i16(0.004),
+            atmospheric_pressure: data.get_fixed_u16(100.0),
+        })
+    }
+}
+
+/// PGN 130312, a temperature and its set point. This has been superseded by
+/// [`TemperatureExtendedRange`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Temperature {
+    pub sid: Option<u8>,
+    pub instance: u8,
+    pub source: TemperatureSource,
+    /// Temperature in kelvin.
+    pub actual: Option<f32>,
+    /// Set temperature in kelvin.
+    pub set: Option<f32>,
+}
+
+impl Message for Temperature {
+    const PGN: u32 = 130312;
+
+    type EncodedLen = typenum::U8;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_opt_u8(self.sid);
+        buf.put_u8(self.instance);
+        buf.put_u8(self.source.into());
+        buf.put_fixed_u16(self.actual, 0.01);
+        buf.put_fixed_u16(self.set, 0.01);
+        buf.put_reserved(1);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        Ok(Self {
+            sid: data.get_opt_u8(),
+            instance: data.get_u8(),
+            source: data.get_u8().into(),
+            actual: data.get_fixed_u16(0.01),
+            set: data.get_fixed_u16(0.01),
+        })
+    }
+}
+
+/// PGN 130313, a relative humidity and its set point.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Humidity {
+    pub sid: Option<u8>,
+    pub instance: u8,
+    pub source: HumiditySource,
+    /// Relative humidity in percent.
+    pub actual: Option<f32>,
+    /// Set humidity in percent.
+    pub set: Option<f32>,
+}
+
+impl Message for Humidity {
+    const PGN: u32 = 130313;
+
+    type EncodedLen = typenum::U8;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_opt_u8(self.sid);
+        buf.put_u8(self.instance);
+        buf.put_u8(self.source.into());
+        buf.put_fixed_i16(self.actual, 0.004);
+        buf.put_fixed_i16(self.set, 0.004);
+        buf.put_reserved(1);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        Ok(Self {
+            sid: data.get_opt_u8(),
+            instance: data.get_u8(),
+            source: data.get_u8().into(),
+            actual: data.get_fixed_i16(0.004),
+            set: data.get_fixed_i16(0.004),
+        })
+    }
+}
+
+/// PGN 130314, a measured pressure.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ActualPressure {
+    pub sid: Option<u8>,
+    pub instance: u8,
+    pub source: PressureSource,
+    /// Pressure in pascals.
+    pub pressure: Option<f32>,
+}
+
+impl Message for ActualPressure {
+    const PGN: u32 = 130314;
+
+    type EncodedLen = typenum::U8;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_opt_u8(self.sid);
+        buf.put_u8(self.instance);
+        buf.put_u8(self.source.into());
+        buf.put_fixed_i32(self.pressure.map(f64::from), 0.1);
+        buf.put_reserved(1);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        Ok(Self {
+            sid: data.get_opt_u8(),
+            instance: data.get_u8(),
+            source: data.get_u8().into(),
+            pressure: data.get_fixed_i32(0.1).map(|p| p as f32),
+        })
+    }
+}
+
+/// PGN 130315, a pressure set point.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SetPressure {
+    pub sid: Option<u8>,
+    pub instance: u8,
+    pub source: PressureSource,
+    /// Pressure in pascals.
+    pub pressure: Option<f32>,
+}
+
+impl Message for SetPressure {
+    const PGN: u32 = 130315;
+
+    type EncodedLen = typenum::U8;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_opt_u8(self.sid);
+        buf.put_u8(self.instance);
+        buf.put_u8(self.source.into());
+        buf.put_fixed_u32(self.pressure.map(f64::from), 0.1);
+        buf.put_reserved(1);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        Ok(Self {
+            sid: data.get_opt_u8(),
+            instance: data.get_u8(),
+            source: data.get_u8().into(),
+            pressure: data.get_fixed_u32(0.1).map(|p| p as f32),
+        })
+    }
+}
+
+/// PGN 130316, a temperature with a higher resolution and range than
+/// [`Temperature`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TemperatureExtendedRange {
+    pub sid: Option<u8>,
+    pub instance: u8,
+    pub source: TemperatureSource,
+    /// Temperature in kelvin, with a resolution of 0.001 K.
+    pub actual: Option<f32>,
+    /// Set temperature in kelvin, with a resolution of 0.1 K.
+    pub set: Option<f32>,
+}
+
+impl Message for TemperatureExtendedRange {
+    const PGN: u32 = 130316;
+
+    type EncodedLen = typenum::U8;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_opt_u8(self.sid);
+        buf.put_u8(self.instance);
+        buf.put_u8(self.source.into());
+        buf.put_fixed_u24(self.actual, 0.001);
+        buf.put_fixed_u16(self.set, 0.1);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        Ok(Self {
+            sid: data.get_opt_u8(),
+            instance: data.get_u8(),
+            source: data.get_u8().into(),
+            actual: data.get_fixed_u24(0.001),
+            set: data.get_fixed_u16(0.1),
+        })
+    }
+}
+
+/// The maximum length in bytes of the station ID and name in
+/// [`MeteorologicalStationData`].
+pub const MAX_STATION_STR_LEN: usize = 32;
+
+/// PGN 130323, a report from a weather station. This is a fast packet.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct MeteorologicalStationData {
+    pub mode: ResidualMode,
+    /// Days since 1970-01-01.
+    pub date: Option<u16>,
+    /// Time since midnight (UTC).
+    pub time: Option<Duration>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    /// Wind speed in m/s.
+    pub wind_speed: Option<f32>,
+    /// Wind direction in radians.
+    pub wind_direction: Option<f32>,
+    pub wind_reference: WindReference,
+    /// Wind gusts in m/s.
+    pub wind_gusts: Option<f32>,
+    /// Atmospheric pressure in pascals.
+    pub atmospheric_pressure: Option<f32>,
+    /// Ambient temperature in kelvin.
+    pub ambient_temperature: Option<f32>,
+    pub station_id: heapless::String<MAX_STATION_STR_LEN>,
+    pub station_name: heapless::String<MAX_STATION_STR_LEN>,
+}
+
+impl MeteorologicalStationData {
+    const FIXED_LEN: usize = 26;
+}
+
+impl Message for MeteorologicalStationData {
+    const PGN: u32 = 130323;
+
+    // 26 + 2 * (2 + MAX_STATION_STR_LEN)
+    type EncodedLen = typenum::U94;
+
+    type DecodeError = ();
+
+    fn encoded_len(&self) -> usize {
+        Self::FIXED_LEN + lau_str_len(&self.station_id) + lau_str_len(&self.station_name)
+    }
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_u8(0xf0 | (u8::from(self.mode) & 0x0f));
+        buf.put_opt_u16(self.date);
+        put_time(&mut buf, self.time);
+        buf.put_fixed_i32(self.latitude, 1e-7);
+        buf.put_fixed_i32(self.longitude, 1e-7);
+        buf.put_fixed_u16(self.wind_speed, 0.01);
+        buf.put_fixed_u16(self.wind_direction, 1e-4);
+        buf.put_u8(0xf8 | (u8::from(self.wind_reference) & 0x07));
+        buf.put_fixed_u16(self.wind_gusts, 0.01);
+        buf.put_fixed_u16(self.atmospheric_pressure, 100.0);
+        buf.put_fixed_u16(self.ambient_temperature, 0.01);
+        put_lau_str(&mut buf, &self.station_id);
+        put_lau_str(&mut buf, &self.station_name);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::FIXED_LEN {
+            return Err(());
+        }
+
+        Ok(Self {
+            mode: (data.get_u8() & 0x0f).into(),
+            date: data.get_opt_u16(),
+            time: get_time(&mut data),
+            latitude: data.get_fixed_i32(1e-7),
+            longitude: data.get_fixed_i32(1e-7),
+            wind_speed: data.get_fixed_u16(0.01),
+            wind_direction: data.get_fixed_u16(1e-4),
+            wind_reference: (data.get_u8() & 0x07).into(),
+            wind_gusts: data.get_fixed_u16(0.01),
+            atmospheric_pressure: data.get_fixed_u16(100.0),
+            ambient_temperature: data.get_fixed_u16(0.01),
+            station_id: get_lau_str(&mut data).ok_or(())?,
+            station_name: get_lau_str(&mut data).ok_or(())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::well_known::round_trip;
+
+    #[test]
+    fn wind_data() {
+        let msg: WindData = round_trip(&[0x00, 0x34, 0x03, 0x1a, 0x4f, 0xfa, 0xff, 0xff]);
+
+        assert_eq!(msg.sid, Some(0));
+        assert!((msg.speed.unwrap() - 8.2).abs() < 1e-6);
+        assert!((msg.angle.unwrap() - 2.025).abs() < 1e-6);
+        assert_eq!(msg.reference, WindReference::Apparent);
+    }
+
+    #[test]
+    fn environmental_parameters() {
+        let msg: EnvironmentalParameters =
+            round_trip(&[0x01, 0x41, 0xbd, 0x73, 0xb8, 0x2e, 0xf5, 0x03]);
+
+        assert_eq!(msg.temperature_source, TemperatureSource::Outside);
+        assert_eq!(msg.humidity_source, HumiditySource::Outside);
+        assert!((msg.temperature.unwrap() - 296.29).abs() < 1e-3);
+        assert!((msg.humidity.unwrap() - 47.84).abs() < 1e-3);
+        assert_eq!(msg.atmospheric_pressure, Some(101300.0));
+    }
+
+    #[test]
+    fn unknown_sources() {
+        let msg: Temperature = round_trip(&[0xff, 0x00, 0x2a, 0xff, 0xff, 0xff, 0xff, 0xff]);
+
+        assert_eq!(msg.source, TemperatureSource::Unknown(42));
+        assert_eq!(msg.actual, None);
+
+        let msg: Humidity = round_trip(&[0xff, 0x00, 0x07, 0xff, 0x7f, 0xff, 0x7f, 0xff]);
+
+        assert_eq!(msg.source, HumiditySource::Unknown(7));
+    }
+
+    #[test]
+    fn actual_pressure() {
+        let msg: ActualPressure = round_trip(&[0x02, 0x01, 0x00, 0x0a, 0x76, 0x0f, 0x00, 0xff]);
+
+        assert_eq!(msg.instance, 1);
+        assert_eq!(msg.source, PressureSource::Atmospheric);
+        assert!((msg.pressure.unwrap() - 101325.8).abs() < 1e-2);
+    }
+
+    #[test]
+    fn temperature_extended_range() {
+        let msg: TemperatureExtendedRange =
+            round_trip(&[0x03, 0x00, 0x0e, 0x20, 0x24, 0x0b, 0x6e, 0x1d]);
+
+        assert_eq!(msg.source, TemperatureSource::ExhaustGas);
+        assert!((msg.actual.unwrap() - 730.144).abs() < 1e-3);
+        assert!((msg.set.unwrap() - 753.4).abs() < 1e-3);
+    }
+
+    #[test]
+    fn meteorological_station_data() {
+        let msg: MeteorologicalStationData = round_trip(&[
+            0xf0, 0x38, 0x4a, 0x58, 0xba, 0xff, 0x1a, 0x87, 0x1c, 0xd6, 0x23, 0x95, 0x47, 0x08,
+            0xb7, 0x34, 0x03, 0x1a, 0x4f, 0xf8, 0xe8, 0x03, 0xf5, 0x03, 0xbd, 0x73, 0x05, 0x01,
+            0x53, 0x46, 0x4f, 0x0b, 0x01, 0x53, 0x46, 0x20, 0x42, 0x61, 0x79, 0x20, 0x57, 0x58,
+        ]);
+
+        assert_eq!(msg.mode, ResidualMode::Autonomous);
+        assert_eq!(msg.date, Some(19000));
+        assert!((msg.wind_gusts.unwrap() - 10.0).abs() < 1e-6);
+        assert_eq!(msg.wind_reference, WindReference::TrueNorth);
+        assert_eq!(msg.station_id, "SFO");
+        assert_eq!(msg.station_name, "SF Bay WX");
+    }
+
+    #[test]
+    fn utf16_station_name() {
+        let mut data = [0xff; 34];
+        data[..26].copy_from_slice(&[
+            0xf4, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f, 0xff, 0xff, 0xff,
+            0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        ]);
+        data[26..].copy_from_slice(&[0x02, 0x01, 0x06, 0x00, 0x53, 0x00, 0xd6, 0x00]);
+
+        let msg = MeteorologicalStationData::decode(&data).unwrap();
+
+        assert_eq!(msg.mode, ResidualMode::Manual);
+        assert_eq!(msg.latitude, None);
+        assert_eq!(msg.station_id, "");
+        assert_eq!(msg.station_name, "SÖ");
+    }
+}
@@ -0,0 +1,104 @@
+//! Tank levels.
+
+use generic_array::typenum::{self, Unsigned};
+
+use crate::{Buf, BufMut, Message};
+
+lookup! {
+    /// What a tank contains.
+    pub enum FluidType: u8 {
+        Fuel = 0,
+        FreshWater = 1,
+        /// Gray water.
+        WasteWater = 2,
+        LiveWell = 3,
+        Oil = 4,
+        BlackWater = 5,
+        Gasoline = 6,
+    }
+}
+
+/// PGN 127505, the level of a tank.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FluidLevel {
+    /// 4-bit tank instance, unique per fluid type.
+    pub instance: u8,
+    pub fluid_type: FluidType,
+    /// Level in percent of the capacity.
+    pub level: Option<f32>,
+    /// Capacity in liters.
+    pub capacity: Option<f32>,
+}
+
+impl Message for FluidLevel {
+    const PGN: u32 = 127505;
+
+    type EncodedLen = typenum::U8;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_u8((self.instance & 0x0f) | (u8::from(self.fluid_type) << 4));
+        buf.put_fixed_i16(self.level, 0.004);
+        buf.put_fixed_u32(self.capacity.map(f64::from), 0.1);
+        buf.put_reserved(1);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        let instance_type = data.get_u8();
+
+        Ok(Self {
+            instance: instance_type & 0x0f,
+            fluid_type: (instance_type >> 4).into(),
+            level: data.get_fixed_i16(0.004),
+            capacity: data.get_fixed_u32(0.1).map(|c| c as f32),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::well_known::round_trip;
+
+    #[test]
+    fn fluid_level() {
+        let msg: FluidLevel = round_trip(&[0x01, 0x8a, 0x2f, 0xd0, 0x07, 0x00, 0x00, 0xff]);
+
+        assert_eq!(msg.instance, 1);
+        assert_eq!(msg.fluid_type, FluidType::Fuel);
+        assert!((msg.level.unwrap() - 48.68).abs() < 1e-3);
+        assert_eq!(msg.capacity, Some(200.0));
+
+        let msg: FluidLevel = round_trip(&[0x52, 0xff, 0x7f, 0xff, 0xff, 0xff, 0xff, 0xff]);
+
+        assert_eq!(msg.instance, 2);
+        assert_eq!(msg.fluid_type, FluidType::BlackWater);
+        assert_eq!(msg.level, None);
+        assert_eq!(msg.capacity, None);
+    }
+
+    #[test]
+    fn encode_fluid_level() {
+        let msg = FluidLevel {
+            instance: 0,
+            fluid_type: FluidType::Unknown(14),
+            level: Some(100.0),
+            capacity: Some(75.5),
+        };
+
+        let mut buf = [0; 8];
+        msg.encode(&mut buf);
+
+        assert_eq!(buf, [0xe0, 0xa8, 0x61, 0xf3, 0x02, 0x00, 0x00, 0xff]);
+        assert_eq!(
+            round_trip::<FluidLevel>(&buf).fluid_type,
+            FluidType::Unknown(14)
+        );
+    }
+}
@@ -0,0 +1,675 @@
+//! Position, time and satellite information from GNSS receivers.
+
+use core::time::Duration;
+
+use generic_array::typenum::{self, Unsigned};
+
+use super::{get_fixed_str, get_time, put_fixed_str, put_time, DirectionReference};
+use crate::{Buf, BufMut, Message};
+
+lookup! {
+    /// The satellite system(s) used for a fix.
+    pub enum GnssType: u8 {
+        Gps = 0,
+        Glonass = 1,
+        GpsGlonass = 2,
+        GpsSbasWaas = 3,
+        GpsSbasWaasGlonass = 4,
+        Chayka = 5,
+        Integrated = 6,
+        Surveyed = 7,
+        Galileo = 8,
+    }
+}
+
+lookup! {
+    pub enum GnssMethod: u8 {
+        NoGnss = 0,
+        GnssFix = 1,
+        DgnssFix = 2,
+        PreciseGnss = 3,
+        RtkFixedInteger = 4,
+        RtkFloat = 5,
+        DeadReckoning = 6,
+        ManualInput = 7,
+        Simulator = 8,
+    }
+}
+
+lookup! {
+    pub enum GnssIntegrity: u8 {
+        NoChecking = 0,
+        Safe = 1,
+        Caution = 2,
+    }
+}
+
+lookup! {
+    pub enum GnssMode: u8 {
+        OneDimensional = 0,
+        TwoDimensional = 1,
+        ThreeDimensional = 2,
+        Auto = 3,
+    }
+}
+
+lookup! {
+    pub enum RangeResidualMode: u8 {
+        /// The range residuals were used to calculate the position.
+        UsedInCalculation = 0,
+        /// The range residuals were calculated after the position.
+        CalculatedAfterPosition = 1,
+    }
+}
+
+lookup! {
+    pub enum SatelliteStatus: u8 {
+        NotTracked = 0,
+        Tracked = 1,
+        Used = 2,
+        NotTrackedDiff = 3,
+        TrackedDiff = 4,
+        UsedDiff = 5,
+    }
+}
+
+/// PGN 129025. A high-frequency position update with less precision than
+/// [`GnssPositionData`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PositionRapidUpdate {
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+}
+
+impl Message for PositionRapidUpdate {
+    const PGN: u32 = 129025;
+
+    type EncodedLen = typenum::U8;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_fixed_i32(self.latitude, 1e-7);
+        buf.put_fixed_i32(self.longitude, 1e-7);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        Ok(Self {
+            latitude: data.get_fixed_i32(1e-7),
+            longitude: data.get_fixed_i32(1e-7),
+        })
+    }
+}
+
+/// PGN 129026, course and speed over ground.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CogSogRapidUpdate {
+    /// Sequence ID, used to tie together related messages.
+    pub sid: Option<u8>,
+    pub cog_reference: DirectionReference,
+    /// Course over ground in radians.
+    pub cog: Option<f32>,
+    /// Speed over ground in m/s.
+    pub sog: Option<f32>,
+}
+
+impl Message for CogSogRapidUpdate {
+    const PGN: u32 = 129026;
+
+    type EncodedLen = typenum::U8;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_opt_u8(self.sid);
+        buf.put_u8(0xfc | (u8::from(self.cog_reference) & 0x03));
+        buf.put_fixed_u16(self.cog, 1e-4);
+        buf.put_fixed_u16(self.sog, 0.01);
+        buf.put_reserved(2);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        Ok(Self {
+            sid: data.get_opt_u8(),
+            cog_reference: (data.get_u8() & 0x03).into(),
+            cog: data.get_fixed_u16(1e-4),
+            sog: data.get_fixed_u16(0.01),
+        })
+    }
+}
+
+/// A differential reference station in [`GnssPositionData`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ReferenceStation {
+    pub kind: GnssType,
+    /// 12-bit station ID.
+    pub id: Option<u16>,
+    /// Age of the DGNSS corrections in seconds.
+    pub age: Option<f32>,
+}
+
+/// The maximum number of reference stations in [`GnssPositionData`] that
+/// this crate can handle. Messages with more are not decoded.
+pub const MAX_REFERENCE_STATIONS: usize = 8;
+
+/// PGN 129029, a complete GNSS fix. This is a fast packet.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GnssPositionData {
+    pub sid: Option<u8>,
+    /// Days since 1970-01-01.
+    pub date: Option<u16>,
+    /// Time since midnight (UTC).
+    pub time: Option<Duration>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    /// Altitude in meters, referenced to the WGS-84 ellipsoid.
+    pub altitude: Option<f64>,
+    pub system: GnssType,
+    pub method: GnssMethod,
+    pub integrity: GnssIntegrity,
+    /// Number of satellites used for the fix.
+    pub satellites: Option<u8>,
+    pub hdop: Option<f32>,
+    pub pdop: Option<f32>,
+    /// The difference between the WGS-84 ellipsoid and mean sea level, in
+    /// meters.
+    pub geoidal_separation: Option<f32>,
+    pub reference_stations: heapless::Vec<ReferenceStation, MAX_REFERENCE_STATIONS>,
+}
+
+impl GnssPositionData {
+    const FIXED_LEN: usize = 43;
+}
+
+impl Message for GnssPositionData {
+    const PGN: u32 = 129029;
+
+    // 43 + 4 * MAX_REFERENCE_STATIONS
+    type EncodedLen = typenum::U75;
+
+    type DecodeError = ();
+
+    fn encoded_len(&self) -> usize {
+        Self::FIXED_LEN + 4 * self.reference_stations.len()
+    }
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_opt_u8(self.sid);
+        buf.put_opt_u16(self.date);
+        put_time(&mut buf, self.time);
+        buf.put_fixed_i64(self.latitude, 1e-16);
+        buf.put_fixed_i64(self.longitude, 1e-16);
+        buf.put_fixed_i64(self.altitude, 1e-6);
+        buf.put_u8((u8::from(self.system) & 0x0f) | (u8::from(self.method) << 4));
+        buf.put_u8(0xfc | (u8::from(self.integrity) & 0x03));
+        buf.put_opt_u8(self.satellites);
+        buf.put_fixed_i16(self.hdop, 0.01);
+        buf.put_fixed_i16(self.pdop, 0.01);
+        buf.put_fixed_i32(self.geoidal_separation.map(f64::from), 0.01);
+        buf.put_u8(self.reference_stations.len() as u8);
+
+        for station in &self.reference_stations {
+            buf.put_u16(
+                u16::from(u8::from(station.kind) & 0x0f) | (station.id.unwrap_or(0xfff) << 4),
+            );
+            buf.put_fixed_u16(station.age, 0.01);
+        }
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::FIXED_LEN {
+            return Err(());
+        }
+
+        let sid = data.get_opt_u8();
+        let date = data.get_opt_u16();
+        let time = get_time(&mut data);
+        let latitude = data.get_fixed_i64(1e-16);
+        let longitude = data.get_fixed_i64(1e-16);
+        let altitude = data.get_fixed_i64(1e-6);
+        let system_method = data.get_u8();
+        let integrity = (data.get_u8() & 0x03).into();
+        let satellites = data.get_opt_u8();
+        let hdop = data.get_fixed_i16(0.01);
+        let pdop = data.get_fixed_i16(0.01);
+        let geoidal_separation = data.get_fixed_i32(0.01).map(|v| v as f32);
+
+        let n = match data.get_u8() {
+            0xff => 0,
+            n => usize::from(n),
+        };
+
+        if n > MAX_REFERENCE_STATIONS || data.remaining() < 4 * n {
+            return Err(());
+        }
+
+        let mut reference_stations = heapless::Vec::new();
+
+        for _ in 0..n {
+            let kind_id = data.get_u16();
+            let station = ReferenceStation {
+                kind: ((kind_id & 0x0f) as u8).into(),
+                id: match kind_id >> 4 {
+                    0xfff => None,
+                    id => Some(id),
+                },
+                age: data.get_fixed_u16(0.01),
+            };
+
+            // cannot fail since n <= MAX_REFERENCE_STATIONS
+            let _ = reference_stations.push(station);
+        }
+
+        Ok(Self {
+            sid,
+            date,
+            time,
+            latitude,
+            longitude,
+            altitude,
+            system: (system_method & 0x0f).into(),
+            method: (system_method >> 4).into(),
+            integrity,
+            satellites,
+            hdop,
+            pdop,
+            geoidal_separation,
+            reference_stations,
+        })
+    }
+}
+
+/// PGN 129033, the current UTC date and time along with the local offset.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TimeDate {
+    /// Days since 1970-01-01.
+    pub date: Option<u16>,
+    /// Time since midnight (UTC).
+    pub time: Option<Duration>,
+    /// Offset of the local time from UTC, in minutes.
+    pub local_offset: Option<i16>,
+}
+
+impl Message for TimeDate {
+    const PGN: u32 = 129033;
+
+    type EncodedLen = typenum::U8;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_opt_u16(self.date);
+        put_time(&mut buf, self.time);
+        buf.put_i16(self.local_offset.unwrap_or(i16::MAX));
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        Ok(Self {
+            date: data.get_opt_u16(),
+            time: get_time(&mut data),
+            local_offset: match data.get_i16() {
+                i16::MAX => None,
+                offset => Some(offset),
+            },
+        })
+    }
+}
+
+/// PGN 129539, dilution of precision.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GnssDops {
+    pub sid: Option<u8>,
+    pub desired_mode: GnssMode,
+    pub actual_mode: GnssMode,
+    pub hdop: Option<f32>,
+    pub vdop: Option<f32>,
+    pub tdop: Option<f32>,
+}
+
+impl Message for GnssDops {
+    const PGN: u32 = 129539;
+
+    type EncodedLen = typenum::U8;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_opt_u8(self.sid);
+        buf.put_u8(
+            0xc0 | (u8::from(self.desired_mode) & 0x07)
+                | ((u8::from(self.actual_mode) & 0x07) << 3),
+        );
+        buf.put_fixed_i16(self.hdop, 0.01);
+        buf.put_fixed_i16(self.vdop, 0.01);
+        buf.put_fixed_i16(self.tdop, 0.01);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        let sid = data.get_opt_u8();
+        let modes = data.get_u8();
+
+        Ok(Self {
+            sid,
+            desired_mode: (modes & 0x07).into(),
+            actual_mode: ((modes >> 3) & 0x07).into(),
+            hdop: data.get_fixed_i16(0.01),
+            vdop: data.get_fixed_i16(0.01),
+            tdop: data.get_fixed_i16(0.01),
+        })
+    }
+}
+
+/// A satellite in [`GnssSatsInView`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Satellite {
+    pub prn: u8,
+    /// Elevation in radians.
+    pub elevation: Option<f32>,
+    /// Azimuth in radians.
+    pub azimuth: Option<f32>,
+    /// Signal to noise ratio in dB.
+    pub snr: Option<f32>,
+    /// Range residuals in meters.
+    pub range_residuals: Option<f32>,
+    pub status: SatelliteStatus,
+}
+
+/// The maximum number of satellites in [`GnssSatsInView`], limited by the
+/// size of a fast packet.
+pub const MAX_SATELLITES: usize = 18;
+
+/// PGN 129540, the satellites currently in view. This is a fast packet.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GnssSatsInView {
+    pub sid: Option<u8>,
+    pub range_residual_mode: RangeResidualMode,
+    pub satellites: heapless::Vec<Satellite, MAX_SATELLITES>,
+}
+
+impl GnssSatsInView {
+    const FIXED_LEN: usize = 3;
+}
+
+impl Message for GnssSatsInView {
+    const PGN: u32 = 129540;
+
+    // 3 + 12 * MAX_SATELLITES
+    type EncodedLen = typenum::U219;
+
+    type DecodeError = ();
+
+    fn encoded_len(&self) -> usize {
+        Self::FIXED_LEN + 12 * self.satellites.len()
+    }
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_opt_u8(self.sid);
+        buf.put_u8(0xfc | (u8::from(self.range_residual_mode) & 0x03));
+        buf.put_u8(self.satellites.len() as u8);
+
+        for sat in &self.satellites {
+            buf.put_u8(sat.prn);
+            buf.put_fixed_i16(sat.elevation, 1e-4);
+            buf.put_fixed_u16(sat.azimuth, 1e-4);
+            buf.put_fixed_u16(sat.snr, 0.01);
+            buf.put_fixed_i32(sat.range_residuals.map(f64::from), 1e-5);
+            buf.put_u8(0xf0 | (u8::from(sat.status) & 0x0f));
+        }
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::FIXED_LEN {
+            return Err(());
+        }
+
+        let sid = data.get_opt_u8();
+        let range_residual_mode = (data.get_u8() & 0x03).into();
+
+        let n = match data.get_u8() {
+            0xff => 0,
+            n => usize::from(n),
+        };
+
+        if n > MAX_SATELLITES || data.remaining() < 12 * n {
+            return Err(());
+        }
+
+        let mut satellites = heapless::Vec::new();
+
+        for _ in 0..n {
+            let sat = Satellite {
+                prn: data.get_u8(),
+                elevation: data.get_fixed_i16(1e-4),
+                azimuth: data.get_fixed_u16(1e-4),
+                snr: data.get_fixed_u16(0.01),
+                range_residuals: data.get_fixed_i32(1e-5).map(|v| v as f32),
+                status: (data.get_u8() & 0x0f).into(),
+            };
+
+            // cannot fail since n <= MAX_SATELLITES
+            let _ = satellites.push(sat);
+        }
+
+        Ok(Self {
+            sid,
+            range_residual_mode,
+            satellites,
+        })
+    }
+}
+
+/// PGN 129044, the datum used for positions and its offset from the
+/// reference datum (usually WGS-84). This is a fast packet.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Datum {
+    /// A datum code such as "W84", as defined by IHO S-60.
+    pub local_datum: heapless::String<4>,
+    pub delta_latitude: Option<f64>,
+    pub delta_longitude: Option<f64>,
+    /// Altitude offset in meters.
+    pub delta_altitude: Option<f32>,
+    pub reference_datum: heapless::String<4>,
+}
+
+impl Message for Datum {
+    const PGN: u32 = 129044;
+
+    type EncodedLen = typenum::U20;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        put_fixed_str(&mut buf, &self.local_datum, 4);
+        buf.put_fixed_i32(self.delta_latitude, 1e-7);
+        buf.put_fixed_i32(self.delta_longitude, 1e-7);
+        buf.put_fixed_i32(self.delta_altitude.map(f64::from), 0.01);
+        put_fixed_str(&mut buf, &self.reference_datum, 4);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        Ok(Self {
+            local_datum: get_fixed_str(&mut data, 4),
+            delta_latitude: data.get_fixed_i32(1e-7),
+            delta_longitude: data.get_fixed_i32(1e-7),
+            delta_altitude: data.get_fixed_i32(0.01).map(|v| v as f32),
+            reference_datum: get_fixed_str(&mut data, 4),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use generic_array::GenericArray;
+
+    use super::*;
+    use crate::{fast_packet, well_known::round_trip, FastPacket};
+
+    #[test]
+    fn position_rapid_update() {
+        let msg: PositionRapidUpdate =
+            round_trip(&[0x87, 0x1c, 0xd6, 0x23, 0x95, 0x47, 0x08, 0xb7]);
+
+        assert!((msg.latitude.unwrap() - 60.1234567).abs() < 1e-9);
+        assert!((msg.longitude.unwrap() + 122.4194155).abs() < 1e-9);
+
+        let na: PositionRapidUpdate = round_trip(&[0xff, 0xff, 0xff, 0x7f, 0xff, 0xff, 0xff, 0x7f]);
+        assert_eq!(na.latitude, None);
+        assert_eq!(na.longitude, None);
+    }
+
+    #[test]
+    fn cog_sog_rapid_update() {
+        let msg: CogSogRapidUpdate = round_trip(&[0x05, 0xfc, 0x5c, 0x3d, 0x02, 0x02, 0xff, 0xff]);
+
+        assert_eq!(msg.sid, Some(5));
+        assert_eq!(msg.cog_reference, DirectionReference::True);
+        assert!((msg.cog.unwrap() - core::f32::consts::FRAC_PI_2).abs() < 1e-4);
+        assert!((msg.sog.unwrap() - 5.14).abs() < 1e-6);
+    }
+
+    const GNSS_POSITION_DATA: [u8; 47] = [
+        0x01, 0x38, 0x4a, 0x58, 0xba, 0xff, 0x1a, 0x00, 0x80, 0x5b, 0xf9, 0x58, 0xdd, 0x41, 0x08,
+        0x00, 0xc0, 0x4e, 0xf4, 0x50, 0x97, 0xd3, 0xff, 0x20, 0xbc, 0xbe, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x23, 0xfd, 0x09, 0x50, 0x00, 0x96, 0x00, 0x21, 0x07, 0x00, 0x00, 0x01, 0xa0, 0x02,
+        0x5e, 0x01,
+    ];
+
+    #[test]
+    fn gnss_position_data() {
+        let msg: GnssPositionData = round_trip(&GNSS_POSITION_DATA);
+
+        assert_eq!(msg.sid, Some(1));
+        assert_eq!(msg.date, Some(19000));
+        assert_eq!(msg.time, Some(Duration::from_millis(45_296_700)));
+        assert_eq!(msg.latitude, Some(59.5));
+        assert_eq!(msg.longitude, Some(-1.25));
+        assert_eq!(msg.altitude, Some(12.5));
+        assert_eq!(msg.system, GnssType::GpsSbasWaas);
+        assert_eq!(msg.method, GnssMethod::DgnssFix);
+        assert_eq!(msg.integrity, GnssIntegrity::Safe);
+        assert_eq!(msg.satellites, Some(9));
+        assert!((msg.hdop.unwrap() - 0.8).abs() < 1e-6);
+        assert!((msg.pdop.unwrap() - 1.5).abs() < 1e-6);
+        assert_eq!(msg.geoidal_separation, Some(18.25));
+        assert_eq!(
+            msg.reference_stations,
+            [ReferenceStation {
+                kind: GnssType::Gps,
+                id: Some(42),
+                age: Some(3.5),
+            }]
+        );
+    }
+
+    #[test]
+    fn gnss_position_data_fast_packet() {
+        let msg = GnssPositionData::decode(&GNSS_POSITION_DATA).unwrap();
+
+        let mut buf = GenericArray::<u8, <GnssPositionData as Message>::EncodedLen>::default();
+        let mut reader = fast_packet::Reader::<GnssPositionData>::new();
+        let mut decoded = None;
+
+        for packet in msg.encode_to_fast_packets(&mut buf, 3) {
+            assert_eq!(decoded, None);
+            decoded = reader.read(FastPacket(packet.0));
+        }
+
+        assert_eq!(decoded, Some(Ok(msg)));
+    }
+
+    #[test]
+    fn time_date() {
+        let msg: TimeDate = round_trip(&[0x38, 0x4a, 0x58, 0xba, 0xff, 0x1a, 0x78, 0x00]);
+
+        assert_eq!(msg.date, Some(19000));
+        assert_eq!(msg.time, Some(Duration::from_millis(45_296_700)));
+        assert_eq!(msg.local_offset, Some(120));
+    }
+
+    #[test]
+    fn gnss_dops() {
+        let msg: GnssDops = round_trip(&[0x07, 0xd3, 0x5a, 0x00, 0x78, 0x00, 0xff, 0x7f]);
+
+        assert_eq!(msg.desired_mode, GnssMode::Auto);
+        assert_eq!(msg.actual_mode, GnssMode::ThreeDimensional);
+        assert!((msg.hdop.unwrap() - 0.9).abs() < 1e-6);
+        assert!((msg.vdop.unwrap() - 1.2).abs() < 1e-6);
+        assert_eq!(msg.tdop, None);
+    }
+
+    #[test]
+    fn gnss_sats_in_view() {
+        let msg: GnssSatsInView = round_trip(&[
+            0x02, 0xfc, 0x02, 0x05, 0xae, 0x1e, 0xb8, 0x7a, 0x9a, 0x10, 0xff, 0xff, 0xff, 0x7f,
+            0xf2, 0x0c, 0x3a, 0x0a, 0xe8, 0x28, 0xd8, 0x0e, 0x6a, 0xff, 0xff, 0xff, 0xf1,
+        ]);
+
+        assert_eq!(
+            msg.range_residual_mode,
+            RangeResidualMode::UsedInCalculation
+        );
+        assert_eq!(msg.satellites.len(), 2);
+        assert_eq!(msg.satellites[0].prn, 5);
+        assert_eq!(msg.satellites[0].snr, Some(42.5));
+        assert_eq!(msg.satellites[0].range_residuals, None);
+        assert_eq!(msg.satellites[0].status, SatelliteStatus::Used);
+        assert_eq!(msg.satellites[1].prn, 12);
+        assert!((msg.satellites[1].range_residuals.unwrap() + 0.0015).abs() < 1e-9);
+        assert_eq!(msg.satellites[1].status, SatelliteStatus::Tracked);
+    }
+
+    #[test]
+    fn datum() {
+        let msg: Datum = round_trip(&[
+            0x57, 0x38, 0x34, 0xff, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x57, 0x38, 0x34, 0xff,
+        ]);
+
+        assert_eq!(msg.local_datum, "W84");
+        assert_eq!(msg.delta_latitude, Some(0.0));
+        assert_eq!(msg.reference_datum, "W84");
+
+        // encoded as Latin-1, one byte per character
+        let msg = Datum {
+            local_datum: heapless::String::try_from("Wé").unwrap(),
+            reference_datum: heapless::String::try_from("W€").unwrap(),
+            ..msg
+        };
+        let mut buf = [0; 20];
+        msg.encode(&mut buf);
+        assert_eq!(buf[..4], [0x57, 0xe9, 0xff, 0xff]);
+        assert_eq!(buf[16..], [0x57, b'?', 0xff, 0xff]);
+        assert_eq!(Datum::decode(&buf).unwrap().local_datum, "Wé");
+    }
+}
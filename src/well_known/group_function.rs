@@ -0,0 +1,624 @@
+//! NMEA Group Function (PGN 126208), used to request messages, to change
+//! the fields of messages sent by other devices and to acknowledge such
+//! requests and commands.
+//!
+//! Group functions reference the fields of another PGN by their number,
+//! starting at 1. Each field value is sent in the whole number of bytes
+//! needed to hold the field, so the length of a value depends on the
+//! referenced PGN. [`Parameters`] therefore keeps the field-value pairs in
+//! their encoded form, and iterating over them requires a function that
+//! returns the length of each field.
+
+use core::time::Duration;
+
+use generic_array::typenum;
+
+use super::Manufacturer;
+use crate::{id::is_proprietary, Buf, BufMut, Message};
+
+lookup! {
+    /// Whether the referenced PGN could be requested or commanded.
+    pub enum PgnErrorCode: u8 {
+        Acknowledge = 0,
+        PgnNotSupported = 1,
+        PgnNotAvailable = 2,
+        AccessDenied = 3,
+        NotSupported = 4,
+        TagNotSupported = 5,
+        ReadOrWriteNotSupported = 6,
+    }
+}
+
+lookup! {
+    /// Whether the requested transmission interval or commanded priority
+    /// could be applied.
+    pub enum TransmissionIntervalErrorCode: u8 {
+        Acknowledge = 0,
+        NotSupported = 1,
+        TooShort = 2,
+        AccessDenied = 3,
+        RequestNotSupported = 4,
+    }
+}
+
+lookup! {
+    /// Whether a parameter of a request or command could be applied.
+    pub enum ParameterErrorCode: u8 {
+        Acknowledge = 0,
+        InvalidParameterField = 1,
+        TemporaryError = 2,
+        OutOfRange = 3,
+        AccessDenied = 4,
+        NotSupported = 5,
+        ReadOrWriteNotSupported = 6,
+    }
+}
+
+/// The maximum encoded length of [`Parameters`]. This leaves room for the
+/// manufacturer and [`MAX_PARAMETERS`] fields of the longest possible
+/// [`ReadFields`] in a fast packet.
+pub const MAX_PARAMETERS_LEN: usize = 223 - GroupFunction::HEADER_LEN - 2 - 3 - MAX_PARAMETERS;
+
+/// The maximum number of parameters in an [`Acknowledge`] or
+/// [`ReadFields`].
+pub const MAX_PARAMETERS: usize = 32;
+
+/// Field-value pairs referencing the fields of another PGN, stored in their
+/// encoded form. See the [module-level documentation](self) for more
+/// information.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Parameters {
+    data: heapless::Vec<u8, MAX_PARAMETERS_LEN>,
+    len: u8,
+}
+
+impl Parameters {
+    pub const fn new() -> Self {
+        Self {
+            data: heapless::Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// The number of field-value pairs.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Appends a field and its encoded value, failing if there is no room
+    /// left.
+    #[allow(clippy::result_unit_err)]
+    pub fn push(&mut self, field: u8, value: &[u8]) -> Result<(), ()> {
+        if self.len == u8::MAX || self.data.len() + 1 + value.len() > MAX_PARAMETERS_LEN {
+            return Err(());
+        }
+
+        // cannot fail since we just checked the length
+        let _ = self.data.push(field);
+        let _ = self.data.extend_from_slice(value);
+        self.len += 1;
+
+        Ok(())
+    }
+
+    /// Iterate over the field-value pairs. `field_len` returns the encoded
+    /// length of a field in bytes, or [`None`] if the field is unknown.
+    pub fn iter<F>(&self, field_len: F) -> ParametersIter<'_, F>
+    where
+        F: FnMut(u8) -> Option<usize>,
+    {
+        ParametersIter {
+            data: &self.data,
+            remaining: self.len,
+            field_len,
+        }
+    }
+
+    fn get(data: &[u8], len: u8) -> Option<Self> {
+        Some(Self {
+            data: heapless::Vec::from_slice(data).ok()?,
+            len,
+        })
+    }
+
+    fn put(&self, buf: &mut &mut [u8]) {
+        buf.split_off_mut(..self.data.len())
+            .unwrap()
+            .copy_from_slice(&self.data);
+    }
+}
+
+/// An iterator over [`Parameters`]. Yields `Err(field)` if the length of a
+/// field is unknown or its value is truncated, after which the rest of the
+/// pairs cannot be read.
+pub struct ParametersIter<'a, F> {
+    data: &'a [u8],
+    remaining: u8,
+    field_len: F,
+}
+
+impl<'a, F> Iterator for ParametersIter<'a, F>
+where
+    F: FnMut(u8) -> Option<usize>,
+{
+    type Item = Result<(u8, &'a [u8]), u8>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 || self.data.is_empty() {
+            return None;
+        }
+
+        self.remaining -= 1;
+        let field = self.data.get_u8();
+
+        let value = (self.field_len)(field).and_then(|len| self.data.split_off(..len));
+
+        if value.is_none() {
+            self.remaining = 0;
+        }
+
+        Some(value.map(|value| (field, value)).ok_or(field))
+    }
+}
+
+fn get_interval(buf: &mut &[u8]) -> Option<Duration> {
+    buf.get_opt_u32().map(|ms| Duration::from_millis(ms.into()))
+}
+
+fn put_interval(buf: &mut &mut [u8], interval: Option<Duration>) {
+    buf.put_opt_u32(interval.map(|i| i.as_millis() as u32));
+}
+
+/// Request another device to send a PGN, optionally only if its fields
+/// have the given values and at a given interval.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Request {
+    pub pgn: u32,
+    /// How often the PGN should be sent. [`None`] leaves the interval
+    /// unchanged.
+    pub interval: Option<Duration>,
+    /// When to send the PGN relative to the interval, with a resolution of
+    /// 10 ms.
+    pub offset: Option<Duration>,
+    pub parameters: Parameters,
+}
+
+/// Command another device to change the fields of a PGN it sends.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Command {
+    pub pgn: u32,
+    /// The new priority of the PGN. [`None`] leaves it unchanged.
+    pub priority: Option<u8>,
+    pub parameters: Parameters,
+}
+
+/// The response to a [`Request`] or [`Command`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Acknowledge {
+    pub pgn: u32,
+    pub pgn_error: PgnErrorCode,
+    pub interval_error: TransmissionIntervalErrorCode,
+    /// One error code per parameter in the request or command.
+    pub parameter_errors: heapless::Vec<ParameterErrorCode, MAX_PARAMETERS>,
+}
+
+/// Read the fields of a PGN from another device, optionally only if other
+/// fields (the selection) have the given values.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ReadFields {
+    pub pgn: u32,
+    /// The manufacturer of the PGN, present if and only if the PGN is
+    /// proprietary.
+    pub manufacturer: Option<Manufacturer>,
+    /// Identifies the reply.
+    pub unique_id: u8,
+    pub selection: Parameters,
+    /// The numbers of the fields to read.
+    pub fields: heapless::Vec<u8, MAX_PARAMETERS>,
+}
+
+/// The fields of a PGN, used to reply to [`ReadFields`] and to write
+/// fields.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FieldValues {
+    pub pgn: u32,
+    /// The manufacturer of the PGN, present if and only if the PGN is
+    /// proprietary.
+    pub manufacturer: Option<Manufacturer>,
+    pub unique_id: u8,
+    /// The number of leading pairs in [`FieldValues::parameters`] that
+    /// select which instance of the PGN is meant. The remaining pairs are
+    /// the field values.
+    pub selections: u8,
+    pub parameters: Parameters,
+}
+
+/// PGN 126208, NMEA Group Function. This is a fast packet.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum GroupFunction {
+    Request(Request),
+    Command(Command),
+    Acknowledge(Acknowledge),
+    ReadFields(ReadFields),
+    ReadFieldsReply(FieldValues),
+    WriteFields(FieldValues),
+    WriteFieldsReply(FieldValues),
+}
+
+impl GroupFunction {
+    /// The function code and PGN.
+    const HEADER_LEN: usize = 4;
+
+    /// The PGN that the group function refers to.
+    #[must_use]
+    pub fn pgn(&self) -> u32 {
+        match self {
+            Self::Request(r) => r.pgn,
+            Self::Command(c) => c.pgn,
+            Self::Acknowledge(a) => a.pgn,
+            Self::ReadFields(r) => r.pgn,
+            Self::ReadFieldsReply(f) | Self::WriteFields(f) | Self::WriteFieldsReply(f) => f.pgn,
+        }
+    }
+
+    fn function_code(&self) -> u8 {
+        match self {
+            Self::Request(_) => 0,
+            Self::Command(_) => 1,
+            Self::Acknowledge(_) => 2,
+            Self::ReadFields(_) => 3,
+            Self::ReadFieldsReply(_) => 4,
+            Self::WriteFields(_) => 5,
+            Self::WriteFieldsReply(_) => 6,
+        }
+    }
+}
+
+fn manufacturer_len(manufacturer: Option<Manufacturer>) -> usize {
+    if manufacturer.is_some() {
+        2
+    } else {
+        0
+    }
+}
+
+fn get_manufacturer(buf: &mut &[u8], pgn: u32) -> Option<Option<Manufacturer>> {
+    if !is_proprietary(pgn) {
+        return Some(None);
+    }
+
+    if buf.remaining() < 2 {
+        return None;
+    }
+
+    Some(Some(Manufacturer::get(buf)))
+}
+
+impl Message for GroupFunction {
+    const PGN: u32 = 126208;
+
+    // the maximum fast packet length
+    type EncodedLen = typenum::U223;
+
+    type DecodeError = ();
+
+    fn encoded_len(&self) -> usize {
+        Self::HEADER_LEN
+            + match self {
+                Self::Request(r) => 7 + r.parameters.data.len(),
+                Self::Command(c) => 2 + c.parameters.data.len(),
+                Self::Acknowledge(a) => 2 + a.parameter_errors.len().div_ceil(2),
+                Self::ReadFields(r) => {
+                    manufacturer_len(r.manufacturer) + 3 + r.selection.data.len() + r.fields.len()
+                }
+                Self::ReadFieldsReply(f) | Self::WriteFields(f) | Self::WriteFieldsReply(f) => {
+                    manufacturer_len(f.manufacturer) + 3 + f.parameters.data.len()
+                }
+            }
+    }
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_u8(self.function_code());
+        buf.put_u24(self.pgn());
+
+        match self {
+            Self::Request(r) => {
+                put_interval(&mut buf, r.interval);
+                buf.put_opt_u16(r.offset.map(|o| (o.as_millis() / 10) as u16));
+                buf.put_u8(r.parameters.len);
+                r.parameters.put(&mut buf);
+            }
+            Self::Command(c) => {
+                buf.put_u8(0xf0 | c.priority.unwrap_or(8));
+                buf.put_u8(c.parameters.len);
+                c.parameters.put(&mut buf);
+            }
+            Self::Acknowledge(a) => {
+                buf.put_u8((u8::from(a.pgn_error) & 0x0f) | (u8::from(a.interval_error) << 4));
+                buf.put_u8(a.parameter_errors.len() as u8);
+
+                for pair in a.parameter_errors.chunks(2) {
+                    let high = pair.get(1).map_or(0x0f, |&code| u8::from(code));
+                    buf.put_u8((u8::from(pair[0]) & 0x0f) | (high << 4));
+                }
+            }
+            Self::ReadFields(r) => {
+                if let Some(manufacturer) = r.manufacturer {
+                    manufacturer.put(&mut buf);
+                }
+
+                buf.put_u8(r.unique_id);
+                buf.put_u8(r.selection.len);
+                buf.put_u8(r.fields.len() as u8);
+                r.selection.put(&mut buf);
+
+                for &field in &r.fields {
+                    buf.put_u8(field);
+                }
+            }
+            Self::ReadFieldsReply(f) | Self::WriteFields(f) | Self::WriteFieldsReply(f) => {
+                if let Some(manufacturer) = f.manufacturer {
+                    manufacturer.put(&mut buf);
+                }
+
+                buf.put_u8(f.unique_id);
+                buf.put_u8(f.selections);
+                buf.put_u8(f.parameters.len.saturating_sub(f.selections));
+                f.parameters.put(&mut buf);
+            }
+        }
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::HEADER_LEN + 2 {
+            return Err(());
+        }
+
+        let function_code = data.get_u8();
+        let pgn = data.get_u24();
+
+        match function_code {
+            0 => {
+                if data.remaining() < 7 {
+                    return Err(());
+                }
+
+                let interval = get_interval(&mut data);
+                let offset = data
+                    .get_opt_u16()
+                    .map(|o| Duration::from_millis(u64::from(o) * 10));
+                let len = data.get_u8();
+
+                Ok(Self::Request(Request {
+                    pgn,
+                    interval,
+                    offset,
+                    parameters: Parameters::get(data, len).ok_or(())?,
+                }))
+            }
+            1 => {
+                let priority = match data.get_u8() & 0x0f {
+                    p @ 0..=7 => Some(p),
+                    _ => None,
+                };
+                let len = data.get_u8();
+
+                Ok(Self::Command(Command {
+                    pgn,
+                    priority,
+                    parameters: Parameters::get(data, len).ok_or(())?,
+                }))
+            }
+            2 => {
+                let errors = data.get_u8();
+                let len = usize::from(data.get_u8());
+
+                if len > MAX_PARAMETERS || data.remaining() < len.div_ceil(2) {
+                    return Err(());
+                }
+
+                let mut parameter_errors = heapless::Vec::new();
+
+                for i in 0..len {
+                    let code = if i % 2 == 0 {
+                        data[i / 2] & 0x0f
+                    } else {
+                        data[i / 2] >> 4
+                    };
+
+                    // cannot fail since len <= MAX_PARAMETERS
+                    let _ = parameter_errors.push(code.into());
+                }
+
+                Ok(Self::Acknowledge(Acknowledge {
+                    pgn,
+                    pgn_error: (errors & 0x0f).into(),
+                    interval_error: (errors >> 4).into(),
+                    parameter_errors,
+                }))
+            }
+            3..=6 => {
+                let manufacturer = get_manufacturer(&mut data, pgn).ok_or(())?;
+
+                if data.remaining() < 3 {
+                    return Err(());
+                }
+
+                let unique_id = data.get_u8();
+                let selections = data.get_u8();
+                let len = data.get_u8();
+
+                if function_code == 3 {
+                    let fields_len = usize::from(len);
+                    let selection_len = data.len().checked_sub(fields_len).ok_or(())?;
+                    let (selection, fields) = data.split_at(selection_len);
+                    let selection = Parameters::get(selection, selections);
+
+                    return Ok(Self::ReadFields(ReadFields {
+                        pgn,
+                        manufacturer,
+                        unique_id,
+                        selection: selection.ok_or(())?,
+                        fields: heapless::Vec::from_slice(fields).map_err(|_| ())?,
+                    }));
+                }
+
+                let fields = FieldValues {
+                    pgn,
+                    manufacturer,
+                    unique_id,
+                    selections,
+                    parameters: Parameters::get(data, selections.checked_add(len).ok_or(())?)
+                        .ok_or(())?,
+                };
+
+                Ok(match function_code {
+                    4 => Self::ReadFieldsReply(fields),
+                    5 => Self::WriteFields(fields),
+                    _ => Self::WriteFieldsReply(fields),
+                })
+            }
+            _ => Err(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::well_known::{round_trip, IndustryCode};
+
+    #[test]
+    fn request() {
+        let GroupFunction::Request(request) = round_trip(&[
+            0x00, 0x12, 0xf1, 0x01, 0xe8, 0x03, 0x00, 0x00, 0xff, 0xff, 0x00,
+        ]) else {
+            panic!("not a request");
+        };
+
+        assert_eq!(request.pgn, 127250);
+        assert_eq!(request.interval, Some(Duration::from_secs(1)));
+        assert_eq!(request.offset, None);
+        assert!(request.parameters.is_empty());
+    }
+
+    #[test]
+    fn command() {
+        let msg = round_trip(&[
+            0x01, 0x0d, 0xf2, 0x01, 0xf8, 0x02, 0x01, 0x05, 0x02, 0x34, 0x12,
+        ]);
+        let GroupFunction::Command(command) = msg else {
+            panic!("not a command");
+        };
+
+        assert_eq!(command.pgn, 127501);
+        assert_eq!(command.priority, None);
+        assert_eq!(command.parameters.len(), 2);
+
+        let mut parameters = command.parameters.iter(|field| match field {
+            1 => Some(1),
+            2 => Some(2),
+            _ => None,
+        });
+        assert_eq!(parameters.next(), Some(Ok((1, &[0x05][..]))));
+        assert_eq!(parameters.next(), Some(Ok((2, &[0x34, 0x12][..]))));
+        assert_eq!(parameters.next(), None);
+
+        // the second pair cannot be read without knowing the first field
+        let mut parameters = command.parameters.iter(|_| None);
+        assert_eq!(parameters.next(), Some(Err(1)));
+        assert_eq!(parameters.next(), None);
+    }
+
+    #[test]
+    fn acknowledge() {
+        let msg = GroupFunction::Acknowledge(Acknowledge {
+            pgn: 127501,
+            pgn_error: PgnErrorCode::Acknowledge,
+            interval_error: TransmissionIntervalErrorCode::NotSupported,
+            parameter_errors: heapless::Vec::from_slice(&[
+                ParameterErrorCode::Acknowledge,
+                ParameterErrorCode::InvalidParameterField,
+                ParameterErrorCode::NotSupported,
+            ])
+            .unwrap(),
+        });
+        let mut buf = [0; 8];
+        assert_eq!(msg.encoded_len(), buf.len());
+        msg.encode(&mut buf);
+
+        assert_eq!(buf, [0x02, 0x0d, 0xf2, 0x01, 0x10, 0x03, 0x10, 0xf5]);
+        assert_eq!(GroupFunction::decode(&buf), Ok(msg));
+    }
+
+    #[test]
+    fn read_fields() {
+        let GroupFunction::ReadFields(read) = round_trip(&[
+            0x03, 0x04, 0xff, 0x01, 0x3b, 0x9f, 0x07, 0x01, 0x02, 0x01, 0x02, 0x03, 0x04,
+        ]) else {
+            panic!("not a read fields");
+        };
+
+        assert_eq!(read.pgn, 130820);
+        assert_eq!(
+            read.manufacturer,
+            Some(Manufacturer {
+                code: 1851,
+                industry: IndustryCode::Marine,
+            })
+        );
+        assert_eq!(read.unique_id, 7);
+        assert_eq!(read.selection.len(), 1);
+        assert_eq!(read.fields, [3, 4]);
+
+        let mut selection = Parameters::new();
+        while selection.push(1, &[0x00]).is_ok() {}
+
+        let read = GroupFunction::ReadFields(ReadFields {
+            selection,
+            fields: heapless::Vec::from_slice(&[1; MAX_PARAMETERS]).unwrap(),
+            ..read
+        });
+        assert_eq!(read.encoded_len(), 223);
+        let mut buf = [0; 223];
+        read.encode(&mut buf);
+        assert_eq!(GroupFunction::decode(&buf), Ok(read));
+    }
+
+    #[test]
+    fn write_fields() {
+        let mut parameters = Parameters::new();
+        parameters.push(1, &[0x00]).unwrap();
+        parameters.push(4, &[0x02]).unwrap();
+
+        let msg = GroupFunction::WriteFields(FieldValues {
+            pgn: 127501,
+            manufacturer: None,
+            unique_id: 1,
+            selections: 1,
+            parameters,
+        });
+        let mut buf = [0; 11];
+        assert_eq!(msg.encoded_len(), buf.len());
+        msg.encode(&mut buf);
+
+        assert_eq!(
+            buf,
+            [0x05, 0x0d, 0xf2, 0x01, 0x01, 0x01, 0x01, 0x01, 0x00, 0x04, 0x02]
+        );
+        assert_eq!(GroupFunction::decode(&buf), Ok(msg));
+    }
+}
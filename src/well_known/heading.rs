@@ -0,0 +1,272 @@
+//! Heading, attitude and motion from compasses and inertial sensors.
+
+use generic_array::typenum::{self, Unsigned};
+
+use super::DirectionReference;
+use crate::{Buf, BufMut, Message};
+
+lookup! {
+    /// How a magnetic variation was determined.
+    pub enum MagneticVariationSource: u8 {
+        Manual = 0,
+        AutomaticChart = 1,
+        AutomaticTable = 2,
+        AutomaticCalculation = 3,
+        Wmm2000 = 4,
+        Wmm2005 = 5,
+        Wmm2010 = 6,
+        Wmm2015 = 7,
+        Wmm2020 = 8,
+    }
+}
+
+/// PGN 127250, the heading of the vessel. A magnetic heading can be
+/// converted to a true heading by adding the deviation and variation.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct VesselHeading {
+    /// Sequence ID, used to tie together related messages.
+    pub sid: Option<u8>,
+    /// Heading in radians.
+    pub heading: Option<f32>,
+    /// Compass deviation in radians, positive easterly.
+    pub deviation: Option<f32>,
+    /// Magnetic variation in radians, positive easterly.
+    pub variation: Option<f32>,
+    pub reference: DirectionReference,
+}
+
+impl Message for VesselHeading {
+    const PGN: u32 = 127250;
+
+    type EncodedLen = typenum::U8;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_opt_u8(self.sid);
+        buf.put_fixed_u16(self.heading, 1e-4);
+        buf.put_fixed_i16(self.deviation, 1e-4);
+        buf.put_fixed_i16(self.variation, 1e-4);
+        buf.put_u8(0xfc | (u8::from(self.reference) & 0x03));
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        Ok(Self {
+            sid: data.get_opt_u8(),
+            heading: data.get_fixed_u16(1e-4),
+            deviation: data.get_fixed_i16(1e-4),
+            variation: data.get_fixed_i16(1e-4),
+            reference: (data.get_u8() & 0x03).into(),
+        })
+    }
+}
+
+/// PGN 127251, the rate of change of the heading.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RateOfTurn {
+    pub sid: Option<u8>,
+    /// Rate of turn in rad/s, positive to starboard.
+    pub rate: Option<f32>,
+}
+
+impl Message for RateOfTurn {
+    const PGN: u32 = 127251;
+
+    type EncodedLen = typenum::U8;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_opt_u8(self.sid);
+        buf.put_fixed_i32(self.rate.map(f64::from), 3.125e-8);
+        buf.put_reserved(3);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        Ok(Self {
+            sid: data.get_opt_u8(),
+            rate: data.get_fixed_i32(3.125e-8).map(|r| r as f32),
+        })
+    }
+}
+
+/// PGN 127252, the vertical displacement of the vessel.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Heave {
+    pub sid: Option<u8>,
+    /// Heave in meters, positive downwards.
+    pub heave: Option<f32>,
+}
+
+impl Message for Heave {
+    const PGN: u32 = 127252;
+
+    type EncodedLen = typenum::U8;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_opt_u8(self.sid);
+        buf.put_fixed_i16(self.heave, 0.01);
+        buf.put_reserved(5);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        Ok(Self {
+            sid: data.get_opt_u8(),
+            heave: data.get_fixed_i16(0.01),
+        })
+    }
+}
+
+/// PGN 127257, the orientation of the vessel.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Attitude {
+    pub sid: Option<u8>,
+    /// Yaw in radians.
+    pub yaw: Option<f32>,
+    /// Pitch in radians, positive when the bow is up.
+    pub pitch: Option<f32>,
+    /// Roll in radians, positive when heeling to starboard.
+    pub roll: Option<f32>,
+}
+
+impl Message for Attitude {
+    const PGN: u32 = 127257;
+
+    type EncodedLen = typenum::U8;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_opt_u8(self.sid);
+        buf.put_fixed_i16(self.yaw, 1e-4);
+        buf.put_fixed_i16(self.pitch, 1e-4);
+        buf.put_fixed_i16(self.roll, 1e-4);
+        buf.put_reserved(1);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        Ok(Self {
+            sid: data.get_opt_u8(),
+            yaw: data.get_fixed_i16(1e-4),
+            pitch: data.get_fixed_i16(1e-4),
+            roll: data.get_fixed_i16(1e-4),
+        })
+    }
+}
+
+/// PGN 127258, the magnetic variation at the current position.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct MagneticVariation {
+    pub sid: Option<u8>,
+    pub source: MagneticVariationSource,
+    /// The date the variation is valid from, in days since 1970-01-01.
+    pub age_of_service: Option<u16>,
+    /// Variation in radians, positive easterly.
+    pub variation: Option<f32>,
+}
+
+impl Message for MagneticVariation {
+    const PGN: u32 = 127258;
+
+    type EncodedLen = typenum::U8;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_opt_u8(self.sid);
+        buf.put_u8(0xf0 | (u8::from(self.source) & 0x0f));
+        buf.put_opt_u16(self.age_of_service);
+        buf.put_fixed_i16(self.variation, 1e-4);
+        buf.put_reserved(2);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        Ok(Self {
+            sid: data.get_opt_u8(),
+            source: (data.get_u8() & 0x0f).into(),
+            age_of_service: data.get_opt_u16(),
+            variation: data.get_fixed_i16(1e-4),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::well_known::round_trip;
+
+    #[test]
+    fn vessel_heading() {
+        let msg: VesselHeading = round_trip(&[0x0c, 0x10, 0x3d, 0x2c, 0x01, 0xff, 0x7f, 0xfd]);
+
+        assert_eq!(msg.sid, Some(12));
+        assert!((msg.heading.unwrap() - 1.5632).abs() < 1e-6);
+        assert!((msg.deviation.unwrap() - 0.03).abs() < 1e-6);
+        assert_eq!(msg.variation, None);
+        assert_eq!(msg.reference, DirectionReference::Magnetic);
+    }
+
+    #[test]
+    fn rate_of_turn() {
+        let msg: RateOfTurn = round_trip(&[0xff, 0xc0, 0xf2, 0xfc, 0xff, 0xff, 0xff, 0xff]);
+
+        assert_eq!(msg.sid, None);
+        assert!((msg.rate.unwrap() + 0.00625).abs() < 1e-9);
+    }
+
+    #[test]
+    fn heave() {
+        let msg: Heave = round_trip(&[0x03, 0xd3, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]);
+
+        assert!((msg.heave.unwrap() + 0.45).abs() < 1e-6);
+    }
+
+    #[test]
+    fn attitude() {
+        let msg: Attitude = round_trip(&[0x01, 0xff, 0x7f, 0xc8, 0x00, 0x9c, 0xff, 0xff]);
+
+        assert_eq!(msg.yaw, None);
+        assert!((msg.pitch.unwrap() - 0.02).abs() < 1e-6);
+        assert!((msg.roll.unwrap() + 0.01).abs() < 1e-6);
+    }
+
+    #[test]
+    fn magnetic_variation() {
+        let msg: MagneticVariation = round_trip(&[0x04, 0xf8, 0x38, 0x4a, 0x36, 0xff, 0xff, 0xff]);
+
+        assert_eq!(msg.source, MagneticVariationSource::Wmm2020);
+        assert_eq!(msg.age_of_service, Some(19000));
+        assert!((msg.variation.unwrap() + 0.0202).abs() < 1e-6);
+
+        let unknown: MagneticVariation =
+            round_trip(&[0xff, 0xfe, 0xff, 0xff, 0xff, 0x7f, 0xff, 0xff]);
+        assert_eq!(unknown.source, MagneticVariationSource::Unknown(14));
+    }
+}
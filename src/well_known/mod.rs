@@ -0,0 +1,349 @@
+//! Typed definitions of standardized messages, grouped by domain. Field
+//! names, resolutions and lookups follow [the Canboat project's
+//! documentation](https://canboat.github.io/canboat/canboat.html).
+//!
+//! Fields that may be transmitted as "not available" are represented as
+//! [`Option`]s. Angles are in radians, speeds in meters per second,
+//! temperatures in kelvin, pressures in pascals and latitudes and longitudes
+//! in degrees.
+
+use core::{fmt::Debug, time::Duration};
+
+use generic_array::typenum::{self, Unsigned};
+
+use crate::{Buf, BufMut, Message};
+
+pub mod ais;
+pub mod alert;
+pub mod electrical;
+pub mod engine;
+pub mod environment;
+pub mod fluid;
+pub mod gnss;
+pub mod group_function;
+pub mod heading;
+pub mod navigation;
+pub mod pgn_list;
+pub mod speed;
+pub mod steering;
+pub mod switching;
+pub mod system;
+pub mod thruster;
+
+lookup! {
+    /// Whether a direction is relative to true or magnetic north.
+    pub enum DirectionReference: u8 {
+        True = 0,
+        Magnetic = 1,
+        Error = 2,
+        Null = 3,
+    }
+}
+
+lookup! {
+    /// How a position or measurement was obtained.
+    pub enum ResidualMode: u8 {
+        Autonomous = 0,
+        DifferentialEnhanced = 1,
+        Estimated = 2,
+        Simulator = 3,
+        Manual = 4,
+    }
+}
+
+lookup! {
+    /// The industry a device or message belongs to.
+    pub enum IndustryCode: u8 {
+        Global = 0,
+        Highway = 1,
+        Agriculture = 2,
+        Construction = 3,
+        Marine = 4,
+        Industrial = 5,
+    }
+}
+
+/// The manufacturer of a proprietary message, sent in its first two bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Manufacturer {
+    /// An 11-bit manufacturer code assigned by NMEA.
+    pub code: u16,
+    pub industry: IndustryCode,
+}
+
+impl Manufacturer {
+    pub(crate) fn get(buf: &mut &[u8]) -> Self {
+        let raw = buf.get_u16();
+
+        Self {
+            code: raw & 0x7ff,
+            industry: ((raw >> 13) as u8).into(),
+        }
+    }
+
+    pub(crate) fn put(&self, buf: &mut &mut [u8]) {
+        buf.put_u16((self.code & 0x7ff) | 0x1800 | (u16::from(u8::from(self.industry)) << 13));
+    }
+}
+
+/// Interpret a 2-bit off/on or no/yes field, where 2 is an error and 3
+/// that the value is not available.
+pub(crate) fn get_opt_bool(bits: u8) -> Option<bool> {
+    match bits & 0x03 {
+        0 => Some(false),
+        1 => Some(true),
+        _ => None,
+    }
+}
+
+pub(crate) fn put_opt_bool(value: Option<bool>) -> u8 {
+    match value {
+        Some(value) => u8::from(value),
+        None => 0x03,
+    }
+}
+
+/// Read a time of day, transmitted as a 4-byte count of 0.0001 seconds since
+/// midnight.
+pub(crate) fn get_time(buf: &mut &[u8]) -> Option<Duration> {
+    buf.get_opt_u32()
+        .map(|t| Duration::from_micros(u64::from(t) * 100))
+}
+
+pub(crate) fn put_time(buf: &mut &mut [u8], time: Option<Duration>) {
+    buf.put_opt_u32(time.map(|t| (t.as_micros() / 100) as u32));
+}
+
+/// Read a fixed length string, stripping the padding. Padding might be
+/// `0xff`, NUL, `@` or space, depending on the sender. Non-ASCII bytes are
+/// interpreted as Latin-1, and characters that do not fit in `N` bytes are
+/// dropped.
+pub(crate) fn get_fixed_str<const N: usize>(buf: &mut &[u8], len: usize) -> heapless::String<N> {
+    let bytes = buf.split_off(..len).unwrap();
+    let end = bytes
+        .iter()
+        .rposition(|b| !matches!(b, 0xff | 0x00 | b'@' | b' '))
+        .map_or(0, |i| i + 1);
+
+    let mut s = heapless::String::new();
+
+    for &b in &bytes[..end] {
+        if s.push(char::from(b)).is_err() {
+            break;
+        }
+    }
+
+    s
+}
+
+/// Write a fixed length string as Latin-1, padded with `0xff`. Only the
+/// first `len` characters of `s` are written, and characters outside of
+/// Latin-1 are replaced with `?`.
+pub(crate) fn put_fixed_str(buf: &mut &mut [u8], s: &str, len: usize) {
+    put_padded_str(buf, s, len, 0xff);
+}
+
+/// Like [`put_fixed_str`], but padded with `padding`.
+pub(crate) fn put_padded_str(buf: &mut &mut [u8], s: &str, len: usize, padding: u8) {
+    let dest = buf.split_off_mut(..len).unwrap();
+    let mut n = 0;
+
+    for (b, c) in dest.iter_mut().zip(s.chars()) {
+        *b = u8::try_from(c).unwrap_or(b'?');
+        n += 1;
+    }
+
+    dest[n..].fill(padding);
+}
+
+/// Read a variable length string, prefixed by its length including the
+/// 2-byte header and an encoding byte (0 for UTF-16, 1 for ASCII). Returns
+/// `None` if the string is truncated. Characters that do not fit in `N`
+/// bytes are dropped.
+pub(crate) fn get_lau_str<const N: usize>(buf: &mut &[u8]) -> Option<heapless::String<N>> {
+    if buf.remaining() < 2 {
+        return None;
+    }
+
+    let len = usize::from(buf.get_u8()).checked_sub(2)?;
+    let encoding = buf.get_u8();
+    let bytes = buf.split_off(..len)?;
+    let mut s = heapless::String::new();
+
+    if encoding == 0 {
+        let units = bytes
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]));
+
+        for c in char::decode_utf16(units) {
+            if s.push(c.unwrap_or(char::REPLACEMENT_CHARACTER)).is_err() {
+                break;
+            }
+        }
+    } else {
+        for &b in bytes {
+            if s.push(char::from(b)).is_err() {
+                break;
+            }
+        }
+    }
+
+    Some(s)
+}
+
+/// The encoding byte and length of the characters written by
+/// [`put_lau_str`]. Strings are sent as ASCII if possible and as UTF-16
+/// otherwise, truncated to at most `N` bytes without splitting a character.
+fn lau_str_encoding<const N: usize>(s: &heapless::String<N>) -> (u8, usize) {
+    // the length byte includes the 2-byte header
+    let max = N.min(usize::from(u8::MAX) - 2);
+
+    if s.is_ascii() {
+        return (1, s.len().min(max));
+    }
+
+    let len = s
+        .chars()
+        .map(|c| 2 * c.len_utf16())
+        .scan(0, |len, n| {
+            *len += n;
+            (*len <= max).then_some(*len)
+        })
+        .last()
+        .unwrap_or(0);
+
+    (0, len)
+}
+
+/// Write a variable length string. See [`lau_str_encoding`] for the
+/// encoding.
+pub(crate) fn put_lau_str<const N: usize>(buf: &mut &mut [u8], s: &heapless::String<N>) {
+    let (encoding, len) = lau_str_encoding(s);
+
+    buf.put_u8(len as u8 + 2);
+    buf.put_u8(encoding);
+
+    let dest = buf.split_off_mut(..len).unwrap();
+
+    if encoding == 0 {
+        for (dest, unit) in dest.chunks_exact_mut(2).zip(s.encode_utf16()) {
+            dest.copy_from_slice(&unit.to_le_bytes());
+        }
+    } else {
+        dest.copy_from_slice(&s.as_bytes()[..len]);
+    }
+}
+
+/// The encoded length of a string written with [`put_lau_str`].
+pub(crate) fn lau_str_len<const N: usize>(s: &heapless::String<N>) -> usize {
+    2 + lau_str_encoding(s).1
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DeviceName(pub u64);
+
+impl From<u64> for DeviceName {
+    fn from(value: u64) -> Self {
+        DeviceName(value)
+    }
+}
+
+impl Debug for DeviceName {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "DeviceName(0x{:x})", self.0)
+    }
+}
+
+pub struct IsoAddressClaim {
+    // pub unique_number: u32,
+    // pub manufacturer_code: u16,
+    // pub device_instance: u8,
+    // pub device_function: u8,
+    // pub device_class: u8,
+    // pub system_instance: u8,
+    // pub industry_group: u8,
+    pub name: DeviceName,
+}
+
+impl Message for IsoAddressClaim {
+    const PGN: u32 = 60928;
+
+    type EncodedLen = typenum::U8;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        // buf.put_u32(
+        //     self.unique_number & 0x1FFFFF | ((self.manufacturer_code & 0x7ff) as u32) << 21,
+        // );
+        // buf.put_u8(self.device_instance);
+        // buf.put_u8(self.device_function);
+        // buf.put_u8((self.device_class & 0x7f) << 1);
+        // buf.put_u8(0x80 | ((self.industry_group & 0x7) << 4) | (self.system_instance & 0x0f));
+        buf.put_u64(self.name.0);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError>
+    where
+        Self: Sized,
+    {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        Ok(Self {
+            name: DeviceName(data.get_u64()),
+        })
+    }
+}
+
+/// PGN 59904, a request for another device to send a PGN. Requests sent
+/// to the broadcast address are answered by all devices that send the PGN.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct IsoRequest {
+    pub pgn: u32,
+}
+
+impl Message for IsoRequest {
+    const PGN: u32 = 59904;
+
+    type EncodedLen = typenum::U3;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_u24(self.pgn);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        Ok(Self {
+            pgn: data.get_u24(),
+        })
+    }
+}
+
+/// Decode `data`, check that encoding the result yields `data` again and
+/// return the decoded message.
+#[cfg(test)]
+pub(crate) fn round_trip<T>(data: &[u8]) -> T
+where
+    T: Message + Debug,
+    T::DecodeError: Debug,
+{
+    use generic_array::GenericArray;
+
+    let msg = T::decode(data).unwrap();
+    let mut buf = GenericArray::<u8, T::EncodedLen>::default();
+    msg.encode(&mut buf);
+
+    assert_eq!(&buf[..msg.encoded_len()], data, "{msg:?}");
+
+    msg
+}
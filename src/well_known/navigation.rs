@@ -0,0 +1,677 @@
+//! Cross track error, navigation to a waypoint, routes and waypoint lists.
+
+use core::{fmt::Debug, time::Duration};
+
+use generic_array::typenum::{self, Unsigned};
+
+use super::{
+    get_lau_str, get_opt_bool, get_time, lau_str_len, put_lau_str, put_opt_bool, put_time,
+    DirectionReference, ResidualMode,
+};
+use crate::{Buf, BufMut, Message};
+
+lookup! {
+    /// How the bearing and distance to a waypoint are calculated.
+    pub enum CalculationType: u8 {
+        GreatCircle = 0,
+        RhumbLine = 1,
+    }
+}
+
+lookup! {
+    /// The direction in which a route is being followed.
+    pub enum RouteDirection: u8 {
+        Forward = 0,
+        Reverse = 1,
+    }
+}
+
+/// PGN 129283, the distance from the intended course line.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CrossTrackError {
+    /// Sequence ID, used to tie together related messages.
+    pub sid: Option<u8>,
+    pub mode: ResidualMode,
+    /// Whether navigation to the destination has been terminated.
+    pub navigation_terminated: Option<bool>,
+    /// Cross track error in meters, positive when the vessel is to the right
+    /// (starboard) of the course line.
+    pub xte: Option<f32>,
+}
+
+impl Message for CrossTrackError {
+    const PGN: u32 = 129283;
+
+    type EncodedLen = typenum::U8;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_opt_u8(self.sid);
+        buf.put_u8(
+            (u8::from(self.mode) & 0x0f) | 0x30 | (put_opt_bool(self.navigation_terminated) << 6),
+        );
+        buf.put_fixed_i32(self.xte.map(f64::from), 0.01);
+        buf.put_reserved(2);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        let sid = data.get_opt_u8();
+        let flags = data.get_u8();
+
+        Ok(Self {
+            sid,
+            mode: (flags & 0x0f).into(),
+            navigation_terminated: get_opt_bool(flags >> 6),
+            xte: data.get_fixed_i32(0.01).map(|x| x as f32),
+        })
+    }
+}
+
+/// PGN 129284, bearing, distance and estimated time of arrival to the
+/// destination waypoint. This is a fast packet.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct NavigationData {
+    pub sid: Option<u8>,
+    /// Distance to the destination waypoint in meters.
+    pub distance: Option<f32>,
+    pub bearing_reference: DirectionReference,
+    /// Whether the line perpendicular to the course at the destination has
+    /// been crossed.
+    pub perpendicular_crossed: Option<bool>,
+    pub arrival_circle_entered: Option<bool>,
+    pub calculation_type: CalculationType,
+    /// Time of arrival since midnight (UTC).
+    pub eta_time: Option<Duration>,
+    /// Date of arrival in days since 1970-01-01.
+    pub eta_date: Option<u16>,
+    pub bearing_origin_to_destination: Option<f32>,
+    pub bearing_position_to_destination: Option<f32>,
+    pub origin_waypoint: Option<u32>,
+    pub destination_waypoint: Option<u32>,
+    pub destination_latitude: Option<f64>,
+    pub destination_longitude: Option<f64>,
+    /// The velocity made good towards the destination waypoint in m/s.
+    pub closing_velocity: Option<f32>,
+}
+
+impl Message for NavigationData {
+    const PGN: u32 = 129284;
+
+    type EncodedLen = typenum::U34;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_opt_u8(self.sid);
+        buf.put_fixed_u32(self.distance.map(f64::from), 0.01);
+        buf.put_u8(
+            (u8::from(self.bearing_reference) & 0x03)
+                | (put_opt_bool(self.perpendicular_crossed) << 2)
+                | (put_opt_bool(self.arrival_circle_entered) << 4)
+                | (u8::from(self.calculation_type) << 6),
+        );
+        put_time(&mut buf, self.eta_time);
+        buf.put_opt_u16(self.eta_date);
+        buf.put_fixed_u16(self.bearing_origin_to_destination, 1e-4);
+        buf.put_fixed_u16(self.bearing_position_to_destination, 1e-4);
+        buf.put_opt_u32(self.origin_waypoint);
+        buf.put_opt_u32(self.destination_waypoint);
+        buf.put_fixed_i32(self.destination_latitude, 1e-7);
+        buf.put_fixed_i32(self.destination_longitude, 1e-7);
+        buf.put_fixed_i16(self.closing_velocity, 0.01);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        let sid = data.get_opt_u8();
+        let distance = data.get_fixed_u32(0.01).map(|d| d as f32);
+        let flags = data.get_u8();
+
+        Ok(Self {
+            sid,
+            distance,
+            bearing_reference: (flags & 0x03).into(),
+            perpendicular_crossed: get_opt_bool(flags >> 2),
+            arrival_circle_entered: get_opt_bool(flags >> 4),
+            calculation_type: (flags >> 6).into(),
+            eta_time: get_time(&mut data),
+            eta_date: data.get_opt_u16(),
+            bearing_origin_to_destination: data.get_fixed_u16(1e-4),
+            bearing_position_to_destination: data.get_fixed_u16(1e-4),
+            origin_waypoint: data.get_opt_u32(),
+            destination_waypoint: data.get_opt_u32(),
+            destination_latitude: data.get_fixed_i32(1e-7),
+            destination_longitude: data.get_fixed_i32(1e-7),
+            closing_velocity: data.get_fixed_i16(0.01),
+        })
+    }
+}
+
+/// The maximum length of a [`Waypoint`] name.
+pub const MAX_WAYPOINT_NAME_LEN: usize = 32;
+
+/// A waypoint in a [`RouteInformation`] or [`WaypointList`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Waypoint {
+    pub id: Option<u16>,
+    pub name: heapless::String<MAX_WAYPOINT_NAME_LEN>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+}
+
+impl Waypoint {
+    /// The encoded length of the longest possible waypoint.
+    const MAX_LEN: usize = 10 + 2 + MAX_WAYPOINT_NAME_LEN;
+
+    fn encoded_len(&self) -> usize {
+        10 + lau_str_len(&self.name)
+    }
+
+    fn get(buf: &mut &[u8]) -> Option<Self> {
+        if buf.remaining() < 2 {
+            return None;
+        }
+
+        let id = buf.get_opt_u16();
+        let name = get_lau_str(buf)?;
+
+        if buf.remaining() < 8 {
+            return None;
+        }
+
+        Some(Self {
+            id,
+            name,
+            latitude: buf.get_fixed_i32(1e-7),
+            longitude: buf.get_fixed_i32(1e-7),
+        })
+    }
+
+    fn put(&self, buf: &mut &mut [u8]) {
+        buf.put_opt_u16(self.id);
+        put_lau_str(buf, &self.name);
+        buf.put_fixed_i32(self.latitude, 1e-7);
+        buf.put_fixed_i32(self.longitude, 1e-7);
+    }
+}
+
+/// A list of waypoints, stored in its encoded form of at most `N` bytes.
+///
+/// Waypoints are decoded one at a time when iterating, so that a message
+/// with many waypoints does not need room for the same number of
+/// [`Waypoint`]s.
+#[derive(Clone, PartialEq, Default)]
+pub struct Waypoints<const N: usize> {
+    data: heapless::Vec<u8, N>,
+    len: u16,
+}
+
+impl<const N: usize> Waypoints<N> {
+    pub const fn new() -> Self {
+        Self {
+            data: heapless::Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// The number of waypoints in the list.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Appends a waypoint to the list, failing if its encoded form does not
+    /// fit.
+    #[allow(clippy::result_unit_err)]
+    pub fn push(&mut self, waypoint: &Waypoint) -> Result<(), ()> {
+        let mut buf = [0; Waypoint::MAX_LEN];
+        waypoint.put(&mut buf.as_mut_slice());
+
+        self.data
+            .extend_from_slice(&buf[..waypoint.encoded_len()])
+            .map_err(|_| ())?;
+        self.len += 1;
+
+        Ok(())
+    }
+
+    pub fn iter(&self) -> WaypointsIter<'_> {
+        WaypointsIter {
+            data: &self.data,
+            remaining: self.len,
+        }
+    }
+
+    fn encoded_len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Read `len` waypoints, checking that they are well-formed.
+    fn get(buf: &mut &[u8], len: u16) -> Option<Self> {
+        let start = *buf;
+
+        for _ in 0..len {
+            Waypoint::get(buf)?;
+        }
+
+        let data = heapless::Vec::from_slice(&start[..start.len() - buf.len()]).ok()?;
+
+        Some(Self { data, len })
+    }
+
+    fn put(&self, buf: &mut &mut [u8]) {
+        buf.split_off_mut(..self.data.len())
+            .unwrap()
+            .copy_from_slice(&self.data);
+    }
+}
+
+impl<const N: usize> Debug for Waypoints<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl<const N: usize> defmt::Format for Waypoints<N> {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "[");
+
+        for (i, waypoint) in self.iter().enumerate() {
+            if i > 0 {
+                defmt::write!(f, ", ");
+            }
+
+            defmt::write!(f, "{}", waypoint);
+        }
+
+        defmt::write!(f, "]");
+    }
+}
+
+impl<'a, const N: usize> IntoIterator for &'a Waypoints<N> {
+    type Item = Waypoint;
+
+    type IntoIter = WaypointsIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over [`Waypoints`], decoding each waypoint as it goes.
+pub struct WaypointsIter<'a> {
+    data: &'a [u8],
+    remaining: u16,
+}
+
+impl Iterator for WaypointsIter<'_> {
+    type Item = Waypoint;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        self.remaining -= 1;
+        // the data has been validated when decoding or pushing
+        Waypoint::get(&mut self.data)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = usize::from(self.remaining);
+        (n, Some(n))
+    }
+}
+
+impl ExactSizeIterator for WaypointsIter<'_> {}
+
+/// The maximum length of a [`RouteInformation`] route name.
+pub const MAX_ROUTE_NAME_LEN: usize = 32;
+
+/// The room left for waypoints in a [`RouteInformation`] without a route
+/// name, so that every valid message can be decoded.
+const ROUTE_WAYPOINTS_LEN: usize = 223 - RouteInformation::FIXED_LEN - 2;
+
+/// PGN 129285, the route being followed and (part of) its waypoints. This is
+/// a fast packet.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RouteInformation {
+    /// The route point sequence number of the first waypoint in this
+    /// message, for routes that span multiple messages.
+    pub start_rps: Option<u16>,
+    pub database: Option<u16>,
+    pub route: Option<u16>,
+    pub direction: RouteDirection,
+    /// Whether supplementary route or waypoint data is available.
+    pub supplementary_data: Option<bool>,
+    pub name: heapless::String<MAX_ROUTE_NAME_LEN>,
+    /// When encoding, the waypoints have to fit in the room left by the
+    /// name.
+    pub waypoints: Waypoints<ROUTE_WAYPOINTS_LEN>,
+}
+
+impl RouteInformation {
+    const FIXED_LEN: usize = 10;
+}
+
+impl Message for RouteInformation {
+    const PGN: u32 = 129285;
+
+    // the maximum fast packet length
+    type EncodedLen = typenum::U223;
+
+    type DecodeError = ();
+
+    fn encoded_len(&self) -> usize {
+        Self::FIXED_LEN + lau_str_len(&self.name) + self.waypoints.encoded_len()
+    }
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_opt_u16(self.start_rps);
+        buf.put_u16(self.waypoints.len);
+        buf.put_opt_u16(self.database);
+        buf.put_opt_u16(self.route);
+        buf.put_u8(
+            (u8::from(self.direction) & 0x07) | (put_opt_bool(self.supplementary_data) << 3) | 0xe0,
+        );
+        put_lau_str(&mut buf, &self.name);
+        buf.put_reserved(1);
+        self.waypoints.put(&mut buf);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::FIXED_LEN {
+            return Err(());
+        }
+
+        let start_rps = data.get_opt_u16();
+        let len = data.get_u16();
+        let database = data.get_opt_u16();
+        let route = data.get_opt_u16();
+        let flags = data.get_u8();
+        let name = get_lau_str(&mut data).ok_or(())?;
+
+        if data.remaining() < 1 {
+            return Err(());
+        }
+
+        data.advance(1);
+
+        Ok(Self {
+            start_rps,
+            database,
+            route,
+            direction: (flags & 0x07).into(),
+            supplementary_data: get_opt_bool(flags >> 3),
+            name,
+            waypoints: Waypoints::get(&mut data, len).ok_or(())?,
+        })
+    }
+}
+
+/// PGN 129291, the set (direction) and drift (speed) of the current.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SetAndDrift {
+    pub sid: Option<u8>,
+    pub set_reference: DirectionReference,
+    /// The direction the current flows towards, in radians.
+    pub set: Option<f32>,
+    /// The speed of the current in m/s.
+    pub drift: Option<f32>,
+}
+
+impl Message for SetAndDrift {
+    const PGN: u32 = 129291;
+
+    type EncodedLen = typenum::U8;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_opt_u8(self.sid);
+        buf.put_u8(0xfc | (u8::from(self.set_reference) & 0x03));
+        buf.put_fixed_u16(self.set, 1e-4);
+        buf.put_fixed_u16(self.drift, 0.01);
+        buf.put_reserved(2);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        Ok(Self {
+            sid: data.get_opt_u8(),
+            set_reference: (data.get_u8() & 0x03).into(),
+            set: data.get_fixed_u16(1e-4),
+            drift: data.get_fixed_u16(0.01),
+        })
+    }
+}
+
+const LIST_WAYPOINTS_LEN: usize = 223 - WaypointList::FIXED_LEN;
+
+/// PGN 130074, Route and WP Service - WP List - WP Name & Position. (Part
+/// of) the waypoints stored in a database. This is a fast packet.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct WaypointList {
+    /// The ID of the first waypoint in this message.
+    pub start_waypoint: Option<u16>,
+    /// The total number of valid waypoints in the list.
+    pub valid_waypoints: Option<u16>,
+    pub database: Option<u16>,
+    pub waypoints: Waypoints<LIST_WAYPOINTS_LEN>,
+}
+
+impl WaypointList {
+    const FIXED_LEN: usize = 10;
+}
+
+impl Message for WaypointList {
+    const PGN: u32 = 130074;
+
+    // the maximum fast packet length
+    type EncodedLen = typenum::U223;
+
+    type DecodeError = ();
+
+    fn encoded_len(&self) -> usize {
+        Self::FIXED_LEN + self.waypoints.encoded_len()
+    }
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_opt_u16(self.start_waypoint);
+        buf.put_u16(self.waypoints.len);
+        buf.put_opt_u16(self.valid_waypoints);
+        buf.put_opt_u16(self.database);
+        buf.put_reserved(2);
+        self.waypoints.put(&mut buf);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::FIXED_LEN {
+            return Err(());
+        }
+
+        let start_waypoint = data.get_opt_u16();
+        let len = data.get_u16();
+        let valid_waypoints = data.get_opt_u16();
+        let database = data.get_opt_u16();
+        data.advance(2);
+
+        Ok(Self {
+            start_waypoint,
+            valid_waypoints,
+            database,
+            waypoints: Waypoints::get(&mut data, len).ok_or(())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::well_known::round_trip;
+
+    const WAYPOINTS: [u8; 32] = [
+        0x01, 0x00, 0x06, 0x01, 0x48, 0x6f, 0x6d, 0x65, 0xc8, 0xee, 0x5c, 0x23, 0xb0, 0x0c, 0xc5,
+        0x0a, 0x02, 0x00, 0x06, 0x01, 0x42, 0x75, 0x6f, 0x79, 0x40, 0x76, 0x58, 0x23, 0x40, 0xd7,
+        0xc9, 0x0a,
+    ];
+
+    fn assert_waypoints<const N: usize>(waypoints: &Waypoints<N>) {
+        let mut iter = waypoints.iter();
+        assert_eq!(iter.len(), 2);
+
+        let home = iter.next().unwrap();
+        assert_eq!(home.id, Some(1));
+        assert_eq!(home.name, "Home");
+        assert!((home.latitude.unwrap() - 59.3293).abs() < 1e-7);
+        assert!((home.longitude.unwrap() - 18.0686).abs() < 1e-7);
+
+        let buoy = iter.next().unwrap();
+        assert_eq!(buoy.id, Some(2));
+        assert_eq!(buoy.name, "Buoy");
+
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn cross_track_error() {
+        let msg: CrossTrackError = round_trip(&[0x03, 0x30, 0x2e, 0xfb, 0xff, 0xff, 0xff, 0xff]);
+
+        assert_eq!(msg.mode, ResidualMode::Autonomous);
+        assert_eq!(msg.navigation_terminated, Some(false));
+        assert!((msg.xte.unwrap() + 12.34).abs() < 1e-6);
+    }
+
+    #[test]
+    fn navigation_data() {
+        let msg: NavigationData = round_trip(&[
+            0x01, 0x70, 0xd3, 0x02, 0x00, 0x10, 0x00, 0xcc, 0xbf, 0x19, 0x38, 0x4a, 0x5c, 0x3d,
+            0xb8, 0x7a, 0x03, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0xc8, 0xee, 0x5c, 0x23,
+            0xb0, 0x0c, 0xc5, 0x0a, 0x5e, 0x01,
+        ]);
+
+        assert_eq!(msg.distance, Some(1852.0));
+        assert_eq!(msg.bearing_reference, DirectionReference::True);
+        assert_eq!(msg.perpendicular_crossed, Some(false));
+        assert_eq!(msg.arrival_circle_entered, Some(true));
+        assert_eq!(msg.calculation_type, CalculationType::GreatCircle);
+        assert_eq!(msg.eta_time, Some(Duration::from_secs(43200)));
+        assert_eq!(msg.eta_date, Some(19000));
+        assert_eq!(msg.origin_waypoint, Some(3));
+        assert_eq!(msg.destination_waypoint, Some(4));
+        assert!((msg.destination_latitude.unwrap() - 59.3293).abs() < 1e-7);
+        assert!((msg.closing_velocity.unwrap() - 3.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn route_information() {
+        let mut data = heapless::Vec::<u8, 55>::from_slice(&[
+            0x00, 0x00, 0x02, 0x00, 0x01, 0x00, 0x07, 0x00, 0xe0, 0x0d, 0x01, 0x41, 0x72, 0x63,
+            0x68, 0x69, 0x70, 0x65, 0x6c, 0x61, 0x67, 0x6f, 0xff,
+        ])
+        .unwrap();
+        data.extend_from_slice(&WAYPOINTS).unwrap();
+
+        let msg: RouteInformation = round_trip(&data);
+
+        assert_eq!(msg.start_rps, Some(0));
+        assert_eq!(msg.database, Some(1));
+        assert_eq!(msg.route, Some(7));
+        assert_eq!(msg.direction, RouteDirection::Forward);
+        assert_eq!(msg.supplementary_data, Some(false));
+        assert_eq!(msg.name, "Archipelago");
+        assert_waypoints(&msg.waypoints);
+
+        // a truncated waypoint list is rejected
+        assert_eq!(RouteInformation::decode(&data[..data.len() - 1]), Err(()));
+    }
+
+    #[test]
+    fn route_information_full() {
+        let mut data = heapless::Vec::<u8, 223>::from_slice(&[
+            0x00, 0x00, 0x11, 0x00, 0x01, 0x00, 0x07, 0x00, 0xe0, 0x02, 0x01, 0xff,
+        ])
+        .unwrap();
+        for id in 0..17 {
+            data.extend_from_slice(&[id, 0x00, 0x02, 0x01]).unwrap();
+            data.extend_from_slice(&[0x40, 0x0b, 0x45, 0x23]).unwrap();
+            data.extend_from_slice(&[0xc0, 0x62, 0xd3, 0x0e]).unwrap();
+        }
+
+        let msg: RouteInformation = round_trip(&data);
+
+        assert_eq!(msg.name, "");
+        assert_eq!(msg.waypoints.len(), 17);
+        assert_eq!(msg.waypoints.iter().last().unwrap().id, Some(16));
+    }
+
+    #[test]
+    fn set_and_drift() {
+        let msg: SetAndDrift = round_trip(&[0x05, 0xfc, 0xb8, 0x7a, 0x33, 0x00, 0xff, 0xff]);
+
+        assert_eq!(msg.set_reference, DirectionReference::True);
+        assert!((msg.set.unwrap() - core::f32::consts::PI).abs() < 1e-4);
+        assert!((msg.drift.unwrap() - 0.51).abs() < 1e-6);
+    }
+
+    #[test]
+    fn waypoint_list() {
+        let mut data = heapless::Vec::<u8, 42>::from_slice(&[
+            0x01, 0x00, 0x02, 0x00, 0x02, 0x00, 0x01, 0x00, 0xff, 0xff,
+        ])
+        .unwrap();
+        data.extend_from_slice(&WAYPOINTS).unwrap();
+
+        let msg: WaypointList = round_trip(&data);
+
+        assert_eq!(msg.start_waypoint, Some(1));
+        assert_eq!(msg.valid_waypoints, Some(2));
+        assert_eq!(msg.database, Some(1));
+        assert_waypoints(&msg.waypoints);
+
+        let mut waypoints = Waypoints::new();
+        for waypoint in &msg.waypoints {
+            waypoints.push(&waypoint).unwrap();
+        }
+        assert_eq!(waypoints, msg.waypoints);
+    }
+
+    #[test]
+    fn waypoints_capacity() {
+        let waypoint = Waypoint {
+            id: Some(1),
+            name: heapless::String::try_from("Home").unwrap(),
+            latitude: None,
+            longitude: None,
+        };
+        let mut waypoints = Waypoints::<32>::new();
+
+        assert_eq!(waypoints.push(&waypoint), Ok(()));
+        assert_eq!(waypoints.push(&waypoint), Ok(()));
+        assert_eq!(waypoints.push(&waypoint), Err(()));
+        assert_eq!(waypoints.len(), 2);
+    }
+}
@@ -0,0 +1,105 @@
+//! PGN List (PGN 126464), the PGNs a device sends and receives.
+
+use generic_array::typenum;
+
+use crate::{Buf, BufMut, Message};
+
+lookup! {
+    /// Whether a [`PgnList`] contains the PGNs a device sends or receives.
+    pub enum PgnListFunction: u8 {
+        Transmit = 0,
+        Receive = 1,
+    }
+}
+
+/// The maximum number of PGNs in a [`PgnList`].
+pub const MAX_PGN_LIST_LEN: usize = (223 - 1) / 3;
+
+/// PGN 126464, the PGNs that a device sends or receives. This is a fast
+/// packet.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PgnList {
+    pub function: PgnListFunction,
+    pub pgns: heapless::Vec<u32, MAX_PGN_LIST_LEN>,
+}
+
+impl Message for PgnList {
+    const PGN: u32 = 126464;
+
+    type EncodedLen = typenum::U223;
+
+    type DecodeError = ();
+
+    fn encoded_len(&self) -> usize {
+        1 + 3 * self.pgns.len()
+    }
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_u8(self.function.into());
+
+        for &pgn in &self.pgns {
+            buf.put_u24(pgn);
+        }
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.is_empty() {
+            return Err(());
+        }
+
+        let function = data.get_u8().into();
+        let mut pgns = heapless::Vec::new();
+
+        // trailing bytes that do not make up a whole PGN are padding
+        while data.remaining() >= 3 {
+            pgns.push(data.get_u24()).map_err(|_| ())?;
+        }
+
+        Ok(Self { function, pgns })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{well_known::round_trip, Decoder, Id, NmeaFrame};
+
+    #[test]
+    fn pgn_list() {
+        let msg: PgnList =
+            round_trip(&[0x00, 0x00, 0xee, 0x00, 0x00, 0xee, 0x01, 0x0d, 0xf2, 0x01]);
+
+        assert_eq!(msg.function, PgnListFunction::Transmit);
+        assert_eq!(msg.pgns, [60928, 126464, 127501]);
+    }
+
+    #[test]
+    fn padding() {
+        let msg = PgnList::decode(&[0x01, 0x00, 0xea, 0x00, 0xff, 0xff]).unwrap();
+
+        assert_eq!(msg.function, PgnListFunction::Receive);
+        assert_eq!(msg.pgns, [59904]);
+    }
+
+    #[test]
+    fn full_list_as_fast_packets() {
+        let msg = PgnList {
+            function: PgnListFunction::Transmit,
+            pgns: (0..MAX_PGN_LIST_LEN as u32).map(|i| 127_000 + i).collect(),
+        };
+        let mut buf = [0; 223];
+        let mut decoder = Decoder::<PgnList, 1>::new();
+        let id = Id::new(6, PgnList::PGN, 30, 255);
+        let mut received = None;
+
+        for packet in msg.encode_to_fast_packets(&mut buf, 3) {
+            received = decoder.decode(&NmeaFrame::new(
+                id,
+                heapless::Vec::from_slice(&packet.0).unwrap(),
+            ));
+        }
+
+        assert_eq!(received.unwrap().unwrap().msg, msg);
+    }
+}
@@ -0,0 +1,255 @@
+//! Speed through water, depth and distance travelled.
+
+use core::time::Duration;
+
+use generic_array::typenum::{self, Unsigned};
+
+use super::{get_time, put_time};
+use crate::{Buf, BufMut, Message};
+
+lookup! {
+    /// The kind of sensor measuring speed through water.
+    pub enum WaterReference: u8 {
+        PaddleWheel = 0,
+        PitotTube = 1,
+        Doppler = 2,
+        /// Ultrasound correlation.
+        Correlation = 3,
+        ElectroMagnetic = 4,
+    }
+}
+
+lookup! {
+    pub enum SpeedDirection: u8 {
+        Forward = 0,
+        Reverse = 1,
+    }
+}
+
+/// PGN 128000, the angle between the heading and the course through water.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Leeway {
+    /// Sequence ID, used to tie together related messages.
+    pub sid: Option<u8>,
+    /// Leeway angle in radians, positive when drifting to starboard.
+    pub angle: Option<f32>,
+}
+
+impl Message for Leeway {
+    const PGN: u32 = 128000;
+
+    type EncodedLen = typenum::U8;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_opt_u8(self.sid);
+        buf.put_fixed_i16(self.angle, 1e-4);
+        buf.put_reserved(5);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        Ok(Self {
+            sid: data.get_opt_u8(),
+            angle: data.get_fixed_i16(1e-4),
+        })
+    }
+}
+
+/// PGN 128259, speed through water and over ground.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Speed {
+    pub sid: Option<u8>,
+    /// Speed through water in m/s.
+    pub water_referenced: Option<f32>,
+    /// Speed over ground in m/s.
+    pub ground_referenced: Option<f32>,
+    pub water_reference: WaterReference,
+    pub direction: SpeedDirection,
+}
+
+impl Message for Speed {
+    const PGN: u32 = 128259;
+
+    type EncodedLen = typenum::U8;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_opt_u8(self.sid);
+        buf.put_fixed_u16(self.water_referenced, 0.01);
+        buf.put_fixed_u16(self.ground_referenced, 0.01);
+        buf.put_u8(self.water_reference.into());
+        buf.put_u8(0xf0 | (u8::from(self.direction) & 0x0f));
+        buf.put_reserved(1);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        Ok(Self {
+            sid: data.get_opt_u8(),
+            water_referenced: data.get_fixed_u16(0.01),
+            ground_referenced: data.get_fixed_u16(0.01),
+            water_reference: data.get_u8().into(),
+            direction: (data.get_u8() & 0x0f).into(),
+        })
+    }
+}
+
+/// PGN 128267, the depth below the transducer.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct WaterDepth {
+    pub sid: Option<u8>,
+    /// Depth below the transducer in meters.
+    pub depth: Option<f32>,
+    /// Distance from the transducer to the waterline (positive) or the keel
+    /// (negative), in meters.
+    pub offset: Option<f32>,
+    /// The maximum depth that can be measured, in meters.
+    pub range: Option<f32>,
+}
+
+impl Message for WaterDepth {
+    const PGN: u32 = 128267;
+
+    type EncodedLen = typenum::U8;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_opt_u8(self.sid);
+        buf.put_fixed_u32(self.depth.map(f64::from), 0.01);
+        buf.put_fixed_i16(self.offset, 0.001);
+        buf.put_fixed_u8(self.range, 10.0);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        Ok(Self {
+            sid: data.get_opt_u8(),
+            depth: data.get_fixed_u32(0.01).map(|d| d as f32),
+            offset: data.get_fixed_i16(0.001),
+            range: data.get_fixed_u8(10.0),
+        })
+    }
+}
+
+/// PGN 128275, the total distance travelled and the distance since the log
+/// was reset. This is a fast packet.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DistanceLog {
+    /// Days since 1970-01-01.
+    pub date: Option<u16>,
+    /// Time since midnight (UTC).
+    pub time: Option<Duration>,
+    /// Total distance in meters.
+    pub log: Option<u32>,
+    /// Distance since the last reset in meters.
+    pub trip_log: Option<u32>,
+}
+
+impl Message for DistanceLog {
+    const PGN: u32 = 128275;
+
+    type EncodedLen = typenum::U14;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_opt_u16(self.date);
+        put_time(&mut buf, self.time);
+        buf.put_opt_u32(self.log);
+        buf.put_opt_u32(self.trip_log);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        Ok(Self {
+            date: data.get_opt_u16(),
+            time: get_time(&mut data),
+            log: data.get_opt_u32(),
+            trip_log: data.get_opt_u32(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use generic_array::GenericArray;
+
+    use super::*;
+    use crate::{fast_packet, well_known::round_trip, FastPacket};
+
+    #[test]
+    fn leeway() {
+        let msg: Leeway = round_trip(&[0x01, 0x5e, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]);
+
+        assert!((msg.angle.unwrap() + 0.0162).abs() < 1e-6);
+    }
+
+    #[test]
+    fn speed() {
+        let msg: Speed = round_trip(&[0x09, 0x9a, 0x01, 0xff, 0xff, 0x00, 0xf0, 0xff]);
+
+        assert!((msg.water_referenced.unwrap() - 4.1).abs() < 1e-6);
+        assert_eq!(msg.ground_referenced, None);
+        assert_eq!(msg.water_reference, WaterReference::PaddleWheel);
+        assert_eq!(msg.direction, SpeedDirection::Forward);
+    }
+
+    #[test]
+    fn water_depth() {
+        let msg: WaterDepth = round_trip(&[0xff, 0x1f, 0x05, 0x00, 0x00, 0x0c, 0xfe, 0x0a]);
+
+        assert!((msg.depth.unwrap() - 13.11).abs() < 1e-4);
+        assert!((msg.offset.unwrap() + 0.5).abs() < 1e-6);
+        assert_eq!(msg.range, Some(100.0));
+    }
+
+    #[test]
+    fn distance_log() {
+        let msg: DistanceLog = round_trip(&[
+            0x38, 0x4a, 0x58, 0xba, 0xff, 0x1a, 0xa8, 0xfb, 0x6b, 0x00, 0x36, 0x2e, 0x00, 0x00,
+        ]);
+
+        assert_eq!(msg.date, Some(19000));
+        assert_eq!(msg.time, Some(Duration::from_millis(45_296_700)));
+        assert_eq!(msg.log, Some(7_076_776));
+        assert_eq!(msg.trip_log, Some(11_830));
+
+        let mut buf = GenericArray::<u8, <DistanceLog as Message>::EncodedLen>::default();
+        let packets: heapless::Vec<_, 3> = msg.encode_to_fast_packets(&mut buf, 7).collect();
+
+        assert_eq!(
+            packets.iter().map(|p| p.0).collect::<heapless::Vec<_, 3>>(),
+            [
+                [0xe0, 0x0e, 0x38, 0x4a, 0x58, 0xba, 0xff, 0x1a],
+                [0xe1, 0xa8, 0xfb, 0x6b, 0x00, 0x36, 0x2e, 0x00],
+                [0xe2, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+            ]
+        );
+
+        let mut reader = fast_packet::Reader::<DistanceLog>::new();
+
+        assert_eq!(reader.read(FastPacket(packets[0].0)), None);
+        assert_eq!(reader.read(FastPacket(packets[1].0)), None);
+        assert_eq!(reader.read(FastPacket(packets[2].0)), Some(Ok(msg)));
+    }
+}
@@ -0,0 +1,357 @@
+//! Steering, autopilot and man overboard notifications.
+
+use core::time::Duration;
+
+use generic_array::typenum::{self, Unsigned};
+
+use super::{get_opt_bool, get_time, put_opt_bool, put_time, DirectionReference};
+use crate::{Buf, BufMut, Message};
+
+lookup! {
+    /// Who or what is in control of the steering.
+    pub enum SteeringMode: u8 {
+        MainSteering = 0,
+        NonFollowUpDevice = 1,
+        FollowUpDevice = 2,
+        HeadingControlStandalone = 3,
+        HeadingControl = 4,
+        TrackControl = 5,
+    }
+}
+
+lookup! {
+    /// How a heading change is carried out.
+    pub enum TurnMode: u8 {
+        RudderLimitControlled = 0,
+        TurnRateControlled = 1,
+        RadiusControlled = 2,
+    }
+}
+
+lookup! {
+    /// The direction the rudder is ordered to move in.
+    pub enum RudderDirection: u8 {
+        NoOrder = 0,
+        MoveToStarboard = 1,
+        MoveToPort = 2,
+    }
+}
+
+/// PGN 127237, the state and orders of a heading or track controller such
+/// as an autopilot. This is a fast packet.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct HeadingTrackControl {
+    pub rudder_limit_exceeded: Option<bool>,
+    pub off_heading_limit_exceeded: Option<bool>,
+    pub off_track_limit_exceeded: Option<bool>,
+    /// Whether the controller has been overridden, e.g. by manual steering.
+    pub overridden: Option<bool>,
+    pub steering_mode: SteeringMode,
+    pub turn_mode: TurnMode,
+    pub heading_reference: DirectionReference,
+    pub commanded_rudder_direction: RudderDirection,
+    /// Commanded rudder angle in radians, positive to starboard.
+    pub commanded_rudder_angle: Option<f32>,
+    pub heading_to_steer: Option<f32>,
+    pub track: Option<f32>,
+    pub rudder_limit: Option<f32>,
+    pub off_heading_limit: Option<f32>,
+    /// Ordered radius of turn in meters.
+    pub radius_of_turn: Option<f32>,
+    /// Ordered rate of turn in rad/s.
+    pub rate_of_turn: Option<f32>,
+    /// Off-track limit in meters.
+    pub off_track_limit: Option<f32>,
+    pub vessel_heading: Option<f32>,
+}
+
+impl Message for HeadingTrackControl {
+    const PGN: u32 = 127237;
+
+    type EncodedLen = typenum::U21;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_u8(
+            put_opt_bool(self.rudder_limit_exceeded)
+                | (put_opt_bool(self.off_heading_limit_exceeded) << 2)
+                | (put_opt_bool(self.off_track_limit_exceeded) << 4)
+                | (put_opt_bool(self.overridden) << 6),
+        );
+        buf.put_u8(
+            (u8::from(self.steering_mode) & 0x07)
+                | ((u8::from(self.turn_mode) & 0x07) << 3)
+                | (u8::from(self.heading_reference) << 6),
+        );
+        buf.put_u8(0x1f | (u8::from(self.commanded_rudder_direction) << 5));
+        buf.put_fixed_i16(self.commanded_rudder_angle, 1e-4);
+        buf.put_fixed_u16(self.heading_to_steer, 1e-4);
+        buf.put_fixed_u16(self.track, 1e-4);
+        buf.put_fixed_u16(self.rudder_limit, 1e-4);
+        buf.put_fixed_u16(self.off_heading_limit, 1e-4);
+        buf.put_fixed_i16(self.radius_of_turn, 1.0);
+        buf.put_fixed_i16(self.rate_of_turn, 3.125e-5);
+        buf.put_fixed_i16(self.off_track_limit, 1.0);
+        buf.put_fixed_u16(self.vessel_heading, 1e-4);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        let limits = data.get_u8();
+        let modes = data.get_u8();
+        let rudder = data.get_u8();
+
+        Ok(Self {
+            rudder_limit_exceeded: get_opt_bool(limits),
+            off_heading_limit_exceeded: get_opt_bool(limits >> 2),
+            off_track_limit_exceeded: get_opt_bool(limits >> 4),
+            overridden: get_opt_bool(limits >> 6),
+            steering_mode: (modes & 0x07).into(),
+            turn_mode: ((modes >> 3) & 0x07).into(),
+            heading_reference: (modes >> 6).into(),
+            commanded_rudder_direction: (rudder >> 5).into(),
+            commanded_rudder_angle: data.get_fixed_i16(1e-4),
+            heading_to_steer: data.get_fixed_u16(1e-4),
+            track: data.get_fixed_u16(1e-4),
+            rudder_limit: data.get_fixed_u16(1e-4),
+            off_heading_limit: data.get_fixed_u16(1e-4),
+            radius_of_turn: data.get_fixed_i16(1.0),
+            rate_of_turn: data.get_fixed_i16(3.125e-5),
+            off_track_limit: data.get_fixed_i16(1.0),
+            vessel_heading: data.get_fixed_u16(1e-4),
+        })
+    }
+}
+
+/// PGN 127245, the ordered and actual rudder angle.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Rudder {
+    pub instance: u8,
+    pub direction_order: RudderDirection,
+    /// Ordered rudder angle in radians, positive to starboard.
+    pub angle_order: Option<f32>,
+    /// Rudder angle in radians, positive to starboard.
+    pub position: Option<f32>,
+}
+
+impl Message for Rudder {
+    const PGN: u32 = 127245;
+
+    type EncodedLen = typenum::U8;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_u8(self.instance);
+        buf.put_u8(0xf8 | (u8::from(self.direction_order) & 0x07));
+        buf.put_fixed_i16(self.angle_order, 1e-4);
+        buf.put_fixed_i16(self.position, 1e-4);
+        buf.put_reserved(2);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        Ok(Self {
+            instance: data.get_u8(),
+            direction_order: (data.get_u8() & 0x07).into(),
+            angle_order: data.get_fixed_i16(1e-4),
+            position: data.get_fixed_i16(1e-4),
+        })
+    }
+}
+
+lookup! {
+    pub enum MobStatus: u8 {
+        EmitterActivated = 0,
+        /// The MOB button on board was pressed.
+        ManualActivation = 1,
+        TestMode = 2,
+        NotActive = 3,
+    }
+}
+
+lookup! {
+    pub enum MobPositionSource: u8 {
+        EstimatedByVessel = 0,
+        ReportedByEmitter = 1,
+    }
+}
+
+lookup! {
+    pub enum MobBatteryStatus: u8 {
+        Good = 0,
+        Low = 1,
+    }
+}
+
+/// PGN 127233, Man Overboard Notification (MOB). This is a fast packet.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ManOverboard {
+    /// Sequence ID, used to tie together related messages.
+    pub sid: Option<u8>,
+    /// Identifies the emitter, unique on the vessel.
+    pub emitter_id: Option<u32>,
+    pub status: MobStatus,
+    /// Time of activation since midnight (UTC).
+    pub activation_time: Option<Duration>,
+    pub position_source: MobPositionSource,
+    /// Date of the position in days since 1970-01-01.
+    pub position_date: Option<u16>,
+    /// Time of the position since midnight (UTC).
+    pub position_time: Option<Duration>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub cog_reference: DirectionReference,
+    pub cog: Option<f32>,
+    pub sog: Option<f32>,
+    /// The MMSI of the vessel the person fell from.
+    pub mmsi: Option<u32>,
+    pub battery_status: MobBatteryStatus,
+}
+
+impl Message for ManOverboard {
+    const PGN: u32 = 127233;
+
+    type EncodedLen = typenum::U35;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_opt_u8(self.sid);
+        buf.put_opt_u32(self.emitter_id);
+        buf.put_u8(0xf8 | (u8::from(self.status) & 0x07));
+        put_time(&mut buf, self.activation_time);
+        buf.put_u8(0xf8 | (u8::from(self.position_source) & 0x07));
+        buf.put_opt_u16(self.position_date);
+        put_time(&mut buf, self.position_time);
+        buf.put_fixed_i32(self.latitude, 1e-7);
+        buf.put_fixed_i32(self.longitude, 1e-7);
+        buf.put_u8(0xfc | (u8::from(self.cog_reference) & 0x03));
+        buf.put_fixed_u16(self.cog, 1e-4);
+        buf.put_fixed_u16(self.sog, 0.01);
+        buf.put_opt_u32(self.mmsi);
+        buf.put_u8(0xf8 | (u8::from(self.battery_status) & 0x07));
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        Ok(Self {
+            sid: data.get_opt_u8(),
+            emitter_id: data.get_opt_u32(),
+            status: (data.get_u8() & 0x07).into(),
+            activation_time: get_time(&mut data),
+            position_source: (data.get_u8() & 0x07).into(),
+            position_date: data.get_opt_u16(),
+            position_time: get_time(&mut data),
+            latitude: data.get_fixed_i32(1e-7),
+            longitude: data.get_fixed_i32(1e-7),
+            cog_reference: (data.get_u8() & 0x03).into(),
+            cog: data.get_fixed_u16(1e-4),
+            sog: data.get_fixed_u16(0.01),
+            mmsi: data.get_opt_u32(),
+            battery_status: (data.get_u8() & 0x07).into(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::well_known::round_trip;
+
+    #[test]
+    fn heading_track_control() {
+        let msg: HeadingTrackControl = round_trip(&[
+            0x31, 0x44, 0x5f, 0x2f, 0xf9, 0x5c, 0x3d, 0xff, 0xff, 0xdd, 0x17, 0xd1, 0x06, 0xf4,
+            0x01, 0xc0, 0xfe, 0xff, 0x7f, 0x54, 0x3d,
+        ]);
+
+        assert_eq!(msg.rudder_limit_exceeded, Some(true));
+        assert_eq!(msg.off_heading_limit_exceeded, Some(false));
+        assert_eq!(msg.off_track_limit_exceeded, None);
+        assert_eq!(msg.overridden, Some(false));
+        assert_eq!(msg.steering_mode, SteeringMode::HeadingControl);
+        assert_eq!(msg.turn_mode, TurnMode::RudderLimitControlled);
+        assert_eq!(msg.heading_reference, DirectionReference::Magnetic);
+        assert_eq!(msg.commanded_rudder_direction, RudderDirection::MoveToPort);
+        assert!((msg.commanded_rudder_angle.unwrap() + 0.1745).abs() < 1e-6);
+        assert!((msg.heading_to_steer.unwrap() - core::f32::consts::FRAC_PI_2).abs() < 1e-4);
+        assert_eq!(msg.track, None);
+        assert_eq!(msg.radius_of_turn, Some(500.0));
+        assert!((msg.rate_of_turn.unwrap() + 0.01).abs() < 1e-6);
+        assert_eq!(msg.off_track_limit, None);
+    }
+
+    #[test]
+    fn heading_track_control_bits() {
+        let msg = HeadingTrackControl {
+            rudder_limit_exceeded: None,
+            off_heading_limit_exceeded: Some(true),
+            off_track_limit_exceeded: Some(false),
+            overridden: Some(true),
+            steering_mode: SteeringMode::TrackControl,
+            turn_mode: TurnMode::RadiusControlled,
+            heading_reference: DirectionReference::True,
+            commanded_rudder_direction: RudderDirection::MoveToStarboard,
+            commanded_rudder_angle: None,
+            heading_to_steer: None,
+            track: None,
+            rudder_limit: None,
+            off_heading_limit: None,
+            radius_of_turn: None,
+            rate_of_turn: None,
+            off_track_limit: None,
+            vessel_heading: None,
+        };
+        let mut buf = [0; 21];
+        msg.encode(&mut buf);
+
+        assert_eq!(buf[..3], [0b0100_0111, 0b0001_0101, 0b0011_1111]);
+        assert_eq!(HeadingTrackControl::decode(&buf), Ok(msg));
+    }
+
+    #[test]
+    fn rudder() {
+        let msg: Rudder = round_trip(&[0x00, 0xf9, 0x69, 0x03, 0xb4, 0x01, 0xff, 0xff]);
+
+        assert_eq!(msg.instance, 0);
+        assert_eq!(msg.direction_order, RudderDirection::MoveToStarboard);
+        assert!((msg.angle_order.unwrap() - 0.0873).abs() < 1e-6);
+        assert!((msg.position.unwrap() - 0.0436).abs() < 1e-6);
+    }
+
+    #[test]
+    fn man_overboard() {
+        let msg: ManOverboard = round_trip(&[
+            0x02, 0x78, 0x56, 0x34, 0x12, 0xf8, 0x00, 0x9f, 0xff, 0x1a, 0xf9, 0x38, 0x4a, 0x40,
+            0x3b, 0x00, 0x1b, 0xc8, 0xee, 0x5c, 0x23, 0xb0, 0x0c, 0xc5, 0x0a, 0xfc, 0xb8, 0x7a,
+            0x96, 0x00, 0x80, 0x76, 0xcd, 0x0f, 0xf9,
+        ]);
+
+        assert_eq!(msg.emitter_id, Some(0x1234_5678));
+        assert_eq!(msg.status, MobStatus::EmitterActivated);
+        assert_eq!(msg.activation_time, Some(Duration::from_secs(45296)));
+        assert_eq!(msg.position_source, MobPositionSource::ReportedByEmitter);
+        assert_eq!(msg.position_date, Some(19000));
+        assert_eq!(msg.position_time, Some(Duration::from_secs(45300)));
+        assert!((msg.latitude.unwrap() - 59.3293).abs() < 1e-7);
+        assert!((msg.longitude.unwrap() - 18.0686).abs() < 1e-7);
+        assert_eq!(msg.cog_reference, DirectionReference::True);
+        assert!((msg.sog.unwrap() - 1.5).abs() < 1e-6);
+        assert_eq!(msg.mmsi, Some(265_123_456));
+        assert_eq!(msg.battery_status, MobBatteryStatus::Low);
+    }
+}
@@ -0,0 +1,130 @@
+//! Digital switching: the state of switch banks and commands to change it.
+
+use generic_array::typenum::{self, Unsigned};
+
+use super::{get_opt_bool, put_opt_bool};
+use crate::{Buf, BufMut, Message};
+
+/// The number of channels in a switch bank.
+pub const SWITCH_BANK_SIZE: usize = 28;
+
+/// Read 28 2-bit off/on fields.
+fn get_states(buf: &mut &[u8]) -> [Option<bool>; SWITCH_BANK_SIZE] {
+    let mut bytes = [0; 8];
+    bytes[..7].copy_from_slice(buf.split_off(..7).unwrap());
+    let bits = u64::from_le_bytes(bytes);
+
+    core::array::from_fn(|i| get_opt_bool((bits >> (2 * i)) as u8))
+}
+
+fn put_states(buf: &mut &mut [u8], states: &[Option<bool>; SWITCH_BANK_SIZE]) {
+    let bits = states.iter().enumerate().fold(0u64, |bits, (i, &state)| {
+        bits | u64::from(put_opt_bool(state)) << (2 * i)
+    });
+
+    buf.split_off_mut(..7)
+        .unwrap()
+        .copy_from_slice(&bits.to_le_bytes()[..7]);
+}
+
+/// PGN 127501, the state of the indicators of a switch bank. Indicators
+/// that the bank does not have are not available.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BinarySwitchBankStatus {
+    pub instance: u8,
+    pub indicators: [Option<bool>; SWITCH_BANK_SIZE],
+}
+
+impl Message for BinarySwitchBankStatus {
+    const PGN: u32 = 127501;
+
+    type EncodedLen = typenum::U8;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_u8(self.instance);
+        put_states(&mut buf, &self.indicators);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        Ok(Self {
+            instance: data.get_u8(),
+            indicators: get_states(&mut data),
+        })
+    }
+}
+
+/// PGN 127502, a command to turn the switches of a bank on or off. Switches
+/// that are not available should be left unchanged.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SwitchBankControl {
+    pub instance: u8,
+    pub switches: [Option<bool>; SWITCH_BANK_SIZE],
+}
+
+impl Message for SwitchBankControl {
+    const PGN: u32 = 127502;
+
+    type EncodedLen = typenum::U8;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_u8(self.instance);
+        put_states(&mut buf, &self.switches);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        Ok(Self {
+            instance: data.get_u8(),
+            switches: get_states(&mut data),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::well_known::round_trip;
+
+    #[test]
+    fn binary_switch_bank_status() {
+        let msg: BinarySwitchBankStatus =
+            round_trip(&[0x02, 0x14, 0xff, 0xff, 0xff, 0xff, 0xff, 0xcf]);
+
+        assert_eq!(msg.instance, 2);
+        assert_eq!(msg.indicators[..3], [Some(false), Some(true), Some(true)]);
+        assert_eq!(msg.indicators[3], Some(false));
+        assert_eq!(msg.indicators[4..26], [None; 22]);
+        assert_eq!(msg.indicators[26], Some(false));
+        assert_eq!(msg.indicators[27], None);
+    }
+
+    #[test]
+    fn switch_bank_control() {
+        let mut switches = [None; SWITCH_BANK_SIZE];
+        switches[1] = Some(true);
+        switches[27] = Some(false);
+
+        let msg = SwitchBankControl {
+            instance: 0,
+            switches,
+        };
+        let mut buf = [0; 8];
+        msg.encode(&mut buf);
+
+        assert_eq!(buf, [0x00, 0xf7, 0xff, 0xff, 0xff, 0xff, 0xff, 0x3f]);
+        assert_eq!(SwitchBankControl::decode(&buf), Ok(msg));
+    }
+}
@@ -0,0 +1,89 @@
+//! Messages concerning the network as a whole, such as the system time.
+
+use core::time::Duration;
+
+use generic_array::typenum::{self, Unsigned};
+
+use super::{get_time, put_time};
+use crate::{Buf, BufMut, Message};
+
+lookup! {
+    /// Where a [`SystemTime`] comes from.
+    pub enum TimeSource: u8 {
+        Gps = 0,
+        Glonass = 1,
+        RadioStation = 2,
+        LocalCesiumClock = 3,
+        LocalRubidiumClock = 4,
+        LocalCrystalClock = 5,
+    }
+}
+
+/// PGN 126992, the current UTC date and time and where it comes from.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SystemTime {
+    /// Sequence ID, used to tie together related messages.
+    pub sid: Option<u8>,
+    pub source: TimeSource,
+    /// Days since 1970-01-01.
+    pub date: Option<u16>,
+    /// Time since midnight (UTC).
+    pub time: Option<Duration>,
+}
+
+impl SystemTime {
+    /// The time since 1970-01-01 (UTC), if both the date and the time are
+    /// available.
+    #[must_use]
+    pub fn utc(&self) -> Option<Duration> {
+        Some(Duration::from_secs(u64::from(self.date?) * 86_400) + self.time?)
+    }
+}
+
+impl Message for SystemTime {
+    const PGN: u32 = 126992;
+
+    type EncodedLen = typenum::U8;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_opt_u8(self.sid);
+        buf.put_u8(0xf0 | (u8::from(self.source) & 0x0f));
+        buf.put_opt_u16(self.date);
+        put_time(&mut buf, self.time);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        Ok(Self {
+            sid: data.get_opt_u8(),
+            source: (data.get_u8() & 0x0f).into(),
+            date: data.get_opt_u16(),
+            time: get_time(&mut data),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::well_known::round_trip;
+
+    #[test]
+    fn system_time() {
+        let msg: SystemTime = round_trip(&[0xff, 0xf0, 0x38, 0x4a, 0x58, 0xba, 0xff, 0x1a]);
+
+        assert_eq!(msg.source, TimeSource::Gps);
+        assert_eq!(msg.date, Some(19000));
+        assert_eq!(msg.time, Some(Duration::from_millis(45_296_700)));
+        assert_eq!(
+            msg.utc(),
+            Some(Duration::from_millis(19000 * 86_400_000 + 45_296_700))
+        );
+    }
+}
@@ -0,0 +1,237 @@
+//! Bow and stern thruster control and status.
+//!
+//! Generated from the canboat PGN database by `cargo xtask codegen`. Do
+//! not edit this file; change `xtask/canboat.json` or the generator
+//! instead.
+
+use core::time::Duration;
+
+use generic_array::typenum::{self, Unsigned};
+
+use super::{get_opt_bool, put_opt_bool};
+use crate::{Buf, BufMut, Message};
+
+lookup! {
+    /// Canboat lookup `THRUSTER_DIRECTION_CONTROL`.
+    pub enum ThrusterDirectionControl: u8 {
+        Off = 0,
+        Ready = 1,
+        ToPort = 2,
+        ToStarboard = 3,
+    }
+}
+
+lookup! {
+    /// Canboat lookup `THRUSTER_MOTOR_TYPE`.
+    pub enum ThrusterMotorType: u8 {
+        Value12Vdc = 0,
+        Value24Vdc = 1,
+        Value48Vdc = 2,
+        Value24Vac = 3,
+        Hydraulic = 4,
+    }
+}
+
+lookup! {
+    /// Canboat lookup `THRUSTER_RETRACT_CONTROL`.
+    pub enum ThrusterRetractControl: u8 {
+        Off = 0,
+        Extend = 1,
+        Retract = 2,
+    }
+}
+
+flags! {
+    /// Canboat bit lookup `THRUSTER_CONTROL_EVENTS`.
+    pub struct ThrusterControlEvents: u8 {
+        another_device_controlling_thruster = 0,
+        boat_speed_too_fast_to_safely_use_thruster = 1,
+    }
+}
+
+flags! {
+    /// Canboat bit lookup `THRUSTER_MOTOR_EVENTS`.
+    pub struct ThrusterMotorEvents: u8 {
+        motor_over_temperature_cutout = 0,
+        motor_over_current_cutout = 1,
+        low_oil_level_warning = 2,
+        oil_over_temperature_warning = 3,
+        controller_under_voltage_cutout = 4,
+        manufacturer_defined = 5,
+    }
+}
+
+/// PGN 128006, Thruster Control Status.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ThrusterControlStatus {
+    pub sid: Option<u8>,
+    pub identifier: Option<u8>,
+    pub direction_control: ThrusterDirectionControl,
+    pub power_enabled: Option<bool>,
+    pub retract_control: ThrusterRetractControl,
+    /// Speed control in percent.
+    pub speed_control: Option<f32>,
+    pub control_events: ThrusterControlEvents,
+    /// Command timeout in seconds.
+    pub command_timeout: Option<f32>,
+    /// Azimuth control in radians.
+    pub azimuth_control: Option<f32>,
+}
+
+impl Message for ThrusterControlStatus {
+    const PGN: u32 = 128006;
+
+    type EncodedLen = typenum::U8;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_opt_u8(self.sid);
+        buf.put_opt_u8(self.identifier);
+        buf.put_u8(
+            (u8::from(self.direction_control) & 0x0f)
+                | put_opt_bool(self.power_enabled) << 4
+                | (u8::from(self.retract_control) & 0x03) << 6,
+        );
+        buf.put_fixed_u8(self.speed_control, 1.0);
+        buf.put_u8(self.control_events.into());
+        buf.put_fixed_u8(self.command_timeout, 0.005);
+        buf.put_fixed_u16(self.azimuth_control, 0.0001);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        let sid = data.get_opt_u8();
+        let identifier = data.get_opt_u8();
+        let b = data.get_u8();
+        let direction_control = (b & 0x0f).into();
+        let power_enabled = get_opt_bool(b >> 4);
+        let retract_control = (b >> 6).into();
+        let speed_control = data.get_fixed_u8(1.0);
+        let control_events = data.get_u8().into();
+        let command_timeout = data.get_fixed_u8(0.005);
+        let azimuth_control = data.get_fixed_u16(0.0001);
+
+        Ok(Self {
+            sid,
+            identifier,
+            direction_control,
+            power_enabled,
+            retract_control,
+            speed_control,
+            control_events,
+            command_timeout,
+            azimuth_control,
+        })
+    }
+}
+
+/// PGN 128007, Thruster Information.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ThrusterInformation {
+    pub identifier: Option<u8>,
+    pub motor_type: ThrusterMotorType,
+    /// Power rating in watts.
+    pub power_rating: Option<f32>,
+    /// Maximum temperature rating in kelvin.
+    pub maximum_temperature_rating: Option<f32>,
+    /// Maximum rotational speed in rpm.
+    pub maximum_rotational_speed: Option<f32>,
+}
+
+impl Message for ThrusterInformation {
+    const PGN: u32 = 128007;
+
+    type EncodedLen = typenum::U8;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_opt_u8(self.identifier);
+        buf.put_u8((u8::from(self.motor_type) & 0x0f) | 0xf0);
+        buf.put_fixed_u16(self.power_rating, 1.0);
+        buf.put_fixed_u16(self.maximum_temperature_rating, 0.01);
+        buf.put_fixed_u16(self.maximum_rotational_speed, 0.25);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        let identifier = data.get_opt_u8();
+        let b = data.get_u8();
+        let motor_type = (b & 0x0f).into();
+        let power_rating = data.get_fixed_u16(1.0);
+        let maximum_temperature_rating = data.get_fixed_u16(0.01);
+        let maximum_rotational_speed = data.get_fixed_u16(0.25);
+
+        Ok(Self {
+            identifier,
+            motor_type,
+            power_rating,
+            maximum_temperature_rating,
+            maximum_rotational_speed,
+        })
+    }
+}
+
+/// PGN 128008, Thruster Motor Status.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ThrusterMotorStatus {
+    pub sid: Option<u8>,
+    pub identifier: Option<u8>,
+    pub motor_events: ThrusterMotorEvents,
+    /// Current in amperes.
+    pub current: Option<f32>,
+    /// Temperature in kelvin.
+    pub temperature: Option<f32>,
+    pub operating_time: Option<Duration>,
+}
+
+impl Message for ThrusterMotorStatus {
+    const PGN: u32 = 128008;
+
+    type EncodedLen = typenum::U8;
+
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_opt_u8(self.sid);
+        buf.put_opt_u8(self.identifier);
+        buf.put_u8(self.motor_events.into());
+        buf.put_fixed_u8(self.current, 1.0);
+        buf.put_fixed_u16(self.temperature, 0.01);
+        buf.put_opt_u16(self.operating_time.map(|d| (d.as_secs() / 60) as u16));
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        if data.len() < Self::EncodedLen::USIZE {
+            return Err(());
+        }
+
+        let sid = data.get_opt_u8();
+        let identifier = data.get_opt_u8();
+        let motor_events = data.get_u8().into();
+        let current = data.get_fixed_u8(1.0);
+        let temperature = data.get_fixed_u16(0.01);
+        let operating_time = data
+            .get_opt_u16()
+            .map(|t| Duration::from_secs(u64::from(t) * 60));
+
+        Ok(Self {
+            sid,
+            identifier,
+            motor_events,
+            current,
+            temperature,
+            operating_time,
+        })
+    }
+}
@@ -0,0 +1,315 @@
+use std::{cell::RefCell, collections::VecDeque, convert::Infallible, rc::Rc};
+
+use embedded_can::{ExtendedId, Id};
+use nmea2000::{
+    blocking::Client, id::DESTINATION_BROADCAST, node::MIN_SRC, time::Instant, typenum, Buf,
+    BufMut, Decoder, Message, Metadata, NmeaFrame,
+};
+
+#[derive(Debug, Clone)]
+struct Frame {
+    id: ExtendedId,
+    data: heapless::Vec<u8, 8>,
+}
+
+impl embedded_can::Frame for Frame {
+    fn new(id: impl Into<Id>, data: &[u8]) -> Option<Self> {
+        match id.into() {
+            Id::Extended(id) => Some(Self {
+                id,
+                data: heapless::Vec::from_slice(data).ok()?,
+            }),
+            Id::Standard(_) => None,
+        }
+    }
+
+    fn new_remote(_id: impl Into<Id>, _dlc: usize) -> Option<Self> {
+        None
+    }
+
+    fn is_extended(&self) -> bool {
+        true
+    }
+
+    fn is_remote_frame(&self) -> bool {
+        false
+    }
+
+    fn id(&self) -> Id {
+        Id::Extended(self.id)
+    }
+
+    fn dlc(&self) -> usize {
+        self.data.len()
+    }
+
+    fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+type Bus = Rc<RefCell<Vec<VecDeque<Frame>>>>;
+
+/// A node on an in-process CAN bus. Transmitted frames are delivered to the
+/// receive queues of all other nodes.
+struct FakeCan {
+    node: usize,
+    bus: Bus,
+}
+
+impl embedded_can::nb::Can for FakeCan {
+    type Frame = Frame;
+
+    type Error = Infallible;
+
+    fn transmit(&mut self, frame: &Self::Frame) -> nb::Result<Option<Self::Frame>, Self::Error> {
+        for (i, queue) in self.bus.borrow_mut().iter_mut().enumerate() {
+            if i != self.node {
+                queue.push_back(frame.clone());
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn receive(&mut self) -> nb::Result<Self::Frame, Self::Error> {
+        self.bus.borrow_mut()[self.node]
+            .pop_front()
+            .ok_or(nb::Error::WouldBlock)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct HelloWorld {
+    int: u64,
+}
+
+impl Message for HelloWorld {
+    const PGN: u32 = 130_816;
+
+    type EncodedLen = typenum::U8;
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_u64(self.int);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        Ok(HelloWorld {
+            int: data.get_u64(),
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct LongHello {
+    a: u64,
+    b: u64,
+}
+
+impl Message for LongHello {
+    const PGN: u32 = 130_817;
+
+    type EncodedLen = typenum::U16;
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_u64(self.a);
+        buf.put_u64(self.b);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        Ok(LongHello {
+            a: data.get_u64(),
+            b: data.get_u64(),
+        })
+    }
+}
+
+#[test]
+fn superloop() {
+    let bus: Bus = Rc::new(RefCell::new(vec![VecDeque::new(), VecDeque::new()]));
+    let clock = Rc::new(RefCell::new(Instant::ZERO));
+    let now = || {
+        let clock = clock.clone();
+        move || *clock.borrow()
+    };
+
+    let mut alice = Client::new(
+        0x1234_5678,
+        FakeCan {
+            node: 0,
+            bus: bus.clone(),
+        },
+        now(),
+    );
+    let mut bob = Client::new(
+        0xdead_beef,
+        FakeCan {
+            node: 1,
+            bus: bus.clone(),
+        },
+        now(),
+    );
+
+    let hello = NmeaFrame::from_message(
+        nmea2000::Id::new(4, HelloWorld::PGN, 0, DESTINATION_BROADCAST),
+        &HelloWorld { int: 37 },
+    );
+
+    alice.poll().unwrap();
+    assert_eq!(alice.send(hello.clone()), Err(nb::Error::WouldBlock));
+
+    let mut hellos = Decoder::<HelloWorld, 1>::new();
+    let mut long_hellos = Decoder::<LongHello, 1>::new();
+    let mut received = Vec::new();
+
+    for _ in 0..50 {
+        *clock.borrow_mut() += core::time::Duration::from_millis(10);
+
+        while alice.poll().unwrap().is_some() {}
+
+        while let Some(frame) = bob.poll().unwrap() {
+            assert_eq!(frame.meta, Metadata::received(*clock.borrow()));
+
+            if let Some(res) = hellos.decode(&frame) {
+                received.push(res.map(|r| r.msg.int));
+            }
+
+            if let Some(res) = long_hellos.decode(&frame) {
+                received.push(res.map(|r| r.msg.a + r.msg.b));
+            }
+        }
+
+        if alice.node().is_ready() && received.is_empty() {
+            alice.send(hello.clone()).unwrap();
+            alice
+                .send_fast_packet(LongHello { a: 1, b: 2 }, 4, DESTINATION_BROADCAST)
+                .unwrap();
+        }
+    }
+
+    assert_eq!(received, [Ok(37), Ok(3)]);
+    // bob has a greater name and therefore had to cede the first address
+    assert_eq!(alice.src(), MIN_SRC);
+    assert_eq!(bob.src(), MIN_SRC + 1);
+}
+
+#[test]
+fn loopback() {
+    let bus: Bus = Rc::new(RefCell::new(vec![VecDeque::new()]));
+    let clock = Rc::new(RefCell::new(Instant::ZERO));
+
+    let mut client = Client::new(0x1234_5678, FakeCan { node: 0, bus }, {
+        let clock = clock.clone();
+        move || *clock.borrow()
+    });
+    client.set_loopback(true);
+
+    while !client.node().is_ready() {
+        *clock.borrow_mut() += core::time::Duration::from_millis(10);
+        assert_eq!(client.poll(), Ok(None));
+    }
+
+    client
+        .send(NmeaFrame::from_message(
+            nmea2000::Id::new(4, HelloWorld::PGN, 0, DESTINATION_BROADCAST),
+            &HelloWorld { int: 37 },
+        ))
+        .unwrap();
+
+    let frame = client.poll().unwrap().unwrap();
+    assert_eq!(frame.id.source(), MIN_SRC);
+    assert_eq!(frame.meta, Metadata::local(*clock.borrow()));
+    assert_eq!(HelloWorld::decode(&frame.data), Ok(HelloWorld { int: 37 }));
+    assert_eq!(client.poll(), Ok(None));
+}
+
+/// A controller with a single mailbox. A frame with a higher priority
+/// displaces the frame waiting in the mailbox.
+#[derive(Default)]
+struct Mailbox {
+    waiting: Option<Frame>,
+    sent: Vec<Frame>,
+}
+
+struct DisplacingCan(Rc<RefCell<Mailbox>>);
+
+impl embedded_can::nb::Can for DisplacingCan {
+    type Frame = Frame;
+
+    type Error = Infallible;
+
+    fn transmit(&mut self, frame: &Self::Frame) -> nb::Result<Option<Self::Frame>, Self::Error> {
+        let mut mailbox = self.0.borrow_mut();
+
+        match mailbox.waiting.take() {
+            None => {
+                mailbox.waiting = Some(frame.clone());
+                Ok(None)
+            }
+            Some(waiting) if frame.id.as_raw() < waiting.id.as_raw() => {
+                mailbox.waiting = Some(frame.clone());
+                Ok(Some(waiting))
+            }
+            Some(waiting) => {
+                mailbox.waiting = Some(waiting);
+                Err(nb::Error::WouldBlock)
+            }
+        }
+    }
+
+    fn receive(&mut self) -> nb::Result<Self::Frame, Self::Error> {
+        Err(nb::Error::WouldBlock)
+    }
+}
+
+#[test]
+fn displaced_frame() {
+    let mailbox = Rc::new(RefCell::new(Mailbox::default()));
+    let clock = Rc::new(RefCell::new(Instant::ZERO));
+
+    let mut client = Client::new(0x1234_5678, DisplacingCan(mailbox.clone()), {
+        let clock = clock.clone();
+        move || *clock.borrow()
+    });
+
+    let drain = || {
+        let mut mailbox = mailbox.borrow_mut();
+        if let Some(frame) = mailbox.waiting.take() {
+            mailbox.sent.push(frame);
+        }
+    };
+
+    while !client.node().is_ready() {
+        *clock.borrow_mut() += core::time::Duration::from_millis(10);
+        assert_eq!(client.poll(), Ok(None));
+        drain();
+    }
+
+    mailbox.borrow_mut().sent.clear();
+
+    let hello = |prio, int| {
+        NmeaFrame::from_message(
+            nmea2000::Id::new(prio, HelloWorld::PGN, 0, DESTINATION_BROADCAST),
+            &HelloWorld { int },
+        )
+    };
+
+    client.send(hello(6, 1)).unwrap();
+    client.send(hello(2, 2)).unwrap();
+    // the displaced frame has to be sent first
+    assert_eq!(client.send(hello(6, 3)), Err(nb::Error::WouldBlock));
+
+    drain();
+    assert_eq!(client.poll(), Ok(None));
+    drain();
+
+    let sent: Vec<u64> = mailbox
+        .borrow()
+        .sent
+        .iter()
+        .map(|f| HelloWorld::decode(&f.data).unwrap().int)
+        .collect();
+    assert_eq!(sent, [2, 1]);
+}
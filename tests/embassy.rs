@@ -0,0 +1,318 @@
+use core::{cell::RefCell, convert::Infallible};
+
+use embassy_executor::Executor;
+use embassy_futures::block_on;
+use embassy_sync::{
+    blocking_mutex::raw::CriticalSectionRawMutex, channel, signal::Signal, zerocopy_channel,
+};
+use embassy_time::{with_timeout, Duration};
+use embedded_can::{ExtendedId, Id};
+use nmea2000::{
+    client::{AsyncCan, Shared},
+    id::DESTINATION_BROADCAST,
+    node::MIN_SRC,
+    service::switch_bank::SwitchBank,
+    typenum,
+    well_known::{
+        pgn_list::{PgnList, PgnListFunction},
+        switching::BinarySwitchBankStatus,
+        IsoAddressClaim, IsoRequest,
+    },
+    Buf, BufMut, Decoder, Message, NmeaFrame,
+};
+use static_cell::StaticCell;
+
+#[derive(Debug, Clone)]
+struct Frame {
+    id: ExtendedId,
+    data: heapless::Vec<u8, 8>,
+}
+
+impl embedded_can::Frame for Frame {
+    fn new(id: impl Into<Id>, data: &[u8]) -> Option<Self> {
+        match id.into() {
+            Id::Extended(id) => Some(Self {
+                id,
+                data: heapless::Vec::from_slice(data).ok()?,
+            }),
+            Id::Standard(_) => None,
+        }
+    }
+
+    fn new_remote(_id: impl Into<Id>, _dlc: usize) -> Option<Self> {
+        None
+    }
+
+    fn is_extended(&self) -> bool {
+        true
+    }
+
+    fn is_remote_frame(&self) -> bool {
+        false
+    }
+
+    fn id(&self) -> Id {
+        Id::Extended(self.id)
+    }
+
+    fn dlc(&self) -> usize {
+        self.data.len()
+    }
+
+    fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+type Sent = channel::Channel<CriticalSectionRawMutex, Frame, 8>;
+
+/// A controller alone on the bus. Sent frames are recorded, and like a real
+/// controller, it does not receive them itself.
+struct LonelyCan(&'static Sent);
+
+impl core::fmt::Debug for LonelyCan {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("LonelyCan").finish()
+    }
+}
+
+impl AsyncCan for LonelyCan {
+    type Error = Infallible;
+
+    type Frame = Frame;
+
+    async fn send(&mut self, frame: Self::Frame) -> Result<(), Self::Error> {
+        self.0.try_send(frame).unwrap();
+        Ok(())
+    }
+
+    async fn receive(&mut self) -> Result<Self::Frame, Self::Error> {
+        core::future::pending().await
+    }
+}
+
+/// A controller that receives the frames put into its inbox.
+struct ScriptedCan {
+    sent: &'static Sent,
+    inbox: &'static Sent,
+}
+
+impl core::fmt::Debug for ScriptedCan {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ScriptedCan").finish()
+    }
+}
+
+impl AsyncCan for ScriptedCan {
+    type Error = Infallible;
+
+    type Frame = Frame;
+
+    async fn send(&mut self, frame: Self::Frame) -> Result<(), Self::Error> {
+        self.sent.try_send(frame).unwrap();
+        Ok(())
+    }
+
+    async fn receive(&mut self) -> Result<Self::Frame, Self::Error> {
+        Ok(self.inbox.receive().await)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct HelloWorld {
+    int: u64,
+}
+
+impl Message for HelloWorld {
+    const PGN: u32 = 130_816;
+
+    type EncodedLen = typenum::U8;
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_u64(self.int);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        Ok(HelloWorld {
+            int: data.get_u64(),
+        })
+    }
+}
+
+static SENT: Sent = channel::Channel::new();
+
+/// The frame returned by the event loop with loopback enabled, and whether
+/// it returned nothing with loopback disabled.
+static DONE: Signal<CriticalSectionRawMutex, (NmeaFrame, bool)> = Signal::new();
+
+fn hello(int: u64) -> NmeaFrame {
+    NmeaFrame::from_message(
+        nmea2000::Id::new(4, HelloWorld::PGN, 0, DESTINATION_BROADCAST),
+        &HelloWorld { int },
+    )
+}
+
+#[embassy_executor::task]
+async fn lonely() {
+    let mut buf = [NmeaFrame::DEFAULT; 8];
+    let mut channel = zerocopy_channel::Channel::new(&mut buf);
+    let (mut event_loop, mut client) =
+        nmea2000::client::new(0x1234_5678, LonelyCan(&SENT), &mut channel);
+    event_loop.set_loopback(true);
+
+    client.send(hello(37)).await;
+    // returned once the address claim has finished and it has been sent
+    let looped = event_loop.poll().await.unwrap();
+
+    event_loop.set_loopback(false);
+    client.send(hello(38)).await;
+    let quiet = with_timeout(Duration::from_millis(100), event_loop.poll())
+        .await
+        .is_err();
+
+    DONE.signal((looped, quiet));
+}
+
+#[test]
+fn loopback() {
+    static EXECUTOR: StaticCell<Executor> = StaticCell::new();
+
+    std::thread::spawn(|| {
+        EXECUTOR.init_with(Executor::new).run(|spawner| {
+            spawner.must_spawn(lonely());
+        });
+    });
+
+    let (looped, quiet) = block_on(DONE.wait());
+    assert!(looped.meta.local);
+    assert!(looped.meta.timestamp.is_some());
+    assert_eq!(looped.id.source(), MIN_SRC);
+    assert_eq!(HelloWorld::decode(&looped.data), Ok(HelloWorld { int: 37 }));
+    assert!(quiet);
+
+    let claim = SENT.try_receive().unwrap();
+    assert_eq!(
+        nmea2000::Id::from_can_id(claim.id).pgn(),
+        IsoAddressClaim::PGN
+    );
+    assert_eq!(SENT.try_receive().unwrap().data, looped.data);
+    assert_eq!(
+        HelloWorld::decode(&SENT.try_receive().unwrap().data),
+        Ok(HelloWorld { int: 38 })
+    );
+}
+
+static BANK_SENT: Sent = channel::Channel::new();
+
+static BANK: Shared<SwitchBank<2>> = Shared::new(RefCell::new(SwitchBank::new(5)));
+
+#[embassy_executor::task]
+async fn switch_bank() {
+    let mut buf = [NmeaFrame::DEFAULT; 8];
+    let mut channel = zerocopy_channel::Channel::new(&mut buf);
+    let (mut event_loop, _client) =
+        nmea2000::client::new(0x1234_5678, LonelyCan(&BANK_SENT), &mut channel);
+    event_loop.add_service(&BANK).unwrap();
+
+    BANK.lock(|bank| bank.borrow_mut().set(1, true));
+
+    loop {
+        event_loop.poll().await.unwrap();
+    }
+}
+
+#[test]
+fn service() {
+    static EXECUTOR: StaticCell<Executor> = StaticCell::new();
+
+    std::thread::spawn(|| {
+        EXECUTOR.init_with(Executor::new).run(|spawner| {
+            spawner.must_spawn(switch_bank());
+        });
+    });
+
+    let claim = block_on(BANK_SENT.receive());
+    assert_eq!(
+        nmea2000::Id::from_can_id(claim.id).pgn(),
+        IsoAddressClaim::PGN
+    );
+
+    // sent once the address claim has finished
+    let status = block_on(BANK_SENT.receive());
+    let id = nmea2000::Id::from_can_id(status.id);
+    assert_eq!(id.pgn(), BinarySwitchBankStatus::PGN);
+    assert_eq!(id.source(), MIN_SRC);
+    let status = BinarySwitchBankStatus::decode(&status.data).unwrap();
+    assert_eq!(status.instance, 5);
+    assert_eq!(status.indicators[..3], [Some(false), Some(true), None]);
+}
+
+static LISTS_SENT: Sent = channel::Channel::new();
+
+static LISTS_INBOX: Sent = channel::Channel::new();
+
+#[embassy_executor::task]
+async fn pgn_lists() {
+    let mut buf = [NmeaFrame::DEFAULT; 8];
+    let mut channel = zerocopy_channel::Channel::new(&mut buf);
+    let can = ScriptedCan {
+        sent: &LISTS_SENT,
+        inbox: &LISTS_INBOX,
+    };
+    let (mut event_loop, _client) = nmea2000::client::new(0x1234_5678, can, &mut channel);
+    event_loop
+        .pgn_lists_mut()
+        .register_receive::<HelloWorld>()
+        .unwrap();
+
+    loop {
+        event_loop.poll().await.unwrap();
+    }
+}
+
+#[test]
+fn pgn_list_request() {
+    static EXECUTOR: StaticCell<Executor> = StaticCell::new();
+
+    std::thread::spawn(|| {
+        EXECUTOR.init_with(Executor::new).run(|spawner| {
+            spawner.must_spawn(pgn_lists());
+        });
+    });
+
+    let claim = block_on(LISTS_SENT.receive());
+    assert_eq!(
+        nmea2000::Id::from_can_id(claim.id).pgn(),
+        IsoAddressClaim::PGN
+    );
+
+    // requests are only answered once the address claim has finished
+    std::thread::sleep(std::time::Duration::from_micros(
+        2 * nmea2000::client::ADDRESS_CLAIM_TIMEOUT.as_micros(),
+    ));
+
+    let request = NmeaFrame::from_message(
+        nmea2000::Id::new(6, IsoRequest::PGN, 30, MIN_SRC),
+        &IsoRequest { pgn: PgnList::PGN },
+    );
+    LISTS_INBOX.try_send(request.to_can_frame()).unwrap();
+
+    let mut decoder = Decoder::<PgnList, 1>::new();
+    let mut lists = heapless::Vec::<_, 2>::new();
+
+    while lists.len() < 2 {
+        let frame = block_on(LISTS_SENT.receive());
+        let frame = NmeaFrame::new(nmea2000::Id::from_can_id(frame.id), frame.data);
+
+        if let Some(list) = decoder.decode(&frame) {
+            lists.push(list.unwrap()).unwrap();
+        }
+    }
+
+    assert_eq!(lists[0].id.source(), MIN_SRC);
+    assert_eq!(lists[0].id.destination(), 30);
+    assert_eq!(lists[0].msg.function, PgnListFunction::Transmit);
+    assert_eq!(lists[1].msg.function, PgnListFunction::Receive);
+    assert!(lists[1].msg.pgns.contains(&HelloWorld::PGN));
+}
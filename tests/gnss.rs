@@ -0,0 +1,198 @@
+//! Decoding GNSS messages from frames in the plain format read by canboat's
+//! `analyzer`: one CAN frame per line, with the timestamp, priority, PGN,
+//! source, destination, length and data bytes.
+//!
+//! These frames are not a recording. None of a real receiver was at hand
+//! when this was written, so they were assembled from canboat's field
+//! definitions for a fix in the Stockholm archipelago, and the expected
+//! values are what those definitions decode them to. A recording from a
+//! real device should be added as a separate constant once one is
+//! available, citing the device it was taken from.
+
+use core::time::Duration;
+
+use nmea2000::{
+    well_known::{
+        gnss::{
+            CogSogRapidUpdate, Datum, GnssDops, GnssIntegrity, GnssMethod, GnssMode,
+            GnssPositionData, GnssSatsInView, GnssType, PositionRapidUpdate, RangeResidualMode,
+            SatelliteStatus, TimeDate,
+        },
+        DirectionReference,
+    },
+    Decoder, Id, Message, NmeaFrame,
+};
+
+const SYNTHETIC: &str = "\
+2026-10-12-09:14:03.120,2,129025,35,255,8,b0,c6,56,23,89,57,46,0b
+2026-10-12-09:14:03.123,2,129026,35,255,8,0c,fc,b9,2f,35,01,ff,ff
+2026-10-12-09:14:03.126,3,129029,35,255,8,20,2b,0c,02,51,98,7e,d0
+2026-10-12-09:14:03.129,3,129029,35,255,8,21,13,00,e0,a6,8e,e0,5d
+2026-10-12-09:14:03.132,3,129029,35,255,8,22,3a,08,00,1a,7c,bc,62
+2026-10-12-09:14:03.135,3,129029,35,255,8,23,07,a0,02,b0,f6,da,00
+2026-10-12-09:14:03.138,3,129029,35,255,8,24,00,00,00,00,12,fd,0e
+2026-10-12-09:14:03.141,3,129029,35,255,8,25,48,00,83,00,ba,08,00
+2026-10-12-09:14:03.144,3,129029,35,255,8,26,00,00,ff,ff,ff,ff,ff
+2026-10-12-09:14:03.147,3,129033,35,255,8,02,51,98,7e,d0,13,78,00
+2026-10-12-09:14:03.150,6,129539,35,255,8,0c,d3,48,00,6c,00,ff,7f
+2026-10-12-09:14:03.153,6,129540,35,255,8,40,27,0c,fd,03,02,6e,31
+2026-10-12-09:14:03.156,6,129540,35,255,8,41,fc,c2,cc,10,ff,ff,ff
+2026-10-12-09:14:03.159,6,129540,35,255,8,42,7f,f2,07,00,0f,e8,28
+2026-10-12-09:14:03.162,6,129540,35,255,8,43,42,0e,1e,fb,ff,ff,f5
+2026-10-12-09:14:03.165,6,129540,35,255,8,44,09,ff,7f,ff,ff,ff,ff
+2026-10-12-09:14:03.168,6,129540,35,255,8,45,ff,ff,ff,7f,f0,ff,ff
+2026-10-12-09:14:03.171,6,129044,35,255,8,60,14,57,38,34,ff,00,00
+2026-10-12-09:14:03.174,6,129044,35,255,8,61,00,00,00,00,00,00,00
+2026-10-12-09:14:03.177,6,129044,35,255,8,62,00,00,00,57,38,34,ff
+";
+
+/// 2026-10-12, in days since 1970-01-01.
+const DATE: u16 = 20738;
+
+/// 09:14:03.1, the time of the fix.
+const TIME: Duration = Duration::from_millis(33_243_100);
+
+fn frames() -> impl Iterator<Item = NmeaFrame> {
+    SYNTHETIC.lines().map(|line| {
+        let fields: Vec<&str> = line.split(',').collect();
+        let int = |i: usize| fields[i].parse::<u32>().unwrap();
+        let data: heapless::Vec<u8, 8> = fields[6..]
+            .iter()
+            .map(|b| u8::from_str_radix(b, 16).unwrap())
+            .collect();
+
+        assert_eq!(data.len(), int(5) as usize);
+
+        NmeaFrame::new(
+            Id::new(int(1) as u8, int(2), int(3) as u8, int(4) as u8),
+            data,
+        )
+    })
+}
+
+/// The first message of type `T` in the frames.
+fn decode<T: Message>() -> T
+where
+    T::DecodeError: core::fmt::Debug,
+{
+    let mut decoder = Decoder::<T, 1>::new();
+
+    frames()
+        .find_map(|frame| decoder.decode(&frame))
+        .unwrap()
+        .unwrap()
+        .msg
+}
+
+fn assert_close(value: Option<impl Into<f64>>, expected: f64, tolerance: f64) {
+    let value = value.unwrap().into();
+    assert!(
+        (value - expected).abs() < tolerance,
+        "{value} is not {expected}"
+    );
+}
+
+#[test]
+fn position_rapid_update() {
+    let msg: PositionRapidUpdate = decode();
+
+    assert_close(msg.latitude, 59.288_952, 1e-9);
+    assert_close(msg.longitude, 18.915_930_5, 1e-9);
+}
+
+#[test]
+fn cog_sog_rapid_update() {
+    let msg: CogSogRapidUpdate = decode();
+
+    assert_eq!(msg.sid, Some(12));
+    assert_eq!(msg.cog_reference, DirectionReference::True);
+    assert_close(msg.cog, 1.2217, 1e-6);
+    assert_close(msg.sog, 3.09, 1e-6);
+}
+
+#[test]
+fn gnss_position_data() {
+    let msg: GnssPositionData = decode();
+
+    assert_eq!(msg.sid, Some(12));
+    assert_eq!(msg.date, Some(DATE));
+    assert_eq!(msg.time, Some(TIME));
+    assert_close(msg.latitude, 59.288_952, 1e-12);
+    assert_close(msg.longitude, 18.915_930_5, 1e-12);
+    assert_close(msg.altitude, 14.35, 1e-9);
+    assert_eq!(msg.system, GnssType::GpsGlonass);
+    assert_eq!(msg.method, GnssMethod::GnssFix);
+    assert_eq!(msg.integrity, GnssIntegrity::Safe);
+    assert_eq!(msg.satellites, Some(14));
+    assert_close(msg.hdop, 0.72, 1e-6);
+    assert_close(msg.pdop, 1.31, 1e-6);
+    assert_close(msg.geoidal_separation, 22.34, 1e-4);
+    assert!(msg.reference_stations.is_empty());
+}
+
+#[test]
+fn time_date() {
+    let msg: TimeDate = decode();
+
+    assert_eq!(msg.date, Some(DATE));
+    assert_eq!(msg.time, Some(TIME));
+    assert_eq!(msg.local_offset, Some(120));
+}
+
+#[test]
+fn gnss_dops() {
+    let msg: GnssDops = decode();
+
+    assert_eq!(msg.sid, Some(12));
+    assert_eq!(msg.desired_mode, GnssMode::Auto);
+    assert_eq!(msg.actual_mode, GnssMode::ThreeDimensional);
+    assert_close(msg.hdop, 0.72, 1e-6);
+    assert_close(msg.vdop, 1.08, 1e-6);
+    assert_eq!(msg.tdop, None);
+}
+
+#[test]
+fn gnss_sats_in_view() {
+    let msg: GnssSatsInView = decode();
+
+    assert_eq!(msg.sid, Some(12));
+    assert_eq!(
+        msg.range_residual_mode,
+        RangeResidualMode::CalculatedAfterPosition
+    );
+    assert_eq!(msg.satellites.len(), 3);
+
+    let sat = &msg.satellites[0];
+    assert_eq!(sat.prn, 2);
+    assert_close(sat.elevation, 1.2654, 1e-6);
+    assert_close(sat.azimuth, 4.9916, 1e-6);
+    assert_close(sat.snr, 43.0, 1e-6);
+    assert_eq!(sat.range_residuals, None);
+    assert_eq!(sat.status, SatelliteStatus::Used);
+
+    let sat = &msg.satellites[1];
+    assert_eq!(sat.prn, 7);
+    assert_close(sat.elevation, 0.384, 1e-6);
+    assert_close(sat.azimuth, core::f64::consts::FRAC_PI_3, 1e-4);
+    assert_close(sat.snr, 36.5, 1e-6);
+    assert_close(sat.range_residuals, -0.0125, 1e-9);
+    assert_eq!(sat.status, SatelliteStatus::UsedDiff);
+
+    let sat = &msg.satellites[2];
+    assert_eq!(sat.prn, 9);
+    assert_eq!(sat.elevation, None);
+    assert_eq!(sat.azimuth, None);
+    assert_eq!(sat.snr, None);
+    assert_eq!(sat.status, SatelliteStatus::NotTracked);
+}
+
+#[test]
+fn datum() {
+    let msg: Datum = decode();
+
+    assert_eq!(msg.local_datum, "W84");
+    assert_eq!(msg.delta_latitude, Some(0.0));
+    assert_eq!(msg.delta_longitude, Some(0.0));
+    assert_eq!(msg.delta_altitude, Some(0.0));
+    assert_eq!(msg.reference_datum, "W84");
+}
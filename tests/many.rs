@@ -4,7 +4,7 @@ use embassy_sync::{
     blocking_mutex::raw::CriticalSectionRawMutex, pubsub::PubSubChannel, signal::Signal,
     zerocopy_channel,
 };
-use nmea2000::{client::EventLoop, typenum, Buf, BufMut, Id, Message, NmeaFrame};
+use nmea2000::{typenum, Buf, BufMut, Id, Message, NmeaFrame};
 use static_cell::StaticCell;
 
 use crate::bus::{FakeCan, Frame};
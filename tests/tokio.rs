@@ -0,0 +1,348 @@
+use std::{
+    convert::Infallible,
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use embedded_can::{ExtendedId, Id};
+use nmea2000::{
+    id::DESTINATION_BROADCAST,
+    node::MIN_SRC,
+    service::switch_bank::SwitchBank,
+    tokio::AsyncCan,
+    typenum,
+    well_known::{
+        pgn_list::{PgnList, PgnListFunction},
+        switching::{BinarySwitchBankStatus, SwitchBankControl, SWITCH_BANK_SIZE},
+        IsoRequest,
+    },
+    Buf, BufMut, Message, NmeaFrame,
+};
+use tokio::sync::broadcast;
+
+#[derive(Debug, Clone)]
+struct Frame {
+    id: ExtendedId,
+    data: heapless::Vec<u8, 8>,
+}
+
+impl embedded_can::Frame for Frame {
+    fn new(id: impl Into<Id>, data: &[u8]) -> Option<Self> {
+        match id.into() {
+            Id::Extended(id) => Some(Self {
+                id,
+                data: heapless::Vec::from_slice(data).ok()?,
+            }),
+            Id::Standard(_) => None,
+        }
+    }
+
+    fn new_remote(_id: impl Into<Id>, _dlc: usize) -> Option<Self> {
+        None
+    }
+
+    fn is_extended(&self) -> bool {
+        true
+    }
+
+    fn is_remote_frame(&self) -> bool {
+        false
+    }
+
+    fn id(&self) -> Id {
+        Id::Extended(self.id)
+    }
+
+    fn dlc(&self) -> usize {
+        self.data.len()
+    }
+
+    fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+/// An in-process CAN bus. Like a real controller, a node does not receive
+/// the frames it sends itself.
+struct FakeCan {
+    node: usize,
+    tx: broadcast::Sender<(usize, Frame)>,
+    rx: broadcast::Receiver<(usize, Frame)>,
+}
+
+impl FakeCan {
+    fn new(node: usize, bus: &broadcast::Sender<(usize, Frame)>) -> Self {
+        Self {
+            node,
+            tx: bus.clone(),
+            rx: bus.subscribe(),
+        }
+    }
+}
+
+impl AsyncCan for FakeCan {
+    type Error = Infallible;
+
+    type Frame = Frame;
+
+    async fn send(&mut self, frame: Self::Frame) -> Result<(), Self::Error> {
+        let _ = self.tx.send((self.node, frame));
+        Ok(())
+    }
+
+    async fn receive(&mut self) -> Result<Self::Frame, Self::Error> {
+        loop {
+            let (node, frame) = self.rx.recv().await.unwrap();
+
+            if node != self.node {
+                return Ok(frame);
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct HelloWorld {
+    int: u64,
+}
+
+impl Message for HelloWorld {
+    const PGN: u32 = 130_816;
+
+    type EncodedLen = typenum::U8;
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_u64(self.int);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        Ok(HelloWorld {
+            int: data.get_u64(),
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct LongHello {
+    a: u64,
+    b: u64,
+}
+
+impl Message for LongHello {
+    const PGN: u32 = 130_817;
+
+    type EncodedLen = typenum::U16;
+    type DecodeError = ();
+
+    fn encode(&self, mut buf: &mut [u8]) {
+        buf.put_u64(self.a);
+        buf.put_u64(self.b);
+    }
+
+    fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {
+        Ok(LongHello {
+            a: data.get_u64(),
+            b: data.get_u64(),
+        })
+    }
+}
+
+#[tokio::test]
+async fn mock_bus() {
+    let (bus, _) = broadcast::channel(64);
+
+    let (mut alice_loop, mut alice) = nmea2000::tokio::new(0x1234_5678, FakeCan::new(0, &bus), 8);
+    let (mut bob_loop, bob) = nmea2000::tokio::new(0xdead_beef, FakeCan::new(1, &bus), 8);
+
+    let mut hellos = bob.subscribe::<HelloWorld>();
+    let mut long_hellos = bob.subscribe::<LongHello>();
+
+    let alice_src = Arc::new(AtomicU8::new(0));
+    let bob_src = Arc::new(AtomicU8::new(0));
+
+    let alice_task = tokio::spawn({
+        let src = alice_src.clone();
+        async move {
+            loop {
+                alice_loop.poll().await.unwrap();
+                src.store(alice_loop.src(), Ordering::Relaxed);
+            }
+        }
+    });
+    let bob_task = tokio::spawn({
+        let src = bob_src.clone();
+        async move {
+            loop {
+                bob_loop.poll().await.unwrap();
+                src.store(bob_loop.src(), Ordering::Relaxed);
+            }
+        }
+    });
+
+    alice
+        .send(NmeaFrame::from_message(
+            nmea2000::Id::new(4, HelloWorld::PGN, 0, DESTINATION_BROADCAST),
+            &HelloWorld { int: 37 },
+        ))
+        .await
+        .unwrap();
+    alice
+        .send_fast_packet(LongHello { a: 1, b: 2 }, 4, DESTINATION_BROADCAST)
+        .await
+        .unwrap();
+
+    let hello = hellos.recv().await.unwrap().unwrap();
+    assert_eq!(hello.msg, HelloWorld { int: 37 });
+    assert_eq!(hello.id.source(), MIN_SRC);
+    assert!(!hello.meta.local);
+
+    let long_hello = long_hellos.recv().await.unwrap().unwrap();
+    assert_eq!(long_hello.msg, LongHello { a: 1, b: 2 });
+    assert!(long_hello.meta.timestamp >= hello.meta.timestamp);
+
+    // bob has a greater name and therefore had to cede the first address
+    assert_eq!(alice_src.load(Ordering::Relaxed), MIN_SRC);
+    assert_eq!(bob_src.load(Ordering::Relaxed), MIN_SRC + 1);
+
+    alice_task.abort();
+    bob_task.abort();
+}
+
+#[tokio::test]
+async fn loopback() {
+    let (bus, _) = broadcast::channel(64);
+
+    let (mut event_loop, mut client) = nmea2000::tokio::new(0x1234_5678, FakeCan::new(0, &bus), 8);
+    event_loop.set_loopback(true);
+
+    let mut hellos = client.subscribe::<HelloWorld>();
+
+    let task = tokio::spawn(async move {
+        loop {
+            event_loop.poll().await.unwrap();
+        }
+    });
+
+    client
+        .send(NmeaFrame::from_message(
+            nmea2000::Id::new(4, HelloWorld::PGN, 0, DESTINATION_BROADCAST),
+            &HelloWorld { int: 37 },
+        ))
+        .await
+        .unwrap();
+
+    let hello = hellos.recv().await.unwrap().unwrap();
+    assert_eq!(hello.msg, HelloWorld { int: 37 });
+    assert_eq!(hello.id.source(), MIN_SRC);
+    assert!(hello.meta.local);
+
+    task.abort();
+}
+
+#[tokio::test]
+async fn pgn_lists() {
+    let (bus, _) = broadcast::channel(64);
+
+    let (mut alice_loop, mut alice) = nmea2000::tokio::new(0x1234_5678, FakeCan::new(0, &bus), 8);
+    let (mut bob_loop, mut bob) = nmea2000::tokio::new(0xdead_beef, FakeCan::new(1, &bus), 8);
+
+    let _long_hellos = alice.subscribe::<LongHello>();
+    let mut hellos = bob.subscribe::<HelloWorld>();
+    let mut lists = bob.subscribe::<PgnList>();
+
+    let alice_task = tokio::spawn(async move {
+        loop {
+            alice_loop.poll().await.unwrap();
+        }
+    });
+    let bob_task = tokio::spawn(async move {
+        loop {
+            bob_loop.poll().await.unwrap();
+        }
+    });
+
+    alice
+        .send(NmeaFrame::from_message(
+            nmea2000::Id::new(4, HelloWorld::PGN, 0, DESTINATION_BROADCAST),
+            &HelloWorld { int: 37 },
+        ))
+        .await
+        .unwrap();
+    hellos.recv().await.unwrap().unwrap();
+
+    bob.send(NmeaFrame::from_message(
+        nmea2000::Id::new(6, IsoRequest::PGN, 0, MIN_SRC),
+        &IsoRequest { pgn: PgnList::PGN },
+    ))
+    .await
+    .unwrap();
+
+    let transmit = lists.recv().await.unwrap().unwrap();
+    assert_eq!(transmit.id.source(), MIN_SRC);
+    assert_eq!(transmit.id.destination(), MIN_SRC + 1);
+    assert_eq!(transmit.msg.function, PgnListFunction::Transmit);
+    assert!(transmit.msg.pgns.contains(&HelloWorld::PGN));
+
+    let receive = lists.recv().await.unwrap().unwrap();
+    assert_eq!(receive.msg.function, PgnListFunction::Receive);
+    assert!(receive.msg.pgns.contains(&LongHello::PGN));
+
+    alice_task.abort();
+    bob_task.abort();
+}
+
+#[tokio::test]
+async fn switch_bank() {
+    let (bus, _) = broadcast::channel(64);
+
+    let (mut relay_loop, _relay) = nmea2000::tokio::new(0x1234_5678, FakeCan::new(0, &bus), 8);
+    let (mut panel_loop, mut panel) = nmea2000::tokio::new(0xdead_beef, FakeCan::new(1, &bus), 8);
+
+    let bank = Arc::new(Mutex::new(SwitchBank::<4>::new(2)));
+    relay_loop.add_service(bank.clone());
+
+    let mut statuses = panel.subscribe::<BinarySwitchBankStatus>();
+
+    let relay_task = tokio::spawn(async move {
+        loop {
+            relay_loop.poll().await.unwrap();
+        }
+    });
+    let panel_task = tokio::spawn(async move {
+        loop {
+            panel_loop.poll().await.unwrap();
+        }
+    });
+
+    // sent once the address claim has finished
+    let status = statuses.recv().await.unwrap().unwrap();
+    assert_eq!(status.msg.instance, 2);
+    assert_eq!(status.msg.indicators[1], Some(false));
+
+    let mut switches = [None; SWITCH_BANK_SIZE];
+    switches[1] = Some(true);
+    panel
+        .send(NmeaFrame::from_message(
+            nmea2000::Id::new(3, SwitchBankControl::PGN, 0, DESTINATION_BROADCAST),
+            &SwitchBankControl {
+                instance: 2,
+                switches,
+            },
+        ))
+        .await
+        .unwrap();
+
+    // the change is sent right away
+    let status = statuses.recv().await.unwrap().unwrap();
+    assert_eq!(status.msg.indicators[1], Some(true));
+    assert_eq!(
+        bank.lock().unwrap().channels(),
+        &[false, true, false, false]
+    );
+
+    relay_task.abort();
+    panel_task.abort();
+}
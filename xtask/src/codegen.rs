@@ -0,0 +1,1138 @@
+//! Turns the canboat PGN database into `Message` implementations, `lookup!`
+//! enums and `flags!` structs.
+//!
+//! Every field must be a whole number of bytes, or share a byte with its
+//! neighbours. Variable length strings may only be followed by more
+//! strings, and a repeating field set must be the last fields of a PGN.
+//! PGNs that do not fit, or that canboat marks as incomplete, are rejected
+//! and keep being written by hand.
+
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Write},
+};
+
+use serde_json::Value;
+
+/// Lookups that are generated as `Option<bool>` rather than as an enum.
+const BOOL_LOOKUPS: [&str; 2] = ["OFF_ON", "YES_NO"];
+
+/// The capacity in bytes of variable length strings, for which canboat
+/// gives no maximum.
+const LAU_STRING_LEN: u64 = 32;
+
+/// The most entries of a repeating field set that are kept, if more would
+/// fit in the message.
+const MAX_REPETITIONS: u64 = 32;
+
+/// The longest message that fits in a fast packet.
+const MAX_FAST_PACKET_LEN: u64 = 223;
+
+/// A module of `well_known` and the PGNs generated into it.
+#[derive(Debug)]
+pub struct Module {
+    pub name: &'static str,
+    /// The first paragraph of the module documentation.
+    pub doc: &'static str,
+    pub pgns: &'static [u64],
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Kind {
+    Reserved,
+    /// An unsigned integer without a unit, where all ones is N/A.
+    Int,
+    /// A scaled number.
+    Fixed {
+        signed: bool,
+        resolution: f64,
+    },
+    Duration {
+        resolution: f64,
+    },
+    Lookup(String),
+    Bool,
+    Flags(String),
+    /// A Latin-1 string padded to the field length.
+    FixedString,
+    /// A string prefixed by its length and encoding.
+    LauString,
+}
+
+#[derive(Debug)]
+struct Field {
+    order: u64,
+    ident: String,
+    doc: Option<String>,
+    kind: Kind,
+    /// Missing after variable length fields.
+    bit_offset: Option<u64>,
+    /// Zero for variable length fields.
+    bit_len: u64,
+}
+
+impl Field {
+    fn bit_start(&self) -> u64 {
+        self.bit_offset.unwrap_or(0) % 8
+    }
+
+    fn mask(&self) -> u8 {
+        ((1u16 << self.bit_len) - 1) as u8
+    }
+
+    fn is_whole_bytes(&self) -> bool {
+        self.bit_start() == 0 && self.bit_len.is_multiple_of(8)
+    }
+}
+
+/// A run of fields that occupy whole bytes: a single field, or fields
+/// sharing a byte.
+enum Slot<'a> {
+    Bytes(&'a Field),
+    Bits(Vec<&'a Field>),
+}
+
+#[derive(Debug, Default, Clone)]
+struct Imports {
+    duration: bool,
+    unsigned: bool,
+    opt_bool: bool,
+    fixed_str: bool,
+    lau_str: bool,
+}
+
+fn str_field<'a>(value: &'a Value, key: &str) -> Result<&'a str, String> {
+    value[key]
+        .as_str()
+        .ok_or_else(|| format!("missing string `{key}`"))
+}
+
+fn u64_field(value: &Value, key: &str) -> Result<u64, String> {
+    value[key]
+        .as_u64()
+        .ok_or_else(|| format!("missing integer `{key}`"))
+}
+
+fn words(s: &str) -> impl Iterator<Item = &str> {
+    s.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+}
+
+/// `camelCase`, `SNAKE_CASE` or `Some words` to `CamelCase`. Identifiers
+/// must not start with a digit, so those are prefixed with `Value`.
+fn camel_case(s: &str) -> String {
+    let mut out = String::new();
+
+    for word in words(s) {
+        let mut prev_digit = true;
+
+        for (i, c) in word.chars().enumerate() {
+            let upper = if i > 0
+                && c.is_ascii_uppercase()
+                && word.chars().any(|c| c.is_ascii_lowercase())
+            {
+                // a camelCase word boundary
+                true
+            } else {
+                prev_digit
+            };
+
+            out.push(if upper {
+                c.to_ascii_uppercase()
+            } else {
+                c.to_ascii_lowercase()
+            });
+            prev_digit = c.is_ascii_digit();
+        }
+    }
+
+    if out.starts_with(|c: char| c.is_ascii_digit()) {
+        out.insert_str(0, "Value");
+    }
+
+    out
+}
+
+/// `camelCase` or `Some words` to `snake_case`. A number at the end is a
+/// word of its own, as in `status_1`.
+fn snake_case(s: &str) -> String {
+    let camel = camel_case(s);
+    let mut out = String::new();
+
+    for (i, c) in camel.char_indices() {
+        let trailing_number = c.is_ascii_digit()
+            && out.ends_with(|c: char| c.is_ascii_alphabetic())
+            && camel[i..].chars().all(|c| c.is_ascii_digit());
+
+        if (c.is_ascii_uppercase() || trailing_number) && !out.is_empty() {
+            out.push('_');
+        }
+
+        out.push(c.to_ascii_lowercase());
+    }
+
+    if matches!(
+        out.as_str(),
+        "type" | "match" | "ref" | "mod" | "use" | "loop"
+    ) {
+        out.push('_');
+    }
+
+    out
+}
+
+/// `AC RMS Current` to `AC RMS current`: capitalized words other than the
+/// first are lowercased, acronyms are kept.
+fn sentence_case(s: &str) -> String {
+    s.split(' ')
+        .enumerate()
+        .map(|(i, w)| {
+            let capitalized = w.chars().skip(1).all(|c| !c.is_ascii_uppercase());
+
+            if i > 0 && capitalized {
+                w.to_lowercase()
+            } else {
+                w.to_owned()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn unit_name(unit: &str) -> &str {
+    match unit {
+        "A" => "amperes",
+        "V" => "volts",
+        "W" => "watts",
+        "K" => "kelvin",
+        "Pa" => "pascals",
+        "rad" => "radians",
+        "rad/s" => "radians per second",
+        "m" => "meters",
+        "m/s" => "meters per second",
+        "L" => "liters",
+        "L/h" => "liters per hour",
+        "s" => "seconds",
+        "%" => "percent",
+        unit => unit,
+    }
+}
+
+/// A float literal for `value`.
+fn float(value: f64) -> String {
+    format!("{value:?}")
+}
+
+/// The `Duration` unit for a resolution in seconds, and the number of units
+/// per step.
+fn duration_unit(resolution: f64) -> (&'static str, u64) {
+    if resolution >= 1.0 {
+        ("secs", resolution as u64)
+    } else {
+        ("millis", (resolution * 1000.0) as u64)
+    }
+}
+
+/// The length in bytes of fixed length fields.
+fn byte_len(fields: &[Field]) -> u64 {
+    fields.iter().map(|f| f.bit_len).sum::<u64>() / 8
+}
+
+struct Generator<'a> {
+    lookups: BTreeMap<&'a str, &'a Value>,
+    bit_lookups: BTreeMap<&'a str, &'a Value>,
+    used_lookups: BTreeMap<String, (&'a Value, u64)>,
+    used_flags: BTreeMap<String, (&'a Value, u64)>,
+    imports: Imports,
+}
+
+impl<'a> Generator<'a> {
+    fn field(&mut self, value: &'a Value) -> Result<Field, String> {
+        let name = str_field(value, "Name")?;
+        let id = str_field(value, "Id")?;
+        let field_type = str_field(value, "FieldType")?;
+        let bit_offset = value["BitOffset"].as_u64();
+        let bit_len = match field_type {
+            "STRING_LAU" => 0,
+            _ => u64_field(value, "BitLength")?,
+        };
+        let resolution = value["Resolution"].as_f64().unwrap_or(1.0);
+        let unit = value["Unit"].as_str();
+        let signed = value["Signed"].as_bool().unwrap_or(false);
+
+        if value["Offset"].as_f64().is_some_and(|o| o != 0.0) {
+            return Err(format!("field `{id}` has an offset"));
+        }
+
+        let mut doc = value["Description"].as_str().map(str::to_owned);
+
+        let kind = match field_type {
+            "RESERVED" | "SPARE" => Kind::Reserved,
+            "NUMBER" if unit.is_none() && resolution == 1.0 && !signed => Kind::Int,
+            "NUMBER" | "VERSION" => {
+                if let Some(unit) = unit {
+                    doc.get_or_insert_with(|| {
+                        format!("{} in {}.", sentence_case(name), unit_name(unit))
+                    });
+                }
+
+                Kind::Fixed { signed, resolution }
+            }
+            "DURATION" if unit == Some("s") && !signed => {
+                self.imports.duration = true;
+                Kind::Duration { resolution }
+            }
+            "LOOKUP" => {
+                let lookup = str_field(value, "LookupEnumeration")?;
+
+                if BOOL_LOOKUPS.contains(&lookup) && bit_len == 2 {
+                    self.imports.opt_bool = true;
+                    Kind::Bool
+                } else {
+                    let def = *self
+                        .lookups
+                        .get(lookup)
+                        .ok_or_else(|| format!("unknown lookup `{lookup}`"))?;
+                    let ty = camel_case(lookup);
+                    self.used_lookups.insert(ty.clone(), (def, bit_len));
+                    Kind::Lookup(ty)
+                }
+            }
+            "BITLOOKUP" => {
+                let lookup = str_field(value, "LookupBitEnumeration")?;
+                let def = *self
+                    .bit_lookups
+                    .get(lookup)
+                    .ok_or_else(|| format!("unknown bit lookup `{lookup}`"))?;
+                let ty = camel_case(lookup);
+                self.used_flags.insert(ty.clone(), (def, bit_len));
+                Kind::Flags(ty)
+            }
+            "STRING_FIX" => {
+                self.imports.fixed_str = true;
+                Kind::FixedString
+            }
+            "STRING_LAU" => {
+                self.imports.lau_str = true;
+                Kind::LauString
+            }
+            other => return Err(format!("field `{id}` has unsupported type {other}")),
+        };
+
+        let field = Field {
+            order: u64_field(value, "Order")?,
+            ident: snake_case(id),
+            doc,
+            kind,
+            bit_offset,
+            bit_len,
+        };
+
+        let supported = if field.is_whole_bytes() {
+            match &field.kind {
+                Kind::Reserved | Kind::FixedString | Kind::LauString => true,
+                Kind::Int | Kind::Duration { .. } => matches!(bit_len, 8 | 16 | 32),
+                Kind::Fixed { .. } => matches!(bit_len, 8 | 16 | 24 | 32),
+                Kind::Lookup(_) | Kind::Flags(_) => matches!(bit_len, 8 | 16),
+                Kind::Bool => false,
+            }
+        } else {
+            field.bit_start() + bit_len <= 8
+                && matches!(
+                    field.kind,
+                    Kind::Reserved | Kind::Int | Kind::Lookup(_) | Kind::Bool
+                )
+        };
+
+        if !supported {
+            return Err(format!("field `{id}` has an unsupported layout"));
+        }
+
+        if let Kind::Duration { resolution } = field.kind {
+            if resolution < 1.0 && (resolution * 1000.0).fract() != 0.0
+                || resolution >= 1.0 && resolution.fract() != 0.0
+            {
+                return Err(format!("field `{id}` has an unsupported resolution"));
+            }
+        }
+
+        Ok(field)
+    }
+
+    fn slots(fields: &[Field]) -> Result<Vec<Slot<'_>>, String> {
+        let mut slots = Vec::new();
+        // a repeating field set starts where the fields before it end
+        let mut offset = fields.first().and_then(|f| f.bit_offset).unwrap_or(0);
+        let mut variable = false;
+        let mut bits: Vec<&Field> = Vec::new();
+
+        for field in fields {
+            if variable {
+                if field.kind != Kind::LauString {
+                    return Err(format!(
+                        "field `{}` follows a variable length string",
+                        field.ident
+                    ));
+                }
+
+                slots.push(Slot::Bytes(field));
+                continue;
+            }
+
+            if field.bit_offset.is_some_and(|o| o != offset) {
+                return Err(format!("field `{}` is not contiguous", field.ident));
+            }
+
+            offset += field.bit_len;
+            variable = field.kind == Kind::LauString;
+
+            if field.is_whole_bytes() && bits.is_empty() {
+                slots.push(Slot::Bytes(field));
+                continue;
+            }
+
+            bits.push(field);
+
+            if offset.is_multiple_of(8) {
+                slots.push(Slot::Bits(std::mem::take(&mut bits)));
+            }
+        }
+
+        if !bits.is_empty() {
+            return Err("fields do not fill whole bytes".into());
+        }
+
+        Ok(slots)
+    }
+
+    fn rust_type(&self, field: &Field) -> String {
+        match &field.kind {
+            Kind::Reserved => unreachable!(),
+            Kind::Int if !field.is_whole_bytes() => "u8".into(),
+            Kind::Int => format!("Option<u{}>", field.bit_len),
+            Kind::Fixed { .. } if field.bit_len == 32 => "Option<f64>".into(),
+            Kind::Fixed { .. } => "Option<f32>".into(),
+            Kind::Duration { .. } => "Option<Duration>".into(),
+            Kind::Lookup(ty) | Kind::Flags(ty) => ty.clone(),
+            Kind::Bool => "Option<bool>".into(),
+            Kind::FixedString => format!("heapless::String<{}>", field.bit_len / 8),
+            Kind::LauString => format!("heapless::String<{LAU_STRING_LEN}>"),
+        }
+    }
+
+    fn decode_bytes(field: &Field) -> String {
+        let bits = field.bit_len;
+
+        match &field.kind {
+            Kind::Reserved => format!("data.advance({});", bits / 8),
+            Kind::Int => format!("let {} = data.get_opt_u{bits}();", field.ident),
+            Kind::Fixed { signed, resolution } => format!(
+                "let {} = data.get_fixed_{}{bits}({});",
+                field.ident,
+                if *signed { "i" } else { "u" },
+                float(*resolution)
+            ),
+            Kind::Duration { resolution } => {
+                let (unit, factor) = duration_unit(*resolution);
+                let value = match factor {
+                    1 => "u64::from(t)".to_owned(),
+                    factor => format!("u64::from(t) * {factor}"),
+                };
+
+                format!(
+                    "let {} = data.get_opt_u{bits}().map(|t| Duration::from_{unit}({value}));",
+                    field.ident
+                )
+            }
+            Kind::Lookup(_) | Kind::Flags(_) => {
+                format!("let {} = data.get_u{bits}().into();", field.ident)
+            }
+            Kind::FixedString => format!(
+                "let {} = get_fixed_str(&mut data, {});",
+                field.ident,
+                bits / 8
+            ),
+            Kind::LauString => format!("let {} = get_lau_str(&mut data).ok_or(())?;", field.ident),
+            Kind::Bool => unreachable!(),
+        }
+    }
+
+    fn encode_bytes(field: &Field, receiver: &str) -> String {
+        let bits = field.bit_len;
+        let value = format!("{receiver}.{}", field.ident);
+
+        match &field.kind {
+            Kind::Reserved => format!("buf.put_reserved({});", bits / 8),
+            Kind::Int => format!("buf.put_opt_u{bits}({value});"),
+            Kind::Fixed { signed, resolution } => format!(
+                "buf.put_fixed_{}{bits}({value}, {});",
+                if *signed { "i" } else { "u" },
+                float(*resolution)
+            ),
+            Kind::Duration { resolution } => {
+                let (unit, factor) = duration_unit(*resolution);
+                let raw = match factor {
+                    1 => format!("d.as_{unit}()"),
+                    factor => format!("(d.as_{unit}() / {factor})"),
+                };
+
+                format!("buf.put_opt_u{bits}({value}.map(|d| {raw} as u{bits}));")
+            }
+            Kind::Lookup(_) | Kind::Flags(_) => format!("buf.put_u{bits}({value}.into());"),
+            Kind::FixedString => format!("put_fixed_str(&mut buf, &{value}, {});", bits / 8),
+            Kind::LauString => format!("put_lau_str(&mut buf, &{value});"),
+            Kind::Bool => unreachable!(),
+        }
+    }
+
+    fn decode_bits(field: &Field) -> Option<String> {
+        let start = field.bit_start();
+        let shifted = if start == 0 {
+            "b".to_owned()
+        } else {
+            format!("(b >> {start})")
+        };
+        let masked = if start + field.bit_len == 8 {
+            shifted.clone()
+        } else {
+            format!("({shifted} & {:#04x})", field.mask())
+        };
+
+        let value = match &field.kind {
+            Kind::Reserved => return None,
+            Kind::Int => masked,
+            Kind::Lookup(_) => format!("{masked}.into()"),
+            Kind::Bool if start == 0 => "get_opt_bool(b)".to_owned(),
+            Kind::Bool => format!("get_opt_bool(b >> {start})"),
+            _ => unreachable!(),
+        };
+
+        Some(format!("let {} = {value};", field.ident))
+    }
+
+    fn encode_bits(field: &Field, receiver: &str) -> String {
+        let mask = field.mask();
+        let value = match &field.kind {
+            Kind::Reserved => return format!("{:#04x}", mask << field.bit_start()),
+            Kind::Int => format!("({receiver}.{} & {mask:#04x})", field.ident),
+            Kind::Lookup(_) => format!("(u8::from({receiver}.{}) & {mask:#04x})", field.ident),
+            Kind::Bool => format!("put_opt_bool({receiver}.{})", field.ident),
+            _ => unreachable!(),
+        };
+
+        match field.bit_start() {
+            0 => value,
+            start => format!("{value} << {start}"),
+        }
+    }
+
+    fn struct_fields(&self, fields: &[&Field], out: &mut String) {
+        for field in fields {
+            if let Some(doc) = &field.doc {
+                writeln!(out, "/// {doc}").unwrap();
+            }
+
+            writeln!(out, "pub {}: {},", field.ident, self.rust_type(field)).unwrap();
+        }
+    }
+
+    /// Encode `slots`, reading the values from `receiver`. The count of a
+    /// repeating field set is taken from `entries`.
+    fn encode_slots(slots: &[Slot], receiver: &str, count: Option<u64>, out: &mut String) {
+        for slot in slots {
+            match slot {
+                Slot::Bytes(field) if Some(field.order) == count => {
+                    out.push_str("buf.put_u8(self.entries.len() as u8);");
+                }
+                Slot::Bytes(field) => out.push_str(&Self::encode_bytes(field, receiver)),
+                Slot::Bits(fields) => {
+                    let terms: Vec<String> = fields
+                        .iter()
+                        .map(|f| Self::encode_bits(f, receiver))
+                        .collect();
+                    write!(out, "buf.put_u8({});", terms.join(" | ")).unwrap();
+                }
+            }
+
+            out.push('\n');
+        }
+    }
+
+    /// Decode `slots` into local variables. The count of a repeating field
+    /// set is decoded into `n`.
+    fn decode_slots(slots: &[Slot], count: Option<u64>, out: &mut String) {
+        for slot in slots {
+            match slot {
+                Slot::Bytes(field) if Some(field.order) == count => {
+                    out.push_str("let n = usize::from(data.get_opt_u8().unwrap_or(0));");
+                }
+                Slot::Bytes(field) => out.push_str(&Self::decode_bytes(field)),
+                Slot::Bits(fields) => {
+                    out.push_str("let b = data.get_u8();");
+
+                    for line in fields.iter().filter_map(|f| Self::decode_bits(f)) {
+                        out.push('\n');
+                        out.push_str(&line);
+                    }
+                }
+            }
+
+            out.push('\n');
+        }
+    }
+
+    fn message(&mut self, pgn: &'a Value, out: &mut String) -> Result<(), String> {
+        let number = u64_field(pgn, "PGN")?;
+        let description = str_field(pgn, "Description")?;
+        let fast = match str_field(pgn, "Type")? {
+            "Single" => false,
+            "Fast" => true,
+            other => return Err(format!("unsupported type {other}")),
+        };
+
+        if pgn["Complete"].as_bool() == Some(false) {
+            return Err("it is incomplete in canboat".into());
+        }
+
+        if pgn["RepeatingFieldSet2Size"].as_u64().unwrap_or(0) > 0 {
+            return Err("more than one repeating field set is not supported".into());
+        }
+
+        let mut fields = pgn["Fields"]
+            .as_array()
+            .ok_or("missing `Fields`")?
+            .iter()
+            .map(|f| self.field(f))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let set_len = pgn["RepeatingFieldSet1Size"].as_u64().unwrap_or(0) as usize;
+        let set = if set_len > 0 {
+            let start = u64_field(pgn, "RepeatingFieldSet1StartField")?;
+            let i = fields
+                .iter()
+                .position(|f| f.order == start)
+                .ok_or("missing start of the repeating field set")?;
+
+            if fields.len() - i != set_len {
+                return Err("fields after a repeating field set are not supported".into());
+            }
+
+            fields.split_off(i)
+        } else {
+            Vec::new()
+        };
+
+        let count = pgn["RepeatingFieldSet1CountField"].as_u64();
+        if let Some(order) = count {
+            let field = fields
+                .iter()
+                .find(|f| f.order == order)
+                .ok_or("missing count of the repeating field set")?;
+
+            if field.kind != Kind::Int || field.bit_len != 8 || !field.is_whole_bytes() {
+                return Err(format!("count field `{}` is not supported", field.ident));
+            }
+        }
+
+        let strings: Vec<&Field> = fields
+            .iter()
+            .filter(|f| f.kind == Kind::LauString)
+            .collect();
+
+        if !set.is_empty() && !strings.is_empty() {
+            return Err(
+                "a repeating field set after a variable length string is not supported".into(),
+            );
+        }
+
+        if set.iter().any(|f| f.kind == Kind::LauString) {
+            return Err(
+                "variable length strings in a repeating field set are not supported".into(),
+            );
+        }
+
+        let slots = Self::slots(&fields)?;
+        let set_slots = Self::slots(&set)?;
+        let fixed_len = byte_len(&fields);
+        let entry_len = byte_len(&set);
+        let variable = !set.is_empty() || !strings.is_empty();
+
+        let (encoded_len, padding, max_entries) = if variable {
+            let limit = if fast { MAX_FAST_PACKET_LEN } else { 8 };
+            let max_entries = limit
+                .saturating_sub(fixed_len)
+                .checked_div(entry_len)
+                .unwrap_or(0)
+                .min(MAX_REPETITIONS);
+            let len =
+                fixed_len + strings.len() as u64 * (2 + LAU_STRING_LEN) + max_entries * entry_len;
+
+            if !set.is_empty() && max_entries == 0 || len > limit {
+                return Err("the message is too long".into());
+            }
+
+            (len, 0, max_entries)
+        } else {
+            let len = u64_field(pgn, "Length")?;
+            let padding = len
+                .checked_sub(fixed_len)
+                .ok_or("the fields are longer than `Length`")?;
+
+            self.imports.unsigned = true;
+            (len, padding, 0)
+        };
+
+        let id = str_field(pgn, "Id")?;
+        let name = camel_case(id);
+        let entry = format!("{name}Entry");
+        let max_entries_name = format!("MAX_{}_ENTRIES", snake_case(id).to_uppercase());
+        let values: Vec<&Field> = fields
+            .iter()
+            .filter(|f| f.kind != Kind::Reserved && Some(f.order) != count)
+            .collect();
+        let entry_values: Vec<&Field> = set.iter().filter(|f| f.kind != Kind::Reserved).collect();
+
+        let w = |out: &mut String, args: fmt::Arguments| out.write_fmt(args).unwrap();
+        let derive = "#[derive(Debug, Clone, PartialEq)]\n\
+                      #[cfg_attr(feature = \"defmt\", derive(defmt::Format))]\n";
+
+        if !set.is_empty() {
+            w(
+                out,
+                format_args!(
+                    "\n/// The maximum number of entries in [`{name}`].\n\
+                     pub const {max_entries_name}: usize = {max_entries};\n"
+                ),
+            );
+            w(
+                out,
+                format_args!("\n/// An entry of the repeating field set of [`{name}`].\n"),
+            );
+            out.push_str(derive);
+            w(out, format_args!("pub struct {entry} {{\n"));
+            self.struct_fields(&entry_values, out);
+            out.push_str("}\n");
+        }
+
+        w(out, format_args!("\n/// PGN {number}, {description}."));
+        if fast {
+            out.push_str(" This is a fast packet.");
+        }
+        out.push('\n');
+        out.push_str(derive);
+        w(out, format_args!("pub struct {name} {{\n"));
+        self.struct_fields(&values, out);
+        if !set.is_empty() {
+            w(
+                out,
+                format_args!("pub entries: heapless::Vec<{entry}, {max_entries_name}>,\n"),
+            );
+        }
+        out.push_str("}\n\n");
+
+        // variable length messages without fixed fields need no length check
+        let fixed_len_check = variable && fixed_len > 0;
+
+        if fixed_len_check {
+            w(
+                out,
+                format_args!("impl {name} {{\nconst FIXED_LEN: usize = {fixed_len};\n}}\n\n"),
+            );
+        }
+
+        w(out, format_args!("impl Message for {name} {{\n"));
+        w(out, format_args!("const PGN: u32 = {number};\n\n"));
+        w(
+            out,
+            format_args!("type EncodedLen = typenum::U{encoded_len};\n\n"),
+        );
+        out.push_str("type DecodeError = ();\n\n");
+
+        if variable {
+            let mut terms = Vec::new();
+            if fixed_len_check {
+                terms.push("Self::FIXED_LEN".to_owned());
+            }
+            for field in &strings {
+                terms.push(format!("lau_str_len(&self.{})", field.ident));
+            }
+            if !set.is_empty() {
+                terms.push(format!("{entry_len} * self.entries.len()"));
+            }
+
+            w(
+                out,
+                format_args!(
+                    "fn encoded_len(&self) -> usize {{\n{}\n}}\n\n",
+                    terms.join(" + ")
+                ),
+            );
+        }
+
+        out.push_str("fn encode(&self, mut buf: &mut [u8]) {\n");
+        Self::encode_slots(&slots, "self", count, out);
+        if padding > 0 {
+            w(out, format_args!("buf.put_reserved({padding});\n"));
+        }
+        if !set.is_empty() {
+            out.push_str("\nfor entry in &self.entries {\n");
+            Self::encode_slots(&set_slots, "entry", None, out);
+            out.push_str("}\n");
+        }
+        out.push_str("}\n\n");
+
+        out.push_str("fn decode(mut data: &[u8]) -> Result<Self, Self::DecodeError> {\n");
+        if fixed_len_check {
+            out.push_str("if data.len() < Self::FIXED_LEN {\nreturn Err(());\n}\n\n");
+        } else if !variable {
+            out.push_str("if data.len() < Self::EncodedLen::USIZE {\nreturn Err(());\n}\n\n");
+        }
+        Self::decode_slots(&slots, count, out);
+
+        if !set.is_empty() {
+            if count.is_some() {
+                w(
+                    out,
+                    format_args!(
+                        "\nif n > {max_entries_name} || data.remaining() < {entry_len} * n {{\n\
+                         return Err(());\n\
+                         }}\n"
+                    ),
+                );
+            } else {
+                w(
+                    out,
+                    format_args!(
+                        "let n = (data.remaining() / {entry_len}).min({max_entries_name});\n"
+                    ),
+                );
+            }
+
+            out.push_str("\nlet mut entries = heapless::Vec::new();\n\nfor _ in 0..n {\n");
+            Self::decode_slots(&set_slots, None, out);
+            w(
+                out,
+                format_args!(
+                    "\n// cannot fail since n <= {max_entries_name}\n\
+                     let _ = entries.push({entry} {{\n"
+                ),
+            );
+            for field in &entry_values {
+                w(out, format_args!("{},\n", field.ident));
+            }
+            out.push_str("});\n}\n");
+        }
+
+        out.push_str("\nOk(Self {\n");
+        for field in &values {
+            w(out, format_args!("{},\n", field.ident));
+        }
+        if !set.is_empty() {
+            out.push_str("entries,\n");
+        }
+        out.push_str("})\n}\n}\n");
+
+        Ok(())
+    }
+
+    fn lookup(name: &str, def: &Value, bit_len: u64, out: &mut String) -> Result<(), String> {
+        let repr = if bit_len <= 8 { "u8" } else { "u16" };
+
+        writeln!(
+            out,
+            "\nlookup! {{\n    /// Canboat lookup `{}`.\n    pub enum {name}: {repr} {{",
+            str_field(def, "Name")?
+        )
+        .unwrap();
+
+        for value in def["EnumValues"].as_array().ok_or("missing `EnumValues`")? {
+            writeln!(
+                out,
+                "        {} = {},",
+                camel_case(str_field(value, "Name")?),
+                u64_field(value, "Value")?
+            )
+            .unwrap();
+        }
+
+        out.push_str("    }\n}\n");
+
+        Ok(())
+    }
+
+    fn flags(name: &str, def: &Value, bit_len: u64, out: &mut String) -> Result<(), String> {
+        writeln!(
+            out,
+            "\nflags! {{\n    /// Canboat bit lookup `{}`.\n    pub struct {name}: u{bit_len} {{",
+            str_field(def, "Name")?
+        )
+        .unwrap();
+
+        for value in def["EnumBitValues"]
+            .as_array()
+            .ok_or("missing `EnumBitValues`")?
+        {
+            writeln!(
+                out,
+                "        {} = {},",
+                snake_case(str_field(value, "Name")?),
+                u64_field(value, "Bit")?
+            )
+            .unwrap();
+        }
+
+        out.push_str("    }\n}\n");
+
+        Ok(())
+    }
+}
+
+fn by_name<'a>(db: &'a Value, key: &str) -> BTreeMap<&'a str, &'a Value> {
+    db[key]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|v| Some((v["Name"].as_str()?, v)))
+        .collect()
+}
+
+/// Generate `module` from a canboat database. Fails if one of its PGNs
+/// cannot be generated.
+pub fn generate(db: &Value, module: &Module) -> Result<String, String> {
+    let mut generator = Generator {
+        lookups: by_name(db, "LookupEnumerations"),
+        bit_lookups: by_name(db, "LookupBitEnumerations"),
+        used_lookups: BTreeMap::new(),
+        used_flags: BTreeMap::new(),
+        imports: Imports::default(),
+    };
+
+    let defs = db["PGNs"].as_array().ok_or("missing `PGNs`")?;
+    let mut messages = String::new();
+
+    for &number in module.pgns {
+        let mut matching = defs.iter().filter(|p| p["PGN"].as_u64() == Some(number));
+        let pgn = matching
+            .next()
+            .ok_or_else(|| format!("PGN {number} is not in the database"))?;
+
+        if matching.next().is_some() {
+            return Err(format!("PGN {number} has more than one definition"));
+        }
+
+        generator
+            .message(pgn, &mut messages)
+            .map_err(|e| format!("PGN {number}: {e}"))?;
+    }
+
+    let mut code = format!(
+        "//! {}\n//!\n\
+         //! Generated from the canboat PGN database by `cargo xtask codegen`. Do\n\
+         //! not edit this file; change `xtask/canboat.json` or the generator\n\
+         //! instead.\n\n",
+        module.doc
+    );
+
+    let imports = &generator.imports;
+
+    if imports.duration {
+        code.push_str("use core::time::Duration;\n\n");
+    }
+    if imports.unsigned {
+        code.push_str("use generic_array::typenum::{self, Unsigned};\n\n");
+    } else {
+        code.push_str("use generic_array::typenum;\n\n");
+    }
+
+    let mut helpers = Vec::new();
+    if imports.opt_bool {
+        helpers.extend(["get_opt_bool", "put_opt_bool"]);
+    }
+    if imports.fixed_str {
+        helpers.extend(["get_fixed_str", "put_fixed_str"]);
+    }
+    if imports.lau_str {
+        helpers.extend(["get_lau_str", "lau_str_len", "put_lau_str"]);
+    }
+    helpers.sort_unstable();
+
+    match helpers.as_slice() {
+        [] => {}
+        [helper] => writeln!(code, "use super::{helper};").unwrap(),
+        helpers => writeln!(code, "use super::{{{}}};", helpers.join(", ")).unwrap(),
+    }
+    code.push_str("use crate::{Buf, BufMut, Message};\n");
+
+    for (name, (def, bit_len)) in &generator.used_lookups {
+        Generator::lookup(name, def, *bit_len, &mut code)?;
+    }
+
+    for (name, (def, bit_len)) in &generator.used_flags {
+        Generator::flags(name, def, *bit_len, &mut code)?;
+    }
+
+    code.push_str(&messages);
+
+    Ok(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn module(pgns: &'static [u64]) -> Module {
+        Module {
+            name: "test",
+            doc: "Test messages.",
+            pgns,
+        }
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(camel_case("acPowerCurrentPhaseA"), "AcPowerCurrentPhaseA");
+        assert_eq!(camel_case("THRUSTER_MOTOR_TYPE"), "ThrusterMotorType");
+        assert_eq!(camel_case("To Starboard"), "ToStarboard");
+        assert_eq!(camel_case("12VDC"), "Value12Vdc");
+        assert_eq!(snake_case("acRmsCurrent"), "ac_rms_current");
+        assert_eq!(snake_case("discreteStatus1"), "discrete_status_1");
+        assert_eq!(snake_case("Warning Level 2"), "warning_level_2");
+        assert_eq!(snake_case("nmea2000Version"), "nmea2000_version");
+        assert_eq!(snake_case("type"), "type_");
+        assert_eq!(sentence_case("AC RMS Current"), "AC RMS current");
+    }
+
+    #[test]
+    fn reject_incomplete() {
+        let db = serde_json::json!({
+            "PGNs": [{
+                "PGN": 127744,
+                "Id": "acPowerCurrentPhaseA",
+                "Description": "AC Power / Current - Phase A",
+                "Type": "Single",
+                "Complete": false,
+                "Length": 8,
+                "Fields": [
+                    { "Order": 1, "Id": "sid", "Name": "SID", "BitLength": 8, "BitOffset": 0, "FieldType": "NUMBER" },
+                    { "Order": 2, "Id": "reserved", "Name": "Reserved", "BitLength": 56, "BitOffset": 8, "FieldType": "RESERVED" }
+                ]
+            }]
+        });
+
+        let err = generate(&db, &module(&[127744])).unwrap_err();
+        assert_eq!(err, "PGN 127744: it is incomplete in canboat");
+
+        let err = generate(&db, &module(&[127745])).unwrap_err();
+        assert_eq!(err, "PGN 127745 is not in the database");
+    }
+
+    #[test]
+    fn reject_unsupported() {
+        let db = serde_json::json!({
+            "PGNs": [{
+                "PGN": 129029,
+                "Id": "gnssPositionData",
+                "Description": "GNSS Position Data",
+                "Type": "Fast",
+                "Length": 51,
+                "Fields": [
+                    { "Order": 1, "Id": "sid", "Name": "SID", "BitLength": 8, "BitOffset": 0, "FieldType": "NUMBER" },
+                    { "Order": 2, "Id": "date", "Name": "Date", "BitLength": 16, "BitOffset": 8, "FieldType": "DATE" }
+                ]
+            }]
+        });
+
+        let err = generate(&db, &module(&[129029])).unwrap_err();
+        assert_eq!(err, "PGN 129029: field `date` has unsupported type DATE");
+    }
+
+    #[test]
+    fn fixed_strings() {
+        let db = serde_json::json!({
+            "PGNs": [{
+                "PGN": 126996,
+                "Id": "productInformation",
+                "Description": "Product Information",
+                "Type": "Fast",
+                "Length": 34,
+                "Fields": [
+                    { "Order": 1, "Id": "nmea2000Version", "Name": "NMEA 2000 Version", "BitLength": 16, "BitOffset": 0, "Resolution": 0.001, "FieldType": "VERSION" },
+                    { "Order": 2, "Id": "modelId", "Name": "Model ID", "BitLength": 256, "BitOffset": 16, "FieldType": "STRING_FIX" }
+                ]
+            }]
+        });
+
+        let code = generate(&db, &module(&[126996])).unwrap();
+        assert!(code.contains("pub nmea2000_version: Option<f32>,"));
+        assert!(code.contains("pub model_id: heapless::String<32>,"));
+        assert!(code.contains("type EncodedLen = typenum::U34;"));
+        assert!(code.contains("put_fixed_str(&mut buf, &self.model_id, 32);"));
+        assert!(code.contains("let model_id = get_fixed_str(&mut data, 32);"));
+    }
+
+    #[test]
+    fn strings() {
+        let db = serde_json::json!({
+            "PGNs": [{
+                "PGN": 126998,
+                "Id": "configurationInformation",
+                "Description": "Configuration Information",
+                "Type": "Fast",
+                "Fields": [
+                    { "Order": 1, "Id": "installationDescription1", "Name": "Installation Description #1", "BitLengthVariable": true, "BitOffset": 0, "FieldType": "STRING_LAU" },
+                    { "Order": 2, "Id": "installationDescription2", "Name": "Installation Description #2", "BitLengthVariable": true, "FieldType": "STRING_LAU" }
+                ]
+            }]
+        });
+
+        let code = generate(&db, &module(&[126998])).unwrap();
+        assert!(code.contains("pub installation_description_1: heapless::String<32>,"));
+        assert!(code.contains("type EncodedLen = typenum::U68;"));
+        assert!(code.contains(
+            "lau_str_len(&self.installation_description_1) \
+             + lau_str_len(&self.installation_description_2)"
+        ));
+        assert!(!code.contains("FIXED_LEN"));
+        assert!(code.contains("get_lau_str(&mut data).ok_or(())?;"));
+    }
+
+    #[test]
+    fn repeating_set() {
+        let db = serde_json::json!({
+            "LookupEnumerations": [
+                { "Name": "SATELLITE_STATUS", "MaxValue": 15, "EnumValues": [{ "Name": "Not tracked", "Value": 0 }] }
+            ],
+            "PGNs": [{
+                "PGN": 129540,
+                "Id": "gnssSatsInView",
+                "Description": "GNSS Sats in View",
+                "Type": "Fast",
+                "RepeatingFieldSet1Size": 4,
+                "RepeatingFieldSet1StartField": 3,
+                "RepeatingFieldSet1CountField": 2,
+                "Fields": [
+                    { "Order": 1, "Id": "sid", "Name": "SID", "BitLength": 8, "BitOffset": 0, "FieldType": "NUMBER" },
+                    { "Order": 2, "Id": "satsInView", "Name": "Sats in View", "BitLength": 8, "BitOffset": 8, "FieldType": "NUMBER" },
+                    { "Order": 3, "Id": "prn", "Name": "PRN", "BitLength": 8, "BitOffset": 16, "FieldType": "NUMBER" },
+                    { "Order": 4, "Id": "snr", "Name": "SNR", "BitLength": 16, "BitOffset": 24, "Unit": "dB", "Resolution": 0.01, "FieldType": "NUMBER" },
+                    { "Order": 5, "Id": "status", "Name": "PRN Usage Status", "BitLength": 4, "BitOffset": 40, "FieldType": "LOOKUP", "LookupEnumeration": "SATELLITE_STATUS" },
+                    { "Order": 6, "Id": "reserved", "Name": "Reserved", "BitLength": 4, "BitOffset": 44, "FieldType": "RESERVED" }
+                ]
+            }]
+        });
+
+        let code = generate(&db, &module(&[129540])).unwrap();
+        // 55 entries would fit in a fast packet
+        assert!(code.contains("pub const MAX_GNSS_SATS_IN_VIEW_ENTRIES: usize = 32;"));
+        assert!(code.contains("pub struct GnssSatsInViewEntry {"));
+        assert!(code.contains(
+            "pub entries: heapless::Vec<GnssSatsInViewEntry, MAX_GNSS_SATS_IN_VIEW_ENTRIES>,"
+        ));
+        assert!(!code.contains("pub sats_in_view"));
+        assert!(code.contains("type EncodedLen = typenum::U130;"));
+        assert!(code.contains("buf.put_u8(self.entries.len() as u8);"));
+        assert!(code.contains("buf.put_opt_u8(entry.prn);"));
+        assert!(code.contains("let n = usize::from(data.get_opt_u8().unwrap_or(0));"));
+        assert!(code.contains("if n > MAX_GNSS_SATS_IN_VIEW_ENTRIES || data.remaining() < 4 * n {"));
+    }
+}
@@ -0,0 +1,239 @@
+//! Development tasks, run with `cargo xtask <task>`.
+//!
+//! - `codegen [--check]`: generate the modules in [`MODULES`] from the
+//!   canboat PGN database in `xtask/canboat.json`. With `--check`, fail if
+//!   a checked-in module is out of date instead of writing it.
+//! - `import <canboat.json>`: replace `xtask/canboat.json` with the PGNs in
+//!   [`MODULES`] and the lookups they use, taken from a full copy of
+//!   canboat's `docs/canboat.json`.
+
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, ExitCode, Stdio},
+};
+
+use codegen::Module;
+use serde_json::Value;
+
+mod codegen;
+
+/// The checked-in excerpt of the canboat database.
+const DATABASE: &str = "xtask/canboat.json";
+
+/// The generated modules, written to `src/well_known/<name>.rs`.
+const MODULES: &[Module] = &[
+    Module {
+        name: "engine",
+        doc: "Engine, transmission and fuel consumption data.",
+        pgns: &[127488, 127489, 127493, 127496, 127497, 127498],
+    },
+    Module {
+        name: "thruster",
+        doc: "Bow and stern thruster control and status.",
+        pgns: &[128006, 128007, 128008],
+    },
+];
+
+/// Recorded in the excerpt written by `import`.
+const EXCERPT_COMMENT: &str = "PGN definitions of the canboat project, \
+     https://github.com/canboat/canboat, licensed under the Apache License, Version 2.0. Only \
+     the PGNs generated by `cargo xtask codegen` and the lookups they use are included. \
+     `Source` says where the definitions were taken from. Update them with `cargo xtask import \
+     <path to canboat.json>`.";
+
+/// The source of a database that does not name its own.
+const UPSTREAM_SOURCE: &str = "docs/canboat.json of https://github.com/canboat/canboat";
+
+/// Keys of the database that are copied to the excerpt as they are.
+const EXCERPT_KEYS: [&str; 4] = ["Copyright", "License", "SchemaVersion", "Version"];
+
+fn root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .to_owned()
+}
+
+fn read_json(path: &Path) -> Result<Value, String> {
+    let json = fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+
+    serde_json::from_str(&json).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+fn rustfmt(code: &str) -> Result<String, String> {
+    let mut child = Command::new("rustfmt")
+        .args(["--edition", "2021", "--emit", "stdout"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("rustfmt: {e}"))?;
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(code.as_bytes())
+        .map_err(|e| format!("rustfmt: {e}"))?;
+
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("rustfmt: {e}"))?;
+
+    if !output.status.success() {
+        return Err("rustfmt failed".into());
+    }
+
+    String::from_utf8(output.stdout).map_err(|e| format!("rustfmt: {e}"))
+}
+
+fn codegen(check: bool) -> Result<(), String> {
+    let db = read_json(&root().join(DATABASE))?;
+
+    for module in MODULES {
+        let code = rustfmt(&codegen::generate(&db, module)?)?;
+        let output = format!("src/well_known/{}.rs", module.name);
+        let path = root().join(&output);
+
+        if check {
+            let existing = fs::read_to_string(&path).unwrap_or_default();
+
+            if existing != code {
+                return Err(format!(
+                    "{output} is out of date, run `cargo xtask codegen`"
+                ));
+            }
+        } else {
+            fs::write(&path, code).map_err(|e| format!("{}: {e}", path.display()))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The PGNs in [`MODULES`] and the lookups they use.
+fn excerpt(db: &Value) -> Result<Value, String> {
+    let wanted = |pgn: &Value| {
+        MODULES
+            .iter()
+            .any(|m| pgn["PGN"].as_u64().is_some_and(|n| m.pgns.contains(&n)))
+    };
+    let pgns: Vec<&Value> = db["PGNs"]
+        .as_array()
+        .ok_or("missing `PGNs`")?
+        .iter()
+        .filter(|pgn| wanted(pgn))
+        .collect();
+
+    let used = |key: &str, lookups: &str| -> Vec<Value> {
+        let names: Vec<&str> = pgns
+            .iter()
+            .filter_map(|pgn| pgn["Fields"].as_array())
+            .flatten()
+            .filter_map(|field| field[key].as_str())
+            .collect();
+
+        db[lookups]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|l| l["Name"].as_str().is_some_and(|n| names.contains(&n)))
+            .cloned()
+            .collect()
+    };
+
+    let mut excerpt = serde_json::json!({
+        "Comment": EXCERPT_COMMENT,
+        "Source": db["Source"].as_str().unwrap_or(UPSTREAM_SOURCE),
+        "LookupEnumerations": used("LookupEnumeration", "LookupEnumerations"),
+        "LookupBitEnumerations": used("LookupBitEnumeration", "LookupBitEnumerations"),
+        "PGNs": pgns,
+    });
+
+    for key in EXCERPT_KEYS {
+        if !db[key].is_null() {
+            excerpt[key] = db[key].clone();
+        }
+    }
+
+    Ok(excerpt)
+}
+
+fn import(path: &str) -> Result<(), String> {
+    let excerpt = excerpt(&read_json(Path::new(path))?)?;
+    let json = serde_json::to_string_pretty(&excerpt).map_err(|e| e.to_string())?;
+    let path = root().join(DATABASE);
+
+    fs::write(&path, json + "\n").map_err(|e| format!("{}: {e}", path.display()))
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    let result = match args.as_slice() {
+        ["codegen"] => codegen(false),
+        ["codegen", "--check"] => codegen(true),
+        ["import", path] => import(path),
+        _ => Err("usage: cargo xtask codegen [--check] | cargo xtask import <canboat.json>".into()),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_is_up_to_date() {
+        codegen(true).unwrap();
+    }
+
+    #[test]
+    fn excerpt_is_up_to_date() {
+        let db = read_json(&root().join(DATABASE)).unwrap();
+
+        assert_eq!(excerpt(&db).unwrap(), db);
+    }
+
+    #[test]
+    fn excerpt_filters() {
+        let db = serde_json::json!({
+            "Version": "5.1.3",
+            "LookupEnumerations": [
+                { "Name": "THRUSTER_DIRECTION_CONTROL", "EnumValues": [] },
+                { "Name": "YES_NO", "EnumValues": [] }
+            ],
+            "LookupBitEnumerations": [
+                { "Name": "ENGINE_STATUS_1", "EnumBitValues": [] }
+            ],
+            "PGNs": [
+                { "PGN": 128006, "Fields": [{ "LookupEnumeration": "THRUSTER_DIRECTION_CONTROL" }] },
+                { "PGN": 127500, "Fields": [{ "LookupEnumeration": "YES_NO" }] }
+            ]
+        });
+
+        let excerpt = excerpt(&db).unwrap();
+
+        assert_eq!(excerpt["Version"], "5.1.3");
+        assert_eq!(excerpt["Source"], UPSTREAM_SOURCE);
+        assert_eq!(excerpt["PGNs"].as_array().unwrap().len(), 1);
+        assert_eq!(
+            excerpt["LookupEnumerations"][0]["Name"],
+            "THRUSTER_DIRECTION_CONTROL"
+        );
+        assert_eq!(excerpt["LookupEnumerations"].as_array().unwrap().len(), 1);
+        assert!(excerpt["LookupBitEnumerations"]
+            .as_array()
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,147 @@
+//! Round trips frames through the generated messages and checks the decoded
+//! values. The generated modules cannot hold tests of their own.
+
+use std::time::Duration;
+
+use nmea2000::{
+    typenum::Unsigned,
+    well_known::{
+        engine::{
+            EngineInstance, EngineParametersDynamic, EngineParametersRapidUpdate,
+            EngineParametersStatic, EngineStatus1, GearStatus, TransmissionParametersDynamic,
+            TripParametersEngine, TripParametersVessel,
+        },
+        thruster::{
+            ThrusterControlStatus, ThrusterDirectionControl, ThrusterMotorStatus,
+            ThrusterRetractControl,
+        },
+    },
+    Message,
+};
+
+fn round_trip<T: Message<DecodeError = ()>>(data: &[u8]) -> T {
+    let msg = T::decode(data).unwrap();
+    let mut buf = vec![0; T::EncodedLen::USIZE];
+    msg.encode(&mut buf);
+    assert_eq!(&buf[..msg.encoded_len()], data);
+
+    msg
+}
+
+#[test]
+fn engine_parameters_rapid_update() {
+    let msg: EngineParametersRapidUpdate =
+        round_trip(&[0x01, 0x20, 0x1c, 0x0e, 0x00, 0xfb, 0xff, 0xff]);
+
+    assert_eq!(msg.instance, EngineInstance::DualEngineStarboard);
+    assert_eq!(msg.speed, Some(1800.0));
+    assert_eq!(msg.boost_pressure, Some(1400.0));
+    assert_eq!(msg.tilt_trim, Some(-5.0));
+}
+
+#[test]
+fn engine_parameters_dynamic() {
+    let msg: EngineParametersDynamic = round_trip(&[
+        0x00, 0xa0, 0x0f, 0x9a, 0x0e, 0x3a, 0x8b, 0x5a, 0x05, 0x7d, 0x00, 0x60, 0xea, 0x00, 0x00,
+        0xff, 0xff, 0x2c, 0x01, 0xff, 0x42, 0x00, 0x09, 0x00, 0x4b, 0x7f,
+    ]);
+
+    assert_eq!(msg.instance, EngineInstance::SingleEngineOrDualEnginePort);
+    assert_eq!(msg.oil_pressure, Some(400_000.0));
+    assert!((msg.oil_temperature.unwrap() - 373.8).abs() < 1e-3);
+    assert!((msg.temperature.unwrap() - 356.42).abs() < 1e-3);
+    assert!((msg.alternator_potential.unwrap() - 13.7).abs() < 1e-3);
+    assert!((msg.fuel_rate.unwrap() - 12.5).abs() < 1e-3);
+    assert_eq!(msg.total_engine_hours, Some(Duration::from_secs(60_000)));
+    assert_eq!(msg.coolant_pressure, None);
+    assert_eq!(msg.fuel_pressure, Some(300_000.0));
+    assert_eq!(
+        msg.discrete_status_1,
+        EngineStatus1 {
+            over_temperature: true,
+            low_coolant_level: true,
+            ..Default::default()
+        }
+    );
+    assert!(msg.discrete_status_2.warning_level_1);
+    assert!(msg.discrete_status_2.maintenance_needed);
+    assert!(!msg.discrete_status_2.engine_shutting_down);
+    assert_eq!(msg.engine_load, Some(75.0));
+    assert_eq!(msg.engine_torque, None);
+}
+
+#[test]
+fn transmission_parameters() {
+    let msg: TransmissionParametersDynamic =
+        round_trip(&[0x01, 0xfe, 0xe8, 0x03, 0x7a, 0x0d, 0x11, 0xff]);
+
+    assert_eq!(msg.transmission_gear, GearStatus::Reverse);
+    assert_eq!(msg.oil_pressure, Some(100_000.0));
+    assert!(msg.discrete_status_1.check_temperature);
+    assert!(msg.discrete_status_1.sail_drive);
+    assert!(!msg.discrete_status_1.low_oil_level);
+}
+
+#[test]
+fn trip_parameters() {
+    let msg: TripParametersVessel = round_trip(&[
+        0x80, 0xee, 0x36, 0x00, 0x40, 0x42, 0x0f, 0x00, 0x2c, 0x01, 0xff, 0xff, 0xff, 0xff,
+    ]);
+
+    assert_eq!(msg.time_to_empty, Some(Duration::from_secs(3600)));
+    assert_eq!(msg.distance_to_empty, Some(10_000.0));
+    assert_eq!(msg.estimated_fuel_remaining, Some(300.0));
+    assert_eq!(msg.trip_run_time, None);
+
+    let msg: TripParametersEngine =
+        round_trip(&[0x01, 0x2a, 0x00, 0x7d, 0x00, 0xff, 0x7f, 0x96, 0x00]);
+
+    assert_eq!(msg.trip_fuel_used, Some(42.0));
+    assert_eq!(msg.fuel_rate_economy, None);
+    assert!((msg.instantaneous_fuel_economy.unwrap() - 15.0).abs() < 1e-3);
+}
+
+#[test]
+fn engine_parameters_static() {
+    let msg: EngineParametersStatic = round_trip(&[
+        0x00, 0xc0, 0x3a, 0x06, 0x01, 0x41, 0x42, 0x43, 0x44, 0x06, 0x01, 0x31, 0x2e, 0x32, 0x33,
+    ]);
+
+    assert_eq!(msg.rated_engine_speed, Some(3760.0));
+    assert_eq!(msg.vin, "ABCD");
+    assert_eq!(msg.software_id, "1.23");
+
+    // truncated strings
+    assert!(EngineParametersStatic::decode(&[0x00, 0xc0, 0x3a, 0x06, 0x01, 0x41]).is_err());
+    assert!(EngineParametersStatic::decode(&[0x00, 0xc0]).is_err());
+}
+
+#[test]
+fn thruster_control_status() {
+    let msg: ThrusterControlStatus = round_trip(&[0x01, 0x00, 0x12, 0x32, 0x01, 0x64, 0xff, 0xff]);
+
+    assert_eq!(msg.sid, Some(1));
+    assert_eq!(msg.identifier, Some(0));
+    assert_eq!(msg.direction_control, ThrusterDirectionControl::ToPort);
+    assert_eq!(msg.power_enabled, Some(true));
+    assert_eq!(msg.retract_control, ThrusterRetractControl::Off);
+    assert_eq!(msg.speed_control, Some(50.0));
+    assert!(msg.control_events.another_device_controlling_thruster);
+    assert!(
+        !msg.control_events
+            .boat_speed_too_fast_to_safely_use_thruster
+    );
+    assert!((msg.command_timeout.unwrap() - 0.5).abs() < 1e-6);
+    assert_eq!(msg.azimuth_control, None);
+}
+
+#[test]
+fn thruster_motor_status() {
+    let msg: ThrusterMotorStatus = round_trip(&[0xff, 0x00, 0x02, 0x0c, 0x3a, 0x73, 0xe8, 0x03]);
+
+    assert_eq!(msg.sid, None);
+    assert!(msg.motor_events.motor_over_current_cutout);
+    assert_eq!(msg.current, Some(12.0));
+    assert!((msg.temperature.unwrap() - 294.98).abs() < 1e-3);
+    assert_eq!(msg.operating_time, Some(Duration::from_secs(60_000)));
+}